@@ -60,6 +60,10 @@ pub enum Action {
     KeysUpdate,
     #[serde(rename = "keys.delete")]
     KeysDelete,
+    #[serde(rename = "tasks.cancel")]
+    TasksCancel,
+    #[serde(rename = "dumps.get")]
+    DumpsGet,
 }
 
 impl Action {
@@ -93,6 +97,8 @@ impl Action {
             KEYS_GET => Some(Self::KeysGet),
             KEYS_UPDATE => Some(Self::KeysUpdate),
             KEYS_DELETE => Some(Self::KeysDelete),
+            TASKS_CANCEL => Some(Self::TasksCancel),
+            DUMPS_GET => Some(Self::DumpsGet),
             _otherwise => None,
         }
     }
@@ -132,4 +138,6 @@ pub mod actions {
     pub const KEYS_GET: u8 = KeysGet.repr();
     pub const KEYS_UPDATE: u8 = KeysUpdate.repr();
     pub const KEYS_DELETE: u8 = KeysDelete.repr();
+    pub const TASKS_CANCEL: u8 = TasksCancel.repr();
+    pub const DUMPS_GET: u8 = DumpsGet.repr();
 }
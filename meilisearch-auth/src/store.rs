@@ -117,10 +117,10 @@ impl HeedAuthStore {
                     actions.extend([Action::SettingsGet, Action::SettingsUpdate].iter());
                 }
                 Action::DumpsAll => {
-                    actions.insert(Action::DumpsCreate);
+                    actions.extend([Action::DumpsCreate, Action::DumpsGet].iter());
                 }
                 Action::TasksAll => {
-                    actions.insert(Action::TasksGet);
+                    actions.extend([Action::TasksGet, Action::TasksCancel].iter());
                 }
                 Action::StatsAll => {
                     actions.insert(Action::StatsGet);
@@ -12,6 +12,16 @@ pub enum MeilisearchHttpError {
         .1.iter().map(|s| format!("`{}`", s)).collect::<Vec<_>>().join(", ")
     )]
     InvalidContentType(String, Vec<String>),
+    #[error("The CSV delimiter `{0}` is invalid. It must be a single ASCII character.")]
+    InvalidCsvDelimiter(char),
+    #[error("The `csvDelimiter` parameter can only be used with the `text/csv` Content-Type.")]
+    CsvDelimiterWithoutCsvPayload,
+    #[error("The CSV quote `{0}` is invalid. It must be a single ASCII character.")]
+    InvalidCsvQuote(char),
+    #[error("The `csvQuote` parameter can only be used with the `text/csv` Content-Type.")]
+    CsvQuoteWithoutCsvPayload,
+    #[error("Canceling tasks requires at least one of the `uids`, `types`, `statuses` or `indexUids` parameters.")]
+    MissingTaskCancelationFilters,
 }
 
 impl ErrorCode for MeilisearchHttpError {
@@ -19,6 +29,11 @@ impl ErrorCode for MeilisearchHttpError {
         match self {
             MeilisearchHttpError::MissingContentType(_) => Code::MissingContentType,
             MeilisearchHttpError::InvalidContentType(_, _) => Code::InvalidContentType,
+            MeilisearchHttpError::InvalidCsvDelimiter(_) => Code::InvalidCsvDelimiter,
+            MeilisearchHttpError::CsvDelimiterWithoutCsvPayload => Code::BadRequest,
+            MeilisearchHttpError::InvalidCsvQuote(_) => Code::InvalidCsvQuote,
+            MeilisearchHttpError::CsvQuoteWithoutCsvPayload => Code::BadRequest,
+            MeilisearchHttpError::MissingTaskCancelationFilters => Code::BadRequest,
         }
     }
 }
@@ -2,12 +2,14 @@ use std::env;
 use std::path::PathBuf;
 use std::sync::Arc;
 
+use actix_web::dev::ServerHandle;
 use actix_web::http::KeepAlive;
 use actix_web::HttpServer;
 use meilisearch_auth::AuthController;
 use meilisearch_http::analytics;
 use meilisearch_http::analytics::Analytics;
 use meilisearch_http::{create_app, setup_meilisearch, Opt};
+use meilisearch_lib::tasks::task::TaskEvent;
 use meilisearch_lib::MeiliSearch;
 
 #[global_allocator]
@@ -73,6 +75,7 @@ async fn run_http(
 ) -> anyhow::Result<()> {
     let _enable_dashboard = &opt.env == "development";
     let opt_clone = opt.clone();
+    let meilisearch = data.clone();
     let http_server = HttpServer::new(move || {
         create_app!(
             data,
@@ -86,17 +89,55 @@ async fn run_http(
     .disable_signals()
     .keep_alive(KeepAlive::Os);
 
-    if let Some(config) = opt.get_ssl_config()? {
-        http_server
-            .bind_rustls(opt.http_addr, config)?
-            .run()
-            .await?;
+    let server = if let Some(config) = opt.get_ssl_config()? {
+        http_server.bind_rustls(opt.http_addr, config)?.run()
     } else {
-        http_server.bind(&opt.http_addr)?.run().await?;
+        http_server.bind(&opt.http_addr)?.run()
+    };
+
+    if opt.dump_on_shutdown {
+        actix_web::rt::spawn(dump_on_shutdown(meilisearch, server.handle()));
     }
+    server.await?;
+
     Ok(())
 }
 
+/// Waits for a SIGINT or a SIGTERM, then creates a last dump before stopping the server. No new
+/// connection is accepted while the dump is created.
+async fn dump_on_shutdown(meilisearch: MeiliSearch, server: ServerHandle) {
+    shutdown_signal().await;
+    server.pause().await;
+
+    match meilisearch.shutdown().await {
+        Ok(Some(task)) if matches!(task.events.last(), Some(TaskEvent::Succeeded { .. })) => {
+            log::info!("Created the dump of task {} before shutting down.", task.id)
+        }
+        Ok(Some(task)) => log::error!("The dump of task {} failed, shutting down.", task.id),
+        Ok(None) => (),
+        Err(e) => log::error!("Could not create a dump before shutting down: {}.", e),
+    }
+
+    server.stop(true).await;
+}
+
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        if let Ok(mut terminate) = signal(SignalKind::terminate()) {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => (),
+                _ = terminate.recv() => (),
+            }
+            return;
+        }
+    }
+
+    let _ = tokio::signal::ctrl_c().await;
+}
+
 pub fn print_launch_resume(opt: &Opt, user: &str, config_read_from: Option<PathBuf>) {
     let commit_sha = option_env!("VERGEN_GIT_SHA").unwrap_or("unknown");
     let commit_date = option_env!("VERGEN_GIT_COMMIT_TIMESTAMP").unwrap_or("unknown");
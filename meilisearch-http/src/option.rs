@@ -7,7 +7,7 @@ use byte_unit::Byte;
 use clap::Parser;
 use meilisearch_lib::{
     export_to_env_if_not_present,
-    options::{IndexerOpts, SchedulerConfig},
+    options::{CompressionLevel, IndexerOpts, SchedulerConfig},
 };
 use rustls::{
     server::{
@@ -47,6 +47,8 @@ const MEILI_IMPORT_DUMP: &str = "MEILI_IMPORT_DUMP";
 const MEILI_IGNORE_MISSING_DUMP: &str = "MEILI_IGNORE_MISSING_DUMP";
 const MEILI_IGNORE_DUMP_IF_DB_EXISTS: &str = "MEILI_IGNORE_DUMP_IF_DB_EXISTS";
 const MEILI_DUMPS_DIR: &str = "MEILI_DUMPS_DIR";
+const MEILI_DUMP_COMPRESSION: &str = "MEILI_DUMP_COMPRESSION";
+const MEILI_DUMP_ON_SHUTDOWN: &str = "MEILI_DUMP_ON_SHUTDOWN";
 const MEILI_LOG_LEVEL: &str = "MEILI_LOG_LEVEL";
 #[cfg(feature = "metrics")]
 const MEILI_ENABLE_METRICS_ROUTE: &str = "MEILI_ENABLE_METRICS_ROUTE";
@@ -221,6 +223,18 @@ pub struct Opt {
     #[serde(default = "default_dumps_dir")]
     pub dumps_dir: PathBuf,
 
+    /// Sets how the dumps are compressed: `gzip`, or `zstd` which is faster, optionally with a
+    /// level between 1 and 22 as in `zstd:1`. Dumps of any compression can be imported.
+    #[clap(long, env = MEILI_DUMP_COMPRESSION, default_value_t)]
+    #[serde(default)]
+    pub dump_compression: CompressionLevel,
+
+    /// Creates a last dump when Meilisearch receives a SIGINT or a SIGTERM, and waits for it
+    /// before shutting down. The new requests are refused in the meantime.
+    #[clap(long, env = MEILI_DUMP_ON_SHUTDOWN)]
+    #[serde(default)]
+    pub dump_on_shutdown: bool,
+
     /// Defines how much detail should be present in Meilisearch's logs.
     ///
     /// Meilisearch currently supports five log levels, listed in order of increasing verbosity: ERROR, WARN, INFO, DEBUG, TRACE.
@@ -314,6 +328,8 @@ impl Opt {
             schedule_snapshot,
             snapshot_interval_sec,
             dumps_dir,
+            dump_compression,
+            dump_on_shutdown,
             log_level,
             indexer_options,
             scheduler_options,
@@ -367,6 +383,8 @@ impl Opt {
             snapshot_interval_sec.to_string(),
         );
         export_to_env_if_not_present(MEILI_DUMPS_DIR, dumps_dir);
+        export_to_env_if_not_present(MEILI_DUMP_COMPRESSION, dump_compression.to_string());
+        export_to_env_if_not_present(MEILI_DUMP_ON_SHUTDOWN, dump_on_shutdown.to_string());
         export_to_env_if_not_present(MEILI_LOG_LEVEL, log_level);
         #[cfg(feature = "metrics")]
         {
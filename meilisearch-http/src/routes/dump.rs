@@ -1,27 +1,153 @@
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
 use actix_web::{web, HttpRequest, HttpResponse};
+use bytes::Bytes;
+use futures::Stream;
 use log::debug;
+use meilisearch_lib::index_controller::DumpInfo;
 use meilisearch_lib::MeiliSearch;
-use meilisearch_types::error::ResponseError;
+use meilisearch_types::error::{Code, ResponseError};
+use serde::{Deserialize, Serialize};
 use serde_json::json;
+use time::OffsetDateTime;
+use tokio::io::AsyncReadExt;
 
 use crate::analytics::Analytics;
+use crate::error::PayloadError;
 use crate::extractors::authentication::{policies::*, GuardedData};
 use crate::extractors::sequential_extractor::SeqHandler;
-use crate::task::SummarizedTaskView;
+use crate::task::{SummarizedTaskView, TaskView};
+
+/// The size of the chunks a dump file is streamed by.
+const DUMP_CHUNK_SIZE: usize = 64 * 1024;
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
-    cfg.service(web::resource("").route(web::post().to(SeqHandler(create_dump))));
+    cfg.service(
+        web::resource("")
+            .route(web::get().to(SeqHandler(list_dumps)))
+            .route(web::post().to(SeqHandler(create_dump))),
+    )
+    .service(web::resource("/{dump_uid}").route(web::get().to(SeqHandler(get_dump))))
+    .service(web::resource("/{dump_uid}/status").route(web::get().to(SeqHandler(get_dump_status))));
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct CreateDumpRequest {
+    /// Only dumps these indexes, and the tasks targeting them.
+    indexes: Option<Vec<String>>,
 }
 
 pub async fn create_dump(
     meilisearch: GuardedData<ActionPolicy<{ actions::DUMPS_CREATE }>, MeiliSearch>,
+    body: Bytes,
     req: HttpRequest,
     analytics: web::Data<dyn Analytics>,
 ) -> Result<HttpResponse, ResponseError> {
-    analytics.publish("Dump Created".to_string(), json!({}), Some(&req));
+    // the body is optional, a dump of every index is created without it.
+    let body: CreateDumpRequest = if body.is_empty() {
+        CreateDumpRequest::default()
+    } else {
+        serde_json::from_slice::<Option<_>>(&body)
+            .map_err(PayloadError::MalformedPayload)?
+            .unwrap_or_default()
+    };
+
+    analytics.publish(
+        "Dump Created".to_string(),
+        json!({ "partial": body.indexes.is_some() }),
+        Some(&req),
+    );
 
-    let res: SummarizedTaskView = meilisearch.register_dump_task().await?.into();
+    let res: SummarizedTaskView = meilisearch.register_dump_task(body.indexes).await?.into();
 
     debug!("returns: {:?}", res);
     Ok(HttpResponse::Accepted().json(res))
 }
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DumpView {
+    uid: String,
+    #[serde(serialize_with = "time::serde::rfc3339::serialize")]
+    created_at: OffsetDateTime,
+    size: u64,
+}
+
+impl From<DumpInfo> for DumpView {
+    fn from(info: DumpInfo) -> Self {
+        Self {
+            uid: info.uid,
+            created_at: info.created_at,
+            size: info.size,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct DumpListView {
+    results: Vec<DumpView>,
+}
+
+pub async fn list_dumps(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DUMPS_GET }>, MeiliSearch>,
+) -> Result<HttpResponse, ResponseError> {
+    let results = meilisearch
+        .list_dumps()
+        .await?
+        .into_iter()
+        .map(DumpView::from)
+        .collect();
+    let res = DumpListView { results };
+
+    debug!("returns: {:?}", res);
+    Ok(HttpResponse::Ok().json(res))
+}
+
+pub async fn get_dump(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DUMPS_GET }>, MeiliSearch>,
+    dump_uid: web::Path<String>,
+) -> Result<HttpResponse, ResponseError> {
+    let dump_uid = dump_uid.into_inner();
+    let file = meilisearch.open_dump(&dump_uid).await?;
+
+    debug!("returns: dump {}", dump_uid);
+    Ok(HttpResponse::Ok()
+        .content_type("application/gzip")
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(format!("{}.dump", dump_uid))],
+        })
+        .streaming(file_to_stream(file)))
+}
+
+pub async fn get_dump_status(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DUMPS_GET }>, MeiliSearch>,
+    dump_uid: web::Path<String>,
+) -> Result<HttpResponse, ResponseError> {
+    let dump_uid = dump_uid.into_inner();
+    let task: TaskView = match meilisearch.dump_task_status(&dump_uid).await? {
+        Some(task) => task.into(),
+        None => {
+            return Err(ResponseError::from_msg(
+                format!("Dump `{}` not found.", dump_uid),
+                Code::DumpNotFound,
+            ))
+        }
+    };
+
+    debug!("returns: {:?}", task);
+    Ok(HttpResponse::Ok().json(task))
+}
+
+fn file_to_stream(mut file: tokio::fs::File) -> impl Stream<Item = Result<Bytes, std::io::Error>> {
+    async_stream::try_stream! {
+        let mut buffer = vec![0; DUMP_CHUNK_SIZE];
+        loop {
+            let read = file.read(&mut buffer).await?;
+            if read == 0 {
+                break;
+            }
+            yield Bytes::copy_from_slice(&buffer[..read]);
+        }
+    }
+}
@@ -15,7 +15,7 @@ use mime::Mime;
 use once_cell::sync::Lazy;
 use serde::Deserialize;
 use serde_cs::vec::CS;
-use serde_json::Value;
+use serde_json::{json, Value};
 use tokio::sync::mpsc;
 
 use crate::analytics::Analytics;
@@ -31,6 +31,7 @@ static ACCEPTED_CONTENT_TYPE: Lazy<Vec<String>> = Lazy::new(|| {
         "application/json".to_string(),
         "application/x-ndjson".to_string(),
         "text/csv".to_string(),
+        "text/tab-separated-values".to_string(),
     ]
 });
 
@@ -79,6 +80,7 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
     )
     // this route needs to be before the /documents/{document_id} to match properly
     .service(web::resource("/delete-batch").route(web::post().to(SeqHandler(delete_documents))))
+    .service(web::resource("/fetch").route(web::post().to(SeqHandler(fetch_documents))))
     .service(
         web::resource("/{document_id}")
             .route(web::get().to(SeqHandler(get_document)))
@@ -109,6 +111,39 @@ pub async fn get_document(
     Ok(HttpResponse::Ok().json(document))
 }
 
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct FetchDocuments {
+    ids: Vec<Value>,
+    fields: Option<Vec<StarOr<String>>>,
+}
+
+pub async fn fetch_documents(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_GET }>, MeiliSearch>,
+    path: web::Path<String>,
+    body: web::Json<FetchDocuments>,
+) -> Result<HttpResponse, ResponseError> {
+    debug!("called with params: {:?}", body);
+    let FetchDocuments { ids, fields } = body.into_inner();
+    let ids = ids
+        .iter()
+        .map(|v| {
+            v.as_str()
+                .map(String::from)
+                .unwrap_or_else(|| v.to_string())
+        })
+        .collect();
+    let attributes_to_retrieve = fields.and_then(fold_star_or);
+
+    let documents = meilisearch
+        .documents_by_ids(path.into_inner(), ids, attributes_to_retrieve)
+        .await?;
+    let ret = json!({ "results": documents });
+
+    debug!("returns: {:?}", ret);
+    Ok(HttpResponse::Ok().json(ret))
+}
+
 pub async fn delete_document(
     meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_DELETE }>, MeiliSearch>,
     path: web::Path<DocumentParam>,
@@ -118,7 +153,11 @@ pub async fn delete_document(
         index_uid,
     } = path.into_inner();
     let update = Update::DeleteDocuments(vec![document_id]);
-    let task: SummarizedTaskView = meilisearch.register_update(index_uid, update).await?.into();
+    let task: SummarizedTaskView = meilisearch
+        .register_update(index_uid, update)
+        .await?
+        .0
+        .into();
     debug!("returns: {:?}", task);
     Ok(HttpResponse::Accepted().json(task))
 }
@@ -130,6 +169,11 @@ pub struct BrowseQuery {
     offset: usize,
     #[serde(default = "crate::routes::PAGINATION_DEFAULT_LIMIT")]
     limit: usize,
+    /// The internal id of the first document to return, as given by the `next` of the previous
+    /// page.
+    from: Option<u32>,
+    /// Only return the documents matching this filter, on the filterable attributes.
+    filter: Option<String>,
     fields: Option<CS<StarOr<String>>>,
 }
 
@@ -142,15 +186,24 @@ pub async fn get_all_documents(
     let BrowseQuery {
         limit,
         offset,
+        from,
+        filter,
         fields,
     } = params.into_inner();
     let attributes_to_retrieve = fields.and_then(fold_star_or);
 
-    let (total, documents) = meilisearch
-        .documents(path.into_inner(), offset, limit, attributes_to_retrieve)
+    let (total, documents, next) = meilisearch
+        .documents(
+            path.into_inner(),
+            offset,
+            limit,
+            from,
+            filter,
+            attributes_to_retrieve,
+        )
         .await?;
 
-    let ret = PaginationView::new(offset, limit, total as usize, documents);
+    let ret = PaginationView::new(offset, limit, total as usize, documents).with_next(next);
 
     debug!("returns: {:?}", ret);
     Ok(HttpResponse::Ok().json(ret))
@@ -160,6 +213,17 @@ pub async fn get_all_documents(
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct UpdateDocumentsQuery {
     pub primary_key: Option<String>,
+    /// Whether the first row of a CSV payload holds the field names, `true` by default.
+    pub csv_has_header: Option<bool>,
+    /// The field delimiter of a CSV payload, `,` by default.
+    pub csv_delimiter: Option<char>,
+    /// The character quoting the fields of a CSV payload, `"` by default.
+    pub csv_quote: Option<char>,
+    /// Only parse and check the payload, without registering any task.
+    pub validate_only: Option<bool>,
+    /// Set to `false` to only add the documents to an existing index. The index is never created
+    /// when the key isn't allowed to create indexes.
+    pub allow_index_creation: Option<bool>,
 }
 
 pub async fn add_documents(
@@ -180,19 +244,18 @@ pub async fn add_documents(
         &req,
     );
 
-    let allow_index_creation = meilisearch.filters().allow_index_creation;
-    let task = document_addition(
+    let allow_index_creation =
+        meilisearch.filters().allow_index_creation && params.allow_index_creation.unwrap_or(true);
+    document_addition(
         extract_mime_type(&req)?,
         meilisearch,
         index_uid,
-        params.primary_key,
+        params,
         body,
         IndexDocumentsMethod::ReplaceDocuments,
         allow_index_creation,
     )
-    .await?;
-
-    Ok(HttpResponse::Accepted().json(task))
+    .await
 }
 
 pub async fn update_documents(
@@ -204,6 +267,7 @@ pub async fn update_documents(
     analytics: web::Data<dyn Analytics>,
 ) -> Result<HttpResponse, ResponseError> {
     debug!("called with params: {:?}", params);
+    let params = params.into_inner();
     let index_uid = path.into_inner();
 
     analytics.update_documents(
@@ -212,37 +276,55 @@ pub async fn update_documents(
         &req,
     );
 
-    let allow_index_creation = meilisearch.filters().allow_index_creation;
-    let task = document_addition(
+    let allow_index_creation =
+        meilisearch.filters().allow_index_creation && params.allow_index_creation.unwrap_or(true);
+    document_addition(
         extract_mime_type(&req)?,
         meilisearch,
         index_uid,
-        params.into_inner().primary_key,
+        params,
         body,
         IndexDocumentsMethod::UpdateDocuments,
         allow_index_creation,
     )
-    .await?;
-
-    Ok(HttpResponse::Accepted().json(task))
+    .await
 }
 
 async fn document_addition(
     mime_type: Option<Mime>,
     meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_ADD }>, MeiliSearch>,
     index_uid: String,
-    primary_key: Option<String>,
+    params: UpdateDocumentsQuery,
     body: Payload,
     method: IndexDocumentsMethod,
     allow_index_creation: bool,
-) -> Result<SummarizedTaskView, ResponseError> {
+) -> Result<HttpResponse, ResponseError> {
     let format = match mime_type
         .as_ref()
         .map(|m| (m.type_().as_str(), m.subtype().as_str()))
     {
         Some(("application", "json")) => DocumentAdditionFormat::Json,
         Some(("application", "x-ndjson")) => DocumentAdditionFormat::Ndjson,
-        Some(("text", "csv")) => DocumentAdditionFormat::Csv,
+        Some(("text", "csv")) => {
+            let delimiter = match params.csv_delimiter {
+                Some(c) if c.is_ascii() => c as u8,
+                Some(c) => return Err(MeilisearchHttpError::InvalidCsvDelimiter(c).into()),
+                None => b',',
+            };
+            let quote = match params.csv_quote {
+                Some(c) if c.is_ascii() => c as u8,
+                Some(c) => return Err(MeilisearchHttpError::InvalidCsvQuote(c).into()),
+                None => b'"',
+            };
+            DocumentAdditionFormat::Csv {
+                delimiter,
+                quote,
+                has_header: params.csv_has_header.unwrap_or(true),
+            }
+        }
+        Some(("text", "tab-separated-values")) => DocumentAdditionFormat::Tsv {
+            has_header: params.csv_has_header.unwrap_or(true),
+        },
         Some((type_, subtype)) => {
             return Err(MeilisearchHttpError::InvalidContentType(
                 format!("{}/{}", type_, subtype),
@@ -257,18 +339,41 @@ async fn document_addition(
         }
     };
 
+    if params.csv_delimiter.is_some() && !matches!(format, DocumentAdditionFormat::Csv { .. }) {
+        return Err(MeilisearchHttpError::CsvDelimiterWithoutCsvPayload.into());
+    }
+    if params.csv_quote.is_some() && !matches!(format, DocumentAdditionFormat::Csv { .. }) {
+        return Err(MeilisearchHttpError::CsvQuoteWithoutCsvPayload.into());
+    }
+
+    if params.validate_only.unwrap_or(false) {
+        let validation = meilisearch
+            .validate_documents(
+                index_uid,
+                Box::new(payload_to_stream(body)),
+                format,
+                params.primary_key,
+            )
+            .await?;
+
+        debug!("returns: {:?}", validation);
+        return Ok(HttpResponse::Ok().json(validation));
+    }
+
     let update = Update::DocumentAddition {
         payload: Box::new(payload_to_stream(body)),
-        primary_key,
+        primary_key: params.primary_key,
         method,
         format,
         allow_index_creation,
     };
 
-    let task = meilisearch.register_update(index_uid, update).await?.into();
+    let (task, _) = meilisearch.register_update(index_uid, update).await?;
+    let enqueue_position = meilisearch.enqueue_position(task.id).await?;
+    let task = SummarizedTaskView::from(task).with_enqueue_position(enqueue_position);
 
     debug!("returns: {:?}", task);
-    Ok(task)
+    Ok(HttpResponse::Accepted().json(task))
 }
 
 pub async fn delete_documents(
@@ -290,20 +395,32 @@ pub async fn delete_documents(
     let task: SummarizedTaskView = meilisearch
         .register_update(path.into_inner(), update)
         .await?
+        .0
         .into();
 
     debug!("returns: {:?}", task);
     Ok(HttpResponse::Accepted().json(task))
 }
 
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ClearDocumentsQuery {
+    filter: Option<String>,
+}
+
 pub async fn clear_all_documents(
     meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_DELETE }>, MeiliSearch>,
     path: web::Path<String>,
+    params: web::Query<ClearDocumentsQuery>,
 ) -> Result<HttpResponse, ResponseError> {
-    let update = Update::ClearDocuments;
+    let update = match params.into_inner().filter {
+        Some(filter) => Update::ClearDocumentsMatching { filter },
+        None => Update::ClearDocuments,
+    };
     let task: SummarizedTaskView = meilisearch
         .register_update(path.into_inner(), update)
         .await?
+        .0
         .into();
 
     debug!("returns: {:?}", task);
@@ -44,14 +44,15 @@ pub async fn list_indexes(
     paginate: web::Query<Pagination>,
 ) -> Result<HttpResponse, ResponseError> {
     let search_rules = &data.filters().search_rules;
-    let indexes: Vec<_> = data.list_indexes().await?;
-    let nb_indexes = indexes.len();
-    let iter = indexes
-        .into_iter()
-        .filter(|i| search_rules.is_index_authorized(&i.uid));
-    let ret = paginate
-        .into_inner()
-        .auto_paginate_unsized(nb_indexes, iter);
+    let paginate = paginate.into_inner();
+    let (total, indexes) = data
+        .list_indexes_paginated(
+            |uid| search_rules.is_index_authorized(uid),
+            paginate.offset,
+            paginate.limit,
+        )
+        .await?;
+    let ret = paginate.format_with(total, indexes);
 
     debug!("returns: {:?}", ret);
     Ok(HttpResponse::Ok().json(ret))
@@ -83,7 +84,7 @@ pub async fn create_index(
         );
 
         let update = Update::CreateIndex { primary_key };
-        let task: SummarizedTaskView = meilisearch.register_update(uid, update).await?.into();
+        let task: SummarizedTaskView = meilisearch.register_update(uid, update).await?.0.into();
 
         Ok(HttpResponse::Accepted().json(task))
     } else {
@@ -143,6 +144,7 @@ pub async fn update_index(
     let task: SummarizedTaskView = meilisearch
         .register_update(path.into_inner(), update)
         .await?
+        .0
         .into();
 
     debug!("returns: {:?}", task);
@@ -155,7 +157,7 @@ pub async fn delete_index(
 ) -> Result<HttpResponse, ResponseError> {
     let uid = path.into_inner();
     let update = Update::DeleteIndex;
-    let task: SummarizedTaskView = meilisearch.register_update(uid, update).await?.into();
+    let task: SummarizedTaskView = meilisearch.register_update(uid, update).await?.0.into();
 
     Ok(HttpResponse::Accepted().json(task))
 }
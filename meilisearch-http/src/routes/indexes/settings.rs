@@ -45,6 +45,7 @@ macro_rules! make_setting_route {
                 let task: SummarizedTaskView = meilisearch
                     .register_update(index_uid.into_inner(), update)
                     .await?
+                    .0
                     .into();
 
                 debug!("returns: {:?}", task);
@@ -79,6 +80,7 @@ macro_rules! make_setting_route {
                 let task: SummarizedTaskView = meilisearch
                     .register_update(index_uid.into_inner(), update)
                     .await?
+                    .0
                     .into();
 
                 debug!("returns: {:?}", task);
@@ -436,6 +438,7 @@ pub async fn update_all(
     let task: SummarizedTaskView = meilisearch
         .register_update(index_uid.into_inner(), update)
         .await?
+        .0
         .into();
 
     debug!("returns: {:?}", task);
@@ -466,6 +469,7 @@ pub async fn delete_all(
     let task: SummarizedTaskView = data
         .register_update(index_uid.into_inner(), update)
         .await?
+        .0
         .into();
 
     debug!("returns: {:?}", task);
@@ -55,11 +55,19 @@ pub struct Pagination {
 }
 
 #[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct PaginationView<T> {
     pub results: Vec<T>,
     pub offset: usize,
     pub limit: usize,
     pub total: usize,
+    /// The offset the results actually start at, never greater than `total`.
+    pub effective_offset: usize,
+    /// Whether the requested `offset` is past the last element.
+    pub offset_out_of_range: bool,
+    /// The cursor to fetch the page following this one, for the routes supporting it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<u32>,
 }
 
 impl Pagination {
@@ -103,12 +111,7 @@ impl Pagination {
     where
         T: Serialize,
     {
-        PaginationView {
-            results,
-            offset: self.offset,
-            limit: self.limit,
-            total,
-        }
+        PaginationView::new(self.offset, self.limit, total, results)
     }
 }
 
@@ -119,8 +122,15 @@ impl<T> PaginationView<T> {
             limit,
             results,
             total,
+            effective_offset: offset.min(total),
+            offset_out_of_range: offset > 0 && offset >= total,
+            next: None,
         }
     }
+
+    pub fn with_next(self, next: Option<u32>) -> Self {
+        Self { next, ..self }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
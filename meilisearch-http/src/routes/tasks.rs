@@ -1,18 +1,30 @@
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+
+use actix_web::http::header::{CacheControl, CacheDirective};
 use actix_web::{web, HttpRequest, HttpResponse};
-use meilisearch_lib::tasks::task::{TaskContent, TaskEvent, TaskId};
+use bytes::Bytes;
+use futures::Stream;
+use meilisearch_auth::SearchRules;
+use meilisearch_lib::tasks::task::{Task, TaskEvent, TaskId};
 use meilisearch_lib::tasks::TaskFilter;
 use meilisearch_lib::MeiliSearch;
 use meilisearch_types::error::ResponseError;
 use meilisearch_types::index_uid::IndexUid;
 use meilisearch_types::star_or::StarOr;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_cs::vec::CS;
 use serde_json::json;
+use time::OffsetDateTime;
+use tokio::sync::broadcast::{self, error::RecvError};
 
 use crate::analytics::Analytics;
+use crate::error::MeilisearchHttpError;
 use crate::extractors::authentication::{policies::*, GuardedData};
 use crate::extractors::sequential_extractor::SeqHandler;
-use crate::task::{TaskListView, TaskStatus, TaskType, TaskView};
+use crate::task::{
+    SummarizedTaskView, TaskListView, TaskStatsView, TaskStatus, TaskType, TaskView,
+};
 
 use super::fold_star_or;
 
@@ -20,46 +32,201 @@ const DEFAULT_LIMIT: fn() -> usize = || 20;
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(web::resource("").route(web::get().to(SeqHandler(get_tasks))))
+        .service(web::resource("/cancel").route(web::post().to(SeqHandler(cancel_tasks))))
+        .service(web::resource("/stats").route(web::get().to(SeqHandler(get_task_stats))))
+        .service(web::resource("/stream").route(web::get().to(SeqHandler(stream_tasks))))
         .service(web::resource("/{task_id}").route(web::get().to(SeqHandler(get_task))));
 }
 
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct TasksFilterQuery {
-    #[serde(rename = "type")]
+    #[serde(rename = "type", alias = "types")]
     type_: Option<CS<StarOr<TaskType>>>,
+    #[serde(alias = "statuses")]
     status: Option<CS<StarOr<TaskStatus>>>,
+    #[serde(alias = "indexUids")]
     index_uid: Option<CS<StarOr<IndexUid>>>,
+    uids: Option<CS<TaskId>>,
+    has_error: Option<bool>,
     #[serde(default = "DEFAULT_LIMIT")]
     limit: usize,
     from: Option<TaskId>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    before_enqueued_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    after_enqueued_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    before_started_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    after_started_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    before_finished_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    after_finished_at: Option<OffsetDateTime>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct CancelTasksQuery {
+    #[serde(rename = "type", alias = "types")]
+    type_: Option<CS<StarOr<TaskType>>>,
+    #[serde(alias = "statuses")]
+    status: Option<CS<StarOr<TaskStatus>>>,
+    #[serde(alias = "indexUids")]
+    index_uid: Option<CS<StarOr<IndexUid>>>,
+    uids: Option<CS<TaskId>>,
+    /// Why the tasks are canceled, recorded on each canceled task.
+    reason: Option<String>,
+}
+
+/// The date bounds of a tasks query. All the bounds are exclusive.
+#[derive(Debug, Default)]
+struct TaskDateFilters {
+    before_enqueued_at: Option<OffsetDateTime>,
+    after_enqueued_at: Option<OffsetDateTime>,
+    before_started_at: Option<OffsetDateTime>,
+    after_started_at: Option<OffsetDateTime>,
+    before_finished_at: Option<OffsetDateTime>,
+    after_finished_at: Option<OffsetDateTime>,
+}
+
+impl TaskDateFilters {
+    fn is_empty(&self) -> bool {
+        self.before_enqueued_at.is_none()
+            && self.after_enqueued_at.is_none()
+            && self.before_started_at.is_none()
+            && self.after_started_at.is_none()
+            && self.before_finished_at.is_none()
+            && self.after_finished_at.is_none()
+    }
+
+    /// Returns whether the dates of the task described by `events` are within the bounds. A task
+    /// that has not started (resp. finished) yet never matches a started (resp. finished) bound.
+    fn matches_events(&self, events: &[TaskEvent]) -> bool {
+        let enqueued_at = events.iter().find_map(|e| match e {
+            TaskEvent::Created(ts) => Some(*ts),
+            _ => None,
+        });
+        let started_at = events.iter().find_map(|e| match e {
+            TaskEvent::Processing(ts) => Some(*ts),
+            _ => None,
+        });
+        let finished_at = events.iter().find_map(|e| match e {
+            TaskEvent::Succeeded { timestamp, .. }
+            | TaskEvent::Failed { timestamp, .. }
+            | TaskEvent::Canceled { timestamp, .. } => Some(*timestamp),
+            _ => None,
+        });
+
+        date_in_bounds(enqueued_at, self.after_enqueued_at, self.before_enqueued_at)
+            && date_in_bounds(started_at, self.after_started_at, self.before_started_at)
+            && date_in_bounds(finished_at, self.after_finished_at, self.before_finished_at)
+    }
 }
 
-#[rustfmt::skip]
-fn task_type_matches_content(type_: &TaskType, content: &TaskContent) -> bool {
-    matches!((type_, content),
-          (TaskType::IndexCreation, TaskContent::IndexCreation { .. })
-        | (TaskType::IndexUpdate, TaskContent::IndexUpdate { .. })
-        | (TaskType::IndexDeletion, TaskContent::IndexDeletion { .. })
-        | (TaskType::DocumentAdditionOrUpdate, TaskContent::DocumentAddition { .. })
-        | (TaskType::DocumentDeletion, TaskContent::DocumentDeletion{ .. })
-        | (TaskType::SettingsUpdate, TaskContent::SettingsUpdate { .. })
-        | (TaskType::DumpCreation, TaskContent::Dump { .. })
-    )
-}
-
-#[rustfmt::skip]
-fn task_status_matches_events(status: &TaskStatus, events: &[TaskEvent]) -> bool {
-    events.last().map_or(false, |event| {
-        matches!((status, event),
-              (TaskStatus::Enqueued, TaskEvent::Created(_))
-            | (TaskStatus::Processing, TaskEvent::Processing(_) | TaskEvent::Batched { .. })
-            | (TaskStatus::Succeeded, TaskEvent::Succeeded { .. })
-            | (TaskStatus::Failed, TaskEvent::Failed { .. }),
-        )
+fn date_in_bounds(
+    date: Option<OffsetDateTime>,
+    after: Option<OffsetDateTime>,
+    before: Option<OffsetDateTime>,
+) -> bool {
+    if after.is_none() && before.is_none() {
+        return true;
+    }
+
+    date.map_or(false, |date| {
+        after.map_or(true, |after| date > after) && before.map_or(true, |before| date < before)
     })
 }
 
+/// Returns whether the task described by `events` failed with an error.
+fn task_has_error(events: &[TaskEvent]) -> bool {
+    matches!(events.last(), Some(TaskEvent::Failed { .. }))
+}
+
+/// Builds the filter of a tasks query, restricted to the indexes allowed by the search rules.
+fn tasks_filter(
+    search_rules: &SearchRules,
+    type_: Option<Vec<TaskType>>,
+    status: Option<Vec<TaskStatus>>,
+    index_uid: Option<Vec<IndexUid>>,
+    uids: Option<Vec<TaskId>>,
+    has_error: Option<bool>,
+    dates: TaskDateFilters,
+) -> Option<TaskFilter> {
+    // We filter on potential indexes and make sure that the search filter
+    // restrictions are also applied.
+    let indexes_filters = match index_uid {
+        Some(indexes) => {
+            let mut filters = TaskFilter::default();
+            for name in indexes {
+                if search_rules.is_index_authorized(&name) {
+                    filters.filter_index(name.to_string());
+                }
+            }
+            Some(filters)
+        }
+        None => {
+            if search_rules.is_index_authorized("*") {
+                None
+            } else {
+                let mut filters = TaskFilter::default();
+                for (index, _policy) in search_rules.clone() {
+                    filters.filter_index(index);
+                }
+                Some(filters)
+            }
+        }
+    };
+
+    // The uids are matched against the ids of the tasks, without loading them.
+    let indexes_filters = match uids {
+        Some(uids) => {
+            let mut filters = indexes_filters.unwrap_or_default();
+            filters.filter_uids(uids);
+            Some(filters)
+        }
+        None => indexes_filters,
+    };
+
+    // The types and statuses are matched against the mappings of the store too.
+    let indexes_filters = match type_ {
+        Some(types) => {
+            let mut filters = indexes_filters.unwrap_or_default();
+            for type_ in types {
+                filters.filter_kind(type_.kind());
+            }
+            Some(filters)
+        }
+        None => indexes_filters,
+    };
+
+    let indexes_filters = match status {
+        Some(statuses) => {
+            let mut filters = indexes_filters.unwrap_or_default();
+            for status in statuses {
+                filters.filter_status(status.into());
+            }
+            Some(filters)
+        }
+        None => indexes_filters,
+    };
+
+    // Then we complete the task filter with other potential error and dates filters.
+    if has_error.is_some() || !dates.is_empty() {
+        let mut filters = indexes_filters.unwrap_or_default();
+        filters.filter_fn(Box::new(move |task| {
+            let matches_error =
+                has_error.map_or(true, |has_error| task_has_error(&task.events) == has_error);
+
+            matches_error && dates.matches_events(&task.events)
+        }));
+        Some(filters)
+    } else {
+        indexes_filters
+    }
+}
+
 async fn get_tasks(
     meilisearch: GuardedData<ActionPolicy<{ actions::TASKS_GET }>, MeiliSearch>,
     params: web::Query<TasksFilterQuery>,
@@ -70,10 +237,27 @@ async fn get_tasks(
         type_,
         status,
         index_uid,
+        uids,
+        has_error,
         limit,
         from,
+        before_enqueued_at,
+        after_enqueued_at,
+        before_started_at,
+        after_started_at,
+        before_finished_at,
+        after_finished_at,
     } = params.into_inner();
 
+    let dates = TaskDateFilters {
+        before_enqueued_at,
+        after_enqueued_at,
+        before_started_at,
+        after_started_at,
+        before_finished_at,
+        after_finished_at,
+    };
+
     let search_rules = &meilisearch.filters().search_rules;
 
     // We first transform a potential indexUid=* into a "not specified indexUid filter"
@@ -81,6 +265,7 @@ async fn get_tasks(
     let type_: Option<Vec<_>> = type_.and_then(fold_star_or);
     let status: Option<Vec<_>> = status.and_then(fold_star_or);
     let index_uid: Option<Vec<_>> = index_uid.and_then(fold_star_or);
+    let uids: Option<Vec<TaskId>> = uids.map(|uids| uids.into_iter().collect());
 
     analytics.publish(
         "Tasks Seen".to_string(),
@@ -88,88 +273,132 @@ async fn get_tasks(
             "filtered_by_index_uid": index_uid.as_ref().map_or(false, |v| !v.is_empty()),
             "filtered_by_type": type_.as_ref().map_or(false, |v| !v.is_empty()),
             "filtered_by_status": status.as_ref().map_or(false, |v| !v.is_empty()),
+            "filtered_by_date": !dates.is_empty(),
+            "filtered_by_uid": uids.as_ref().map_or(false, |v| !v.is_empty()),
+            "filtered_by_error": has_error.is_some(),
         }),
         Some(&req),
     );
 
-    // Then we filter on potential indexes and make sure that the search filter
-    // restrictions are also applied.
-    let indexes_filters = match index_uid {
-        Some(indexes) => {
-            let mut filters = TaskFilter::default();
-            for name in indexes {
-                if search_rules.is_index_authorized(&name) {
-                    filters.filter_index(name.to_string());
-                }
+    let filters = tasks_filter(
+        search_rules,
+        type_,
+        status,
+        index_uid,
+        uids,
+        has_error,
+        dates,
+    );
+
+    // The total is computed along with the page so they are always consistent with each other.
+    let tasks: TaskListView = meilisearch
+        .list_tasks_paginated(filters, limit, from)
+        .await?
+        .into();
+
+    Ok(HttpResponse::Ok().json(tasks))
+}
+
+async fn cancel_tasks(
+    meilisearch: GuardedData<ActionPolicy<{ actions::TASKS_CANCEL }>, MeiliSearch>,
+    params: web::Query<CancelTasksQuery>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let CancelTasksQuery {
+        type_,
+        status,
+        index_uid,
+        uids,
+        reason,
+    } = params.into_inner();
+
+    let by_query = type_.is_some() || status.is_some() || index_uid.is_some();
+    let type_: Option<Vec<_>> = type_.and_then(fold_star_or);
+    let status: Option<Vec<_>> = status.and_then(fold_star_or);
+    let index_uid: Option<Vec<_>> = index_uid.and_then(fold_star_or);
+    let uids: Option<Vec<TaskId>> = uids.map(|uids| uids.into_iter().collect());
+
+    analytics.publish(
+        "Tasks Canceled".to_string(),
+        json!({
+            "number_of_tasks": uids.as_ref().map_or(0, |v| v.len()),
+            "filtered_by_index_uid": index_uid.as_ref().map_or(false, |v| !v.is_empty()),
+            "filtered_by_type": type_.as_ref().map_or(false, |v| !v.is_empty()),
+            "filtered_by_status": status.as_ref().map_or(false, |v| !v.is_empty()),
+        }),
+        Some(&req),
+    );
+
+    let search_rules = &meilisearch.filters().search_rules;
+    let task = match uids {
+        // Without any other filter, the tasks are explicitly canceled by uid.
+        Some(uids) if !by_query => {
+            // Make sure that the tasks exist and that they are visible with this key.
+            for uid in &uids {
+                let filters = if search_rules.is_index_authorized("*") {
+                    None
+                } else {
+                    let mut filters = TaskFilter::default();
+                    for (index, _policy) in search_rules.clone() {
+                        filters.filter_index(index);
+                    }
+                    Some(filters)
+                };
+
+                meilisearch.get_task(*uid, filters).await?;
             }
-            Some(filters)
+
+            meilisearch.register_cancelation_task(uids, reason).await?
         }
-        None => {
-            if search_rules.is_index_authorized("*") {
-                None
-            } else {
-                let mut filters = TaskFilter::default();
-                for (index, _policy) in search_rules.clone() {
-                    filters.filter_index(index);
-                }
-                Some(filters)
-            }
+        None if !by_query => return Err(MeilisearchHttpError::MissingTaskCancelationFilters.into()),
+        uids => {
+            let filters = tasks_filter(
+                search_rules,
+                type_,
+                status,
+                index_uid,
+                uids,
+                None,
+                TaskDateFilters::default(),
+            );
+            meilisearch
+                .register_cancelation_task_matching(filters, reason)
+                .await?
         }
     };
 
-    // Then we complete the task filter with other potential status and types filters.
-    let filters = if type_.is_some() || status.is_some() {
-        let mut filters = indexes_filters.unwrap_or_default();
-        filters.filter_fn(Box::new(move |task| {
-            let matches_type = match &type_ {
-                Some(types) => types
-                    .iter()
-                    .any(|t| task_type_matches_content(t, &task.content)),
-                None => true,
-            };
-
-            let matches_status = match &status {
-                Some(statuses) => statuses
-                    .iter()
-                    .any(|t| task_status_matches_events(t, &task.events)),
-                None => true,
-            };
-
-            matches_type && matches_status
-        }));
-        Some(filters)
-    } else {
-        indexes_filters
-    };
+    let task: SummarizedTaskView = task.into();
+
+    Ok(HttpResponse::Accepted().json(task))
+}
 
-    // We +1 just to know if there is more after this "page" or not.
-    let limit = limit.saturating_add(1);
+async fn get_task_stats(
+    meilisearch: GuardedData<ActionPolicy<{ actions::TASKS_GET }>, MeiliSearch>,
+) -> Result<HttpResponse, ResponseError> {
+    // Only the tasks of the indexes authorized by the key are counted.
+    let search_rules = &meilisearch.filters().search_rules;
+    let filters = tasks_filter(
+        search_rules,
+        None,
+        None,
+        None,
+        None,
+        None,
+        TaskDateFilters::default(),
+    );
 
-    let mut tasks_results: Vec<_> = meilisearch
-        .list_tasks(filters, Some(limit), from)
+    let counts: BTreeMap<_, _> = meilisearch
+        .count_tasks_by_status_and_kind(filters)
         .await?
         .into_iter()
-        .map(TaskView::from)
+        .filter_map(|((status, kind), count)| {
+            let task_type = TaskType::from_kind(kind)?;
+            Some(((TaskStatus::from(status), task_type), count))
+        })
         .collect();
 
-    // If we were able to fetch the number +1 tasks we asked
-    // it means that there is more to come.
-    let next = if tasks_results.len() == limit {
-        tasks_results.pop().map(|t| t.uid)
-    } else {
-        None
-    };
-
-    let from = tasks_results.first().map(|t| t.uid);
-
-    let tasks = TaskListView {
-        results: tasks_results,
-        limit: limit.saturating_sub(1),
-        from,
-        next,
-    };
-
-    Ok(HttpResponse::Ok().json(tasks))
+    Ok(HttpResponse::Ok().json(TaskStatsView::from(counts)))
 }
 
 async fn get_task(
@@ -202,3 +431,118 @@ async fn get_task(
 
     Ok(HttpResponse::Ok().json(task))
 }
+
+/// Streams the tasks as Server-Sent Events: a `snapshot` event with the tasks that are not
+/// finished yet, then a `task` event every time a task is registered or changes state.
+async fn stream_tasks(
+    meilisearch: GuardedData<ActionPolicy<{ actions::TASKS_GET }>, MeiliSearch>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    analytics.publish("Tasks Streamed".to_string(), json!({}), Some(&req));
+
+    let search_rules = meilisearch.filters().search_rules.clone();
+    let (unfinished, events) = meilisearch.subscribe_tasks().await?;
+    let is_authorized = move |task: &Task| {
+        search_rules.is_index_authorized("*")
+            || task
+                .index_uid()
+                .map_or(false, |index| search_rules.is_index_authorized(index))
+    };
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(CacheControl(vec![CacheDirective::NoCache]))
+        .streaming(task_events_stream(unfinished, events, is_authorized)))
+}
+
+/// The stream ends when the scheduler stops. When the client disconnects the stream is dropped,
+/// and so is its subscription. A client that is too slow to keep up receives a `lagged` event
+/// with the number of task events it missed.
+fn task_events_stream(
+    unfinished: Vec<Task>,
+    mut events: broadcast::Receiver<Task>,
+    is_authorized: impl Fn(&Task) -> bool + 'static,
+) -> impl Stream<Item = Result<Bytes, Infallible>> {
+    async_stream::stream! {
+        let snapshot: Vec<TaskView> = unfinished
+            .into_iter()
+            .filter(|task| is_authorized(task))
+            .map(TaskView::from)
+            .collect();
+        yield Ok(sse_event("snapshot", &snapshot));
+
+        loop {
+            match events.recv().await {
+                Ok(task) if is_authorized(&task) => {
+                    yield Ok(sse_event("task", &TaskView::from(task)));
+                }
+                Ok(_) => (),
+                Err(RecvError::Lagged(missed)) => {
+                    yield Ok(sse_event("lagged", &json!({ "missedEvents": missed })));
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    }
+}
+
+fn sse_event(event: &str, data: &impl Serialize) -> Bytes {
+    let data = serde_json::to_string(data).expect("a task view is always serializable");
+    Bytes::from(format!("event: {}\ndata: {}\n\n", event, data))
+}
+
+#[cfg(test)]
+mod test {
+    use futures::StreamExt;
+    use meilisearch_lib::tasks::task::{TaskContent, TaskResult};
+
+    use super::*;
+
+    fn index_deletion(id: TaskId) -> Task {
+        Task {
+            id,
+            content: TaskContent::IndexDeletion {
+                index_uid: IndexUid::new_unchecked("doggos"),
+            },
+            events: vec![TaskEvent::Created(OffsetDateTime::now_utc())],
+            retry_of: None,
+            priority: 0,
+        }
+    }
+
+    #[actix_rt::test]
+    async fn stream_an_event_when_a_task_completes() {
+        let (sender, receiver) = broadcast::channel(16);
+        let stream = task_events_stream(vec![index_deletion(0)], receiver, |_| true);
+
+        let mut task = index_deletion(0);
+        task.push_event(TaskEvent::succeeded(TaskResult::Other));
+        sender.send(task).unwrap();
+        drop(sender);
+
+        let events: Vec<_> = stream.map(|event| event.unwrap()).collect().await;
+        assert_eq!(events.len(), 2);
+
+        let snapshot = std::str::from_utf8(&events[0]).unwrap();
+        assert!(snapshot.starts_with("event: snapshot\ndata: [{"));
+        assert!(snapshot.contains(r#""status":"enqueued""#));
+
+        let completed = std::str::from_utf8(&events[1]).unwrap();
+        assert!(completed.starts_with("event: task\ndata: {"));
+        assert!(completed.contains(r#""uid":0"#));
+        assert!(completed.contains(r#""status":"succeeded""#));
+        assert!(completed.ends_with("\n\n"));
+    }
+
+    #[actix_rt::test]
+    async fn never_stream_unauthorized_tasks() {
+        let (sender, receiver) = broadcast::channel(16);
+        let stream = task_events_stream(vec![index_deletion(0)], receiver, |_| false);
+        sender.send(index_deletion(1)).unwrap();
+        drop(sender);
+
+        let events: Vec<_> = stream.map(|event| event.unwrap()).collect().await;
+        assert_eq!(events, vec![Bytes::from("event: snapshot\ndata: []\n\n")]);
+    }
+}
@@ -1,17 +1,19 @@
+use std::collections::BTreeMap;
 use std::error::Error;
 use std::fmt::{self, Write};
 use std::str::FromStr;
 use std::write;
 
 use meilisearch_lib::index::{Settings, Unchecked};
+use meilisearch_lib::tasks::batch::BatchId;
 use meilisearch_lib::tasks::task::{
-    DocumentDeletion, Task, TaskContent, TaskEvent, TaskId, TaskResult,
+    self, DocumentDeletion, Task, TaskContent, TaskEvent, TaskId, TaskList, TaskResult,
 };
 use meilisearch_types::error::ResponseError;
 use serde::{Deserialize, Serialize, Serializer};
 use time::{Duration, OffsetDateTime};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum TaskType {
     IndexCreation,
@@ -21,10 +23,18 @@ pub enum TaskType {
     DocumentDeletion,
     SettingsUpdate,
     DumpCreation,
+    DocumentsExport,
+    TaskCancelation,
 }
 
 impl From<TaskContent> for TaskType {
     fn from(other: TaskContent) -> Self {
+        Self::from(&other)
+    }
+}
+
+impl From<&TaskContent> for TaskType {
+    fn from(other: &TaskContent) -> Self {
         match other {
             TaskContent::IndexCreation { .. } => TaskType::IndexCreation,
             TaskContent::IndexUpdate { .. } => TaskType::IndexUpdate,
@@ -33,6 +43,41 @@ impl From<TaskContent> for TaskType {
             TaskContent::DocumentDeletion { .. } => TaskType::DocumentDeletion,
             TaskContent::SettingsUpdate { .. } => TaskType::SettingsUpdate,
             TaskContent::Dump { .. } => TaskType::DumpCreation,
+            TaskContent::DocumentsExport { .. } => TaskType::DocumentsExport,
+            TaskContent::TaskCancelation { .. } => TaskType::TaskCancelation,
+        }
+    }
+}
+
+impl TaskType {
+    /// Returns the kind of the content of the tasks of this type, see `TaskContent::kind`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            TaskType::IndexCreation => "IndexCreation",
+            TaskType::IndexUpdate => "IndexUpdate",
+            TaskType::IndexDeletion => "IndexDeletion",
+            TaskType::DocumentAdditionOrUpdate => "DocumentAddition",
+            TaskType::DocumentDeletion => "DocumentDeletion",
+            TaskType::SettingsUpdate => "SettingsUpdate",
+            TaskType::DumpCreation => "Dump",
+            TaskType::DocumentsExport => "DocumentsExport",
+            TaskType::TaskCancelation => "TaskCancelation",
+        }
+    }
+
+    /// Returns the type of the tasks whose content is of the given kind, see `TaskContent::kind`.
+    pub fn from_kind(kind: &str) -> Option<Self> {
+        match kind {
+            "IndexCreation" => Some(TaskType::IndexCreation),
+            "IndexUpdate" => Some(TaskType::IndexUpdate),
+            "IndexDeletion" => Some(TaskType::IndexDeletion),
+            "DocumentAddition" => Some(TaskType::DocumentAdditionOrUpdate),
+            "DocumentDeletion" => Some(TaskType::DocumentDeletion),
+            "SettingsUpdate" => Some(TaskType::SettingsUpdate),
+            "Dump" => Some(TaskType::DumpCreation),
+            "DocumentsExport" => Some(TaskType::DocumentsExport),
+            "TaskCancelation" => Some(TaskType::TaskCancelation),
+            _ => None,
         }
     }
 }
@@ -48,7 +93,7 @@ impl fmt::Display for TaskTypeError {
             f,
             "invalid task type `{}`, expecting one of: \
             indexCreation, indexUpdate, indexDeletion, documentAdditionOrUpdate, \
-            documentDeletion, settingsUpdate, dumpCreation",
+            documentDeletion, settingsUpdate, dumpCreation, documentsExport, taskCancelation",
             self.invalid_type
         )
     }
@@ -74,6 +119,10 @@ impl FromStr for TaskType {
             Ok(TaskType::SettingsUpdate)
         } else if type_.eq_ignore_ascii_case("dumpCreation") {
             Ok(TaskType::DumpCreation)
+        } else if type_.eq_ignore_ascii_case("documentsExport") {
+            Ok(TaskType::DocumentsExport)
+        } else if type_.eq_ignore_ascii_case("taskCancelation") {
+            Ok(TaskType::TaskCancelation)
         } else {
             Err(TaskTypeError {
                 invalid_type: type_.to_string(),
@@ -82,13 +131,51 @@ impl FromStr for TaskType {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum TaskStatus {
     Enqueued,
     Processing,
     Succeeded,
     Failed,
+    Canceled,
+}
+
+impl TaskStatus {
+    /// Returns the status of the task described by `events`, as shown in its `TaskView`.
+    pub fn from_events(events: &[TaskEvent]) -> Self {
+        match events.last() {
+            Some(TaskEvent::Created(_) | TaskEvent::Batched { .. }) | None => TaskStatus::Enqueued,
+            Some(TaskEvent::Processing(_)) => TaskStatus::Processing,
+            Some(TaskEvent::Succeeded { .. }) => TaskStatus::Succeeded,
+            Some(TaskEvent::Failed { .. }) => TaskStatus::Failed,
+            Some(TaskEvent::Canceled { .. }) => TaskStatus::Canceled,
+        }
+    }
+}
+
+impl From<task::TaskStatus> for TaskStatus {
+    fn from(status: task::TaskStatus) -> Self {
+        match status {
+            task::TaskStatus::Enqueued => TaskStatus::Enqueued,
+            task::TaskStatus::Processing => TaskStatus::Processing,
+            task::TaskStatus::Succeeded => TaskStatus::Succeeded,
+            task::TaskStatus::Failed => TaskStatus::Failed,
+            task::TaskStatus::Canceled => TaskStatus::Canceled,
+        }
+    }
+}
+
+impl From<TaskStatus> for task::TaskStatus {
+    fn from(status: TaskStatus) -> Self {
+        match status {
+            TaskStatus::Enqueued => task::TaskStatus::Enqueued,
+            TaskStatus::Processing => task::TaskStatus::Processing,
+            TaskStatus::Succeeded => task::TaskStatus::Succeeded,
+            TaskStatus::Failed => task::TaskStatus::Failed,
+            TaskStatus::Canceled => task::TaskStatus::Canceled,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -101,7 +188,7 @@ impl fmt::Display for TaskStatusError {
         write!(
             f,
             "invalid task status `{}`, expecting one of: \
-            enqueued, processing, succeeded, or failed",
+            enqueued, processing, succeeded, failed, or canceled",
             self.invalid_status,
         )
     }
@@ -121,6 +208,8 @@ impl FromStr for TaskStatus {
             Ok(TaskStatus::Succeeded)
         } else if status.eq_ignore_ascii_case("failed") {
             Ok(TaskStatus::Failed)
+        } else if status.eq_ignore_ascii_case("canceled") {
+            Ok(TaskStatus::Canceled)
         } else {
             Err(TaskStatusError {
                 invalid_status: status.to_string(),
@@ -137,6 +226,9 @@ enum TaskDetails {
     DocumentAddition {
         received_documents: usize,
         indexed_documents: Option<u64>,
+        throughput_docs_per_sec: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        inferred_primary_key: Option<String>,
     },
     #[serde(rename_all = "camelCase")]
     Settings {
@@ -153,7 +245,40 @@ enum TaskDetails {
     #[serde(rename_all = "camelCase")]
     ClearAll { deleted_documents: Option<u64> },
     #[serde(rename_all = "camelCase")]
-    Dump { dump_uid: String },
+    ClearMatching {
+        filter: String,
+        deleted_documents: Option<u64>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Dump {
+        dump_uid: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        indexes: Option<Vec<String>>,
+        index_count: Option<u64>,
+        total_documents: Option<u64>,
+    },
+    #[serde(rename_all = "camelCase")]
+    DocumentsExport {
+        filter: Option<String>,
+        exported_documents: Option<u64>,
+    },
+    #[serde(rename_all = "camelCase")]
+    TaskCancelation {
+        matched_tasks: usize,
+        canceled_tasks: Option<u64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
+}
+
+/// Returns the number of documents indexed per second, or `None` if no document was indexed or
+/// if the duration is not positive.
+fn throughput(indexed_documents: u64, duration: Duration) -> Option<f64> {
+    if indexed_documents == 0 || !duration.is_positive() {
+        return None;
+    }
+
+    Some(indexed_documents as f64 / duration.as_seconds_f64())
 }
 
 /// Serialize a `time::Duration` as a best effort ISO 8601 while waiting for
@@ -219,6 +344,16 @@ pub struct TaskView {
     details: Option<TaskDetails>,
     #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<ResponseError>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    canceled_by: Option<TaskId>,
+    /// Why the task was canceled, as given when canceling it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cancelation_reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retry_of: Option<TaskId>,
+    /// The id of the batch the task was processed in, shared by all the tasks of the batch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    batch_uid: Option<BatchId>,
     #[serde(serialize_with = "serialize_duration")]
     duration: Option<Duration>,
     #[serde(serialize_with = "time::serde::rfc3339::serialize")]
@@ -236,6 +371,8 @@ impl From<Task> for TaskView {
             id,
             content,
             events,
+            retry_of,
+            priority: _,
         } = task;
 
         let (task_type, mut details) = match content {
@@ -245,6 +382,8 @@ impl From<Task> for TaskView {
                 let details = TaskDetails::DocumentAddition {
                     received_documents: documents_count,
                     indexed_documents: None,
+                    throughput_docs_per_sec: None,
+                    inferred_primary_key: None,
                 };
 
                 (TaskType::DocumentAdditionOrUpdate, Some(details))
@@ -268,6 +407,16 @@ impl From<Task> for TaskView {
                     deleted_documents: None,
                 }),
             ),
+            TaskContent::DocumentDeletion {
+                deletion: DocumentDeletion::Filter(filter),
+                ..
+            } => (
+                TaskType::DocumentDeletion,
+                Some(TaskDetails::ClearMatching {
+                    filter,
+                    deleted_documents: None,
+                }),
+            ),
             TaskContent::IndexDeletion { .. } => (
                 TaskType::IndexDeletion,
                 Some(TaskDetails::ClearAll {
@@ -286,9 +435,29 @@ impl From<Task> for TaskView {
                 TaskType::IndexUpdate,
                 Some(TaskDetails::IndexInfo { primary_key }),
             ),
-            TaskContent::Dump { uid } => (
+            TaskContent::Dump { uid, indexes } => (
                 TaskType::DumpCreation,
-                Some(TaskDetails::Dump { dump_uid: uid }),
+                Some(TaskDetails::Dump {
+                    dump_uid: uid,
+                    indexes,
+                    index_count: None,
+                    total_documents: None,
+                }),
+            ),
+            TaskContent::DocumentsExport { filter, .. } => (
+                TaskType::DocumentsExport,
+                Some(TaskDetails::DocumentsExport {
+                    filter,
+                    exported_documents: None,
+                }),
+            ),
+            TaskContent::TaskCancelation { tasks, reason } => (
+                TaskType::TaskCancelation,
+                Some(TaskDetails::TaskCancelation {
+                    matched_tasks: tasks.len(),
+                    canceled_tasks: None,
+                    reason,
+                }),
             ),
         };
 
@@ -302,14 +471,16 @@ impl From<Task> for TaskView {
                     (
                         TaskResult::DocumentAddition {
                             indexed_documents: num,
-                            ..
+                            inferred_primary_key: inferred,
                         },
                         Some(TaskDetails::DocumentAddition {
                             ref mut indexed_documents,
+                            ref mut inferred_primary_key,
                             ..
                         }),
                     ) => {
                         indexed_documents.replace(*num);
+                        *inferred_primary_key = inferred.clone();
                     }
                     (
                         TaskResult::DocumentDeletion {
@@ -327,12 +498,54 @@ impl From<Task> for TaskView {
                         TaskResult::ClearAll {
                             deleted_documents: docs,
                         },
-                        Some(TaskDetails::ClearAll {
-                            ref mut deleted_documents,
-                        }),
+                        Some(
+                            TaskDetails::ClearAll {
+                                ref mut deleted_documents,
+                            }
+                            | TaskDetails::ClearMatching {
+                                ref mut deleted_documents,
+                                ..
+                            },
+                        ),
                     ) => {
                         deleted_documents.replace(*docs);
                     }
+                    (
+                        TaskResult::DocumentsExport {
+                            exported_documents: docs,
+                        },
+                        Some(TaskDetails::DocumentsExport {
+                            ref mut exported_documents,
+                            ..
+                        }),
+                    ) => {
+                        exported_documents.replace(*docs);
+                    }
+                    (
+                        TaskResult::TaskCancelation {
+                            canceled_tasks: tasks,
+                        },
+                        Some(TaskDetails::TaskCancelation {
+                            ref mut canceled_tasks,
+                            ..
+                        }),
+                    ) => {
+                        canceled_tasks.replace(*tasks);
+                    }
+                    (
+                        TaskResult::Dump {
+                            index_count: indexes,
+                            total_documents: documents,
+                        },
+                        Some(TaskDetails::Dump {
+                            ref mut index_count,
+                            ref mut total_documents,
+                            ..
+                        }),
+                    ) => {
+                        index_count.replace(*indexes);
+                        total_documents.replace(*documents);
+                    }
                     _ => (),
                 }
                 (TaskStatus::Succeeded, None, Some(*timestamp))
@@ -345,10 +558,16 @@ impl From<Task> for TaskView {
                     }) => {
                         deleted_documents.replace(0);
                     }
-                    Some(TaskDetails::ClearAll {
-                        ref mut deleted_documents,
-                        ..
-                    }) => {
+                    Some(
+                        TaskDetails::ClearAll {
+                            ref mut deleted_documents,
+                            ..
+                        }
+                        | TaskDetails::ClearMatching {
+                            ref mut deleted_documents,
+                            ..
+                        },
+                    ) => {
                         deleted_documents.replace(0);
                     }
                     Some(TaskDetails::DocumentAddition {
@@ -357,12 +576,36 @@ impl From<Task> for TaskView {
                     }) => {
                         indexed_documents.replace(0);
                     }
+                    Some(TaskDetails::DocumentsExport {
+                        ref mut exported_documents,
+                        ..
+                    }) => {
+                        exported_documents.replace(0);
+                    }
                     _ => (),
                 }
                 (TaskStatus::Failed, Some(error.clone()), Some(*timestamp))
             }
+            TaskEvent::Canceled { timestamp, .. } => (TaskStatus::Canceled, None, Some(*timestamp)),
         };
 
+        let (canceled_by, cancelation_reason) = events
+            .iter()
+            .find_map(|e| match e {
+                TaskEvent::Canceled {
+                    canceled_by,
+                    reason,
+                    ..
+                } => Some((Some(*canceled_by), reason.clone())),
+                _ => None,
+            })
+            .unwrap_or_default();
+
+        let batch_uid = events.iter().find_map(|e| match e {
+            TaskEvent::Batched { batch_id, .. } => Some(*batch_id),
+            _ => None,
+        });
+
         let enqueued_at = match events.first() {
             Some(TaskEvent::Created(ts)) => *ts,
             _ => unreachable!("A task must always have a creation event."),
@@ -375,6 +618,16 @@ impl From<Task> for TaskView {
 
         let duration = finished_at.zip(started_at).map(|(tf, ts)| (tf - ts));
 
+        if let Some(TaskDetails::DocumentAddition {
+            indexed_documents: Some(indexed_documents),
+            ref mut throughput_docs_per_sec,
+            ..
+        }) = details
+        {
+            *throughput_docs_per_sec =
+                duration.and_then(|duration| throughput(indexed_documents, duration));
+        }
+
         Self {
             uid: id,
             index_uid,
@@ -382,6 +635,10 @@ impl From<Task> for TaskView {
             task_type,
             details,
             error,
+            canceled_by,
+            cancelation_reason,
+            retry_of,
+            batch_uid,
             duration,
             enqueued_at,
             started_at,
@@ -396,6 +653,19 @@ pub struct TaskListView {
     pub limit: usize,
     pub from: Option<TaskId>,
     pub next: Option<TaskId>,
+    pub total: u64,
+}
+
+impl From<TaskList> for TaskListView {
+    fn from(list: TaskList) -> Self {
+        Self {
+            results: list.results.into_iter().map(TaskView::from).collect(),
+            limit: list.limit,
+            from: list.from,
+            next: list.next,
+            total: list.total,
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -408,6 +678,18 @@ pub struct SummarizedTaskView {
     task_type: TaskType,
     #[serde(serialize_with = "time::serde::rfc3339::serialize")]
     enqueued_at: OffsetDateTime,
+    /// The number of tasks ahead of this one in the queue when it was registered.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    enqueue_position: Option<u64>,
+}
+
+impl SummarizedTaskView {
+    pub fn with_enqueue_position(self, enqueue_position: u64) -> Self {
+        Self {
+            enqueue_position: Some(enqueue_position),
+            ..self
+        }
+    }
 }
 
 impl From<Task> for SummarizedTaskView {
@@ -429,6 +711,78 @@ impl From<Task> for SummarizedTaskView {
             status: TaskStatus::Enqueued,
             task_type: other.content.into(),
             enqueued_at,
+            enqueue_position: None,
         }
     }
 }
+
+/// The number of tasks in each status and of each type.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskStatsView {
+    statuses: BTreeMap<TaskStatus, u64>,
+    types: BTreeMap<TaskType, u64>,
+}
+
+impl From<BTreeMap<(TaskStatus, TaskType), u64>> for TaskStatsView {
+    fn from(counts: BTreeMap<(TaskStatus, TaskType), u64>) -> Self {
+        // Every status is always returned, even when no task is in it.
+        let mut statuses: BTreeMap<_, _> = [
+            TaskStatus::Enqueued,
+            TaskStatus::Processing,
+            TaskStatus::Succeeded,
+            TaskStatus::Failed,
+            TaskStatus::Canceled,
+        ]
+        .into_iter()
+        .map(|status| (status, 0))
+        .collect();
+        let mut types = BTreeMap::new();
+
+        for ((status, task_type), count) in counts {
+            *statuses.entry(status).or_default() += count;
+            *types.entry(task_type).or_default() += count;
+        }
+
+        Self { statuses, types }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use meilisearch_types::index_uid::IndexUid;
+
+    use super::*;
+
+    #[test]
+    fn task_view_exposes_canceled_by() {
+        let now = OffsetDateTime::now_utc();
+        let task = |events| Task {
+            id: 1,
+            content: TaskContent::IndexDeletion {
+                index_uid: IndexUid::new_unchecked("doggos"),
+            },
+            events,
+            retry_of: None,
+            priority: 0,
+        };
+
+        let canceled = task(vec![
+            TaskEvent::Created(now),
+            TaskEvent::Canceled {
+                canceled_by: 3,
+                reason: Some("wrong index".to_string()),
+                timestamp: now,
+            },
+        ]);
+        let view = serde_json::to_value(TaskView::from(canceled)).unwrap();
+        assert_eq!(view["status"], "canceled");
+        assert_eq!(view["canceledBy"], 3);
+        assert_eq!(view["cancelationReason"], "wrong index");
+
+        let enqueued = task(vec![TaskEvent::Created(now)]);
+        let view = serde_json::to_value(TaskView::from(enqueued)).unwrap();
+        assert!(view.get("canceledBy").is_none());
+        assert!(view.get("cancelationReason").is_none());
+    }
+}
@@ -96,7 +96,10 @@ impl Index<'_> {
             let (response, status_code) = self.service.get(&url).await;
             assert_eq!(200, status_code, "response: {}", response);
 
-            if response["status"] == "succeeded" || response["status"] == "failed" {
+            if response["status"] == "succeeded"
+                || response["status"] == "failed"
+                || response["status"] == "canceled"
+            {
                 return response;
             }
 
@@ -149,6 +152,14 @@ impl Index<'_> {
             let _ = write!(url, "offset={}&", offset);
         }
 
+        if let Some(from) = options.from {
+            let _ = write!(url, "from={}&", from);
+        }
+
+        if let Some(filter) = options.filter {
+            let _ = write!(url, "filter={}&", encode(filter));
+        }
+
         if let Some(attributes_to_retrieve) = options.attributes_to_retrieve {
             let _ = write!(url, "fields={}&", attributes_to_retrieve.join(","));
         }
@@ -156,6 +167,11 @@ impl Index<'_> {
         self.service.get(url).await
     }
 
+    pub async fn fetch_documents(&self, body: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/documents/fetch", encode(self.uid.as_ref()));
+        self.service.post(url, body).await
+    }
+
     pub async fn delete_document(&self, id: u64) -> (Value, StatusCode) {
         let url = format!("/indexes/{}/documents/{}", encode(self.uid.as_ref()), id);
         self.service.delete(url).await
@@ -254,5 +270,7 @@ pub struct GetDocumentOptions {
 pub struct GetAllDocumentsOptions {
     pub limit: Option<usize>,
     pub offset: Option<usize>,
+    pub from: Option<u64>,
+    pub filter: Option<&'static str>,
     pub attributes_to_retrieve: Option<Vec<&'static str>>,
 }
@@ -1,4 +1,4 @@
-use crate::common::{GetAllDocumentsOptions, Server};
+use crate::common::{default_settings, GetAllDocumentsOptions, Server};
 use actix_web::test;
 
 use meilisearch_http::{analytics, create_app};
@@ -130,7 +130,7 @@ async fn error_add_documents_test_bad_content_types() {
     assert_eq!(
         response["message"],
         json!(
-            r#"The Content-Type `text/plain` is invalid. Accepted values for the Content-Type header are: `application/json`, `application/x-ndjson`, `text/csv`"#
+            r#"The Content-Type `text/plain` is invalid. Accepted values for the Content-Type header are: `application/json`, `application/x-ndjson`, `text/csv`, `text/tab-separated-values`"#
         )
     );
     assert_eq!(response["code"], "invalid_content_type");
@@ -154,7 +154,7 @@ async fn error_add_documents_test_bad_content_types() {
     assert_eq!(
         response["message"],
         json!(
-            r#"The Content-Type `text/plain` is invalid. Accepted values for the Content-Type header are: `application/json`, `application/x-ndjson`, `text/csv`"#
+            r#"The Content-Type `text/plain` is invalid. Accepted values for the Content-Type header are: `application/json`, `application/x-ndjson`, `text/csv`, `text/tab-separated-values`"#
         )
     );
     assert_eq!(response["code"], "invalid_content_type");
@@ -197,7 +197,7 @@ async fn error_add_documents_test_no_content_type() {
     assert_eq!(
         response["message"],
         json!(
-            r#"A Content-Type header is missing. Accepted values for the Content-Type header are: `application/json`, `application/x-ndjson`, `text/csv`"#
+            r#"A Content-Type header is missing. Accepted values for the Content-Type header are: `application/json`, `application/x-ndjson`, `text/csv`, `text/tab-separated-values`"#
         )
     );
     assert_eq!(response["code"], "missing_content_type");
@@ -220,7 +220,7 @@ async fn error_add_documents_test_no_content_type() {
     assert_eq!(
         response["message"],
         json!(
-            r#"A Content-Type header is missing. Accepted values for the Content-Type header are: `application/json`, `application/x-ndjson`, `text/csv`"#
+            r#"A Content-Type header is missing. Accepted values for the Content-Type header are: `application/json`, `application/x-ndjson`, `text/csv`, `text/tab-separated-values`"#
         )
     );
     assert_eq!(response["code"], "missing_content_type");
@@ -293,6 +293,411 @@ async fn error_add_malformed_csv_documents() {
     );
 }
 
+#[actix_rt::test]
+async fn validate_documents_only() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let (response, code) = index
+        .service
+        .post(
+            "/indexes/test/documents?validateOnly=true&primaryKey=id",
+            json!([{ "id": 1, "content": "foo" }, { "content": "bar" }, { "content": "baz" }]),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response,
+        json!({
+            "documentsCount": 3,
+            "primaryKey": "id",
+            "errors": ["2 document(s) are missing the primary key `id`, the first one is at position 1."],
+        })
+    );
+
+    let (response, code) = index
+        .service
+        .post(
+            "/indexes/test/documents?validateOnly=true",
+            json!([{ "id": 1 }, { "id": 2 }]),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["documentsCount"], 2);
+    assert_eq!(response["primaryKey"], json!(null));
+    assert_eq!(response["errors"], json!([]));
+
+    // parse errors are part of the summary.
+    let (response, code) = index
+        .service
+        .post("/indexes/test/documents?validateOnly=true", json!(42))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["documentsCount"], 0);
+    assert_eq!(response["errors"].as_array().unwrap().len(), 1);
+
+    // no task was registered, and the index was not created.
+    let (response, code) = index.list_tasks().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 0);
+    let (_response, code) = index.get().await;
+    assert_eq!(code, 404);
+}
+
+#[actix_rt::test]
+async fn add_tsv_documents() {
+    let document = "id\tname\tprice:number\n1\tcheese\t4.5\n2\tbread, white\t2";
+
+    let server = Server::new().await;
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+    let req = test::TestRequest::post()
+        .uri("/indexes/dog/documents")
+        .set_payload(document.to_string())
+        .insert_header(("content-type", "text/tab-separated-values"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    let status_code = res.status();
+    let body = test::read_body(res).await;
+    let response: Value = serde_json::from_slice(&body).unwrap_or_default();
+    assert_eq!(status_code, 202, "{}", response);
+
+    let index = server.index("dog");
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["details"]["indexedDocuments"], 2);
+
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response,
+        json!({ "id": "1", "name": "cheese", "price": 4.5 })
+    );
+
+    let (response, code) = index.get_document(2, None).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response,
+        json!({ "id": "2", "name": "bread, white", "price": 2.0 })
+    );
+}
+
+#[actix_rt::test]
+async fn add_csv_documents_with_custom_delimiter() {
+    let document = "id;content\n1;hello, world";
+
+    let server = Server::new().await;
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+    let req = test::TestRequest::post()
+        .uri("/indexes/dog/documents?csvDelimiter=%3B")
+        .set_payload(document.to_string())
+        .insert_header(("content-type", "text/csv"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    let status_code = res.status();
+    let body = test::read_body(res).await;
+    let response: Value = serde_json::from_slice(&body).unwrap_or_default();
+    assert_eq!(status_code, 202, "{}", response);
+
+    let index = server.index("dog");
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response, json!({ "id": "1", "content": "hello, world" }));
+}
+
+#[actix_rt::test]
+async fn error_add_documents_bad_csv_delimiter() {
+    let server = Server::new().await;
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+
+    // the delimiter must be an ASCII character
+    let req = test::TestRequest::post()
+        .uri("/indexes/dog/documents?csvDelimiter=%C3%A9")
+        .set_payload("id\n1".to_string())
+        .insert_header(("content-type", "text/csv"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    let status_code = res.status();
+    let body = test::read_body(res).await;
+    let response: Value = serde_json::from_slice(&body).unwrap_or_default();
+    assert_eq!(status_code, 400);
+    assert_eq!(
+        response["message"],
+        json!("The CSV delimiter `é` is invalid. It must be a single ASCII character.")
+    );
+    assert_eq!(response["code"], json!("invalid_csv_delimiter"));
+
+    // the delimiter can only be used with csv payloads
+    let req = test::TestRequest::post()
+        .uri("/indexes/dog/documents?csvDelimiter=%3B")
+        .set_payload(json!([{ "id": 1 }]).to_string())
+        .insert_header(("content-type", "application/json"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    let status_code = res.status();
+    let body = test::read_body(res).await;
+    let response: Value = serde_json::from_slice(&body).unwrap_or_default();
+    assert_eq!(status_code, 400);
+    assert_eq!(
+        response["message"],
+        json!("The `csvDelimiter` parameter can only be used with the `text/csv` Content-Type.")
+    );
+    assert_eq!(response["code"], json!("bad_request"));
+}
+
+#[actix_rt::test]
+async fn add_csv_documents_with_quoted_newlines() {
+    let server = Server::new().await;
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/indexes/dog/documents")
+        .set_payload("id,content\n1,\"hello\nworld\"\n2,bye".to_string())
+        .insert_header(("content-type", "text/csv"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 202);
+
+    // the first row of a headerless payload can also span several lines.
+    let req = test::TestRequest::post()
+        .uri("/indexes/cat/documents?csvHasHeader=false")
+        .set_payload("1,\"hello\nworld\"\n2,bye".to_string())
+        .insert_header(("content-type", "text/csv"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 202);
+
+    let index = server.index("dog");
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["details"]["indexedDocuments"], 2);
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response, json!({ "id": "1", "content": "hello\nworld" }));
+
+    let index = server.index("cat");
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["details"]["indexedDocuments"], 2);
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response, json!({ "col0": "1", "col1": "hello\nworld" }));
+}
+
+#[actix_rt::test]
+async fn add_csv_documents_with_custom_quote() {
+    let document = "id,content\n1,'hello, world'\n2,'it''s'";
+
+    let server = Server::new().await;
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+    let req = test::TestRequest::post()
+        .uri("/indexes/dog/documents?csvQuote=%27")
+        .set_payload(document.to_string())
+        .insert_header(("content-type", "text/csv"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    let status_code = res.status();
+    let body = test::read_body(res).await;
+    let response: Value = serde_json::from_slice(&body).unwrap_or_default();
+    assert_eq!(status_code, 202, "{}", response);
+
+    let index = server.index("dog");
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response, json!({ "id": "1", "content": "hello, world" }));
+    let (response, code) = index.get_document(2, None).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response, json!({ "id": "2", "content": "it's" }));
+}
+
+#[actix_rt::test]
+async fn error_add_documents_bad_csv_quote() {
+    let server = Server::new().await;
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+
+    // the quote must be an ASCII character
+    let req = test::TestRequest::post()
+        .uri("/indexes/dog/documents?csvQuote=%C3%A9")
+        .set_payload("id\n1".to_string())
+        .insert_header(("content-type", "text/csv"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    let status_code = res.status();
+    let body = test::read_body(res).await;
+    let response: Value = serde_json::from_slice(&body).unwrap_or_default();
+    assert_eq!(status_code, 400);
+    assert_eq!(
+        response["message"],
+        json!("The CSV quote `é` is invalid. It must be a single ASCII character.")
+    );
+    assert_eq!(response["code"], json!("invalid_csv_quote"));
+
+    // the quote can only be used with csv payloads
+    let req = test::TestRequest::post()
+        .uri("/indexes/dog/documents?csvQuote=%27")
+        .set_payload(json!([{ "id": 1 }]).to_string())
+        .insert_header(("content-type", "application/json"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    let status_code = res.status();
+    let body = test::read_body(res).await;
+    let response: Value = serde_json::from_slice(&body).unwrap_or_default();
+    assert_eq!(status_code, 400);
+    assert_eq!(
+        response["message"],
+        json!("The `csvQuote` parameter can only be used with the `text/csv` Content-Type.")
+    );
+    assert_eq!(response["code"], json!("bad_request"));
+}
+
+#[actix_rt::test]
+async fn add_headerless_csv_documents() {
+    let document = "1,hello\n2,world";
+
+    let server = Server::new().await;
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+    let req = test::TestRequest::post()
+        .uri("/indexes/dog/documents?csvHasHeader=false&primaryKey=col0")
+        .set_payload(document.to_string())
+        .insert_header(("content-type", "text/csv"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    let status_code = res.status();
+    let body = test::read_body(res).await;
+    let response: Value = serde_json::from_slice(&body).unwrap_or_default();
+    assert_eq!(status_code, 202, "{}", response);
+
+    let index = server.index("dog");
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["details"]["indexedDocuments"], 2);
+
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response, json!({ "col0": "1", "col1": "hello" }));
+}
+
+#[actix_rt::test]
+async fn add_csv_documents_with_numeric_header() {
+    // a header made of numbers is still a header, unless `csvHasHeader=false` is set.
+    let document = "1,4.5\n2,3";
+
+    let server = Server::new().await;
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+    let req = test::TestRequest::post()
+        .uri("/indexes/dog/documents?primaryKey=1")
+        .set_payload(document.to_string())
+        .insert_header(("content-type", "text/csv"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    let status_code = res.status();
+    let body = test::read_body(res).await;
+    let response: Value = serde_json::from_slice(&body).unwrap_or_default();
+    assert_eq!(status_code, 202, "{}", response);
+
+    let index = server.index("dog");
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["details"]["indexedDocuments"], 1);
+
+    let (response, code) = index.get_document(2, None).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response, json!({ "1": "2", "4.5": "3" }));
+}
+
+#[actix_rt::test]
+async fn error_add_headerless_csv_documents_with_mismatched_row() {
+    let document = "1,hello\n2,hello,world";
+
+    let server = Server::new().await;
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+    let req = test::TestRequest::post()
+        .uri("/indexes/dog/documents?csvHasHeader=false")
+        .set_payload(document.to_string())
+        .insert_header(("content-type", "text/csv"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    let status_code = res.status();
+    let body = test::read_body(res).await;
+    let response: Value = serde_json::from_slice(&body).unwrap_or_default();
+    assert_eq!(status_code, 400);
+    assert_eq!(
+        response["message"],
+        json!(
+            r#"The `csv` payload provided is malformed: `CSV error: record 2 (line: 3, byte: 18): found record with 3 fields, but the previous record has 2 fields`."#
+        )
+    );
+    assert_eq!(response["code"], json!("malformed_payload"));
+}
+
 #[actix_rt::test]
 async fn error_add_malformed_json_documents() {
     let document = r#"[{"id": 1}, {id: 2}]"#;
@@ -405,6 +810,103 @@ async fn error_add_malformed_json_documents() {
     );
 }
 
+#[actix_rt::test]
+async fn error_add_json_documents_with_trailing_data() {
+    let server = Server::new().await;
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+
+    for document in [r#"{"id": 1} {"id": 2}"#, r#"[{"id": 1}] {"id": 2}"#] {
+        let req = test::TestRequest::post()
+            .uri("/indexes/dog/documents")
+            .set_payload(document.to_string())
+            .insert_header(("content-type", "application/json"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        let status_code = res.status();
+        let body = test::read_body(res).await;
+        let response: Value = serde_json::from_slice(&body).unwrap_or_default();
+        assert_eq!(status_code, 400, "{}", document);
+        assert_eq!(response["code"], json!("malformed_payload"));
+        assert!(response["message"]
+            .as_str()
+            .unwrap()
+            .contains("trailing characters"));
+    }
+}
+
+#[actix_rt::test]
+async fn error_add_malformed_ndjson_documents_after_a_large_record() {
+    // The first record doesn't fit in a single buffer of the reader.
+    let record = json!({ "id": 1, "content": "a".repeat(20_000) }).to_string();
+    let document = format!("{}\n{{id: 2}}", record);
+
+    let server = Server::new().await;
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+    let req = test::TestRequest::post()
+        .uri("/indexes/dog/documents")
+        .set_payload(document)
+        .insert_header(("content-type", "application/x-ndjson"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    let status_code = res.status();
+    let body = test::read_body(res).await;
+    let response: Value = serde_json::from_slice(&body).unwrap_or_default();
+    assert_eq!(status_code, 400);
+    assert_eq!(
+        response["message"],
+        json!(format!(
+            "The `ndjson` payload provided is malformed. `Couldn't serialize document value: key must be a string at line 2 column 2`. The malformed record is at index 1, on the line starting at byte {}: `{{id: 2}}`.",
+            record.len() + 1
+        ))
+    );
+}
+
+#[actix_rt::test]
+async fn error_add_large_malformed_ndjson_record() {
+    let record = format!("{{id: 1, \"content\": \"{}\"}}", "a".repeat(20_000));
+
+    let server = Server::new().await;
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+    let req = test::TestRequest::post()
+        .uri("/indexes/dog/documents")
+        .set_payload(record.clone())
+        .insert_header(("content-type", "application/x-ndjson"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    let status_code = res.status();
+    let body = test::read_body(res).await;
+    let response: Value = serde_json::from_slice(&body).unwrap_or_default();
+    assert_eq!(status_code, 400);
+    assert_eq!(
+        response["message"],
+        json!(format!(
+            "The `ndjson` payload provided is malformed. `Couldn't serialize document value: key must be a string at line 1 column 2`. The malformed record is at index 0, on the line starting at byte 0: `{}...`.",
+            &record[..100]
+        ))
+    );
+}
+
 #[actix_rt::test]
 async fn error_add_malformed_ndjson_documents() {
     let document = "{\"id\": 1}\n{id: 2}";
@@ -432,7 +934,7 @@ async fn error_add_malformed_ndjson_documents() {
     assert_eq!(
         response["message"],
         json!(
-            r#"The `ndjson` payload provided is malformed. `Couldn't serialize document value: key must be a string at line 2 column 2`."#
+            r#"The `ndjson` payload provided is malformed. `Couldn't serialize document value: key must be a string at line 2 column 2`. The malformed record is at index 1, on the line starting at byte 10: `{id: 2}`."#
         )
     );
     assert_eq!(response["code"], json!("malformed_payload"));
@@ -455,7 +957,7 @@ async fn error_add_malformed_ndjson_documents() {
     assert_eq!(status_code, 400);
     assert_eq!(
         response["message"],
-        json!("The `ndjson` payload provided is malformed. `Couldn't serialize document value: key must be a string at line 2 column 2`.")
+        json!("The `ndjson` payload provided is malformed. `Couldn't serialize document value: key must be a string at line 2 column 2`. The malformed record is at index 1, on the line starting at byte 10: `{id: 2}`.")
     );
     assert_eq!(response["code"], json!("malformed_payload"));
     assert_eq!(response["type"], json!("invalid_request"));
@@ -660,6 +1162,7 @@ async fn add_documents_no_index_creation() {
     assert_eq!(response["type"], "documentAdditionOrUpdate");
     assert_eq!(response["details"]["receivedDocuments"], 1);
     assert_eq!(response["details"]["indexedDocuments"], 1);
+    assert_eq!(response["details"]["inferredPrimaryKey"], "id");
 
     let processed_at =
         OffsetDateTime::parse(response["finishedAt"].as_str().unwrap(), &Rfc3339).unwrap();
@@ -673,6 +1176,42 @@ async fn add_documents_no_index_creation() {
     assert_eq!(response["primaryKey"], "id");
 }
 
+#[actix_rt::test]
+async fn add_documents_forbid_index_creation() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let (response, code) = index
+        .service
+        .post(
+            "/indexes/test/documents?allowIndexCreation=false",
+            json!([{ "id": 1, "content": "foo" }]),
+        )
+        .await;
+    assert_eq!(code, 202, "{}", response);
+
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "failed");
+    assert_eq!(response["error"]["code"], "index_not_found");
+
+    let (_response, code) = index.get().await;
+    assert_eq!(code, 404);
+
+    // the documents are added to an existing index.
+    index.create(None).await;
+    index.wait_task(1).await;
+    let (response, code) = index
+        .service
+        .put(
+            "/indexes/test/documents?allowIndexCreation=false",
+            json!([{ "id": 1, "content": "foo" }]),
+        )
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "succeeded");
+}
+
 #[actix_rt::test]
 async fn error_document_add_create_index_bad_uid() {
     let server = Server::new().await;
@@ -730,6 +1269,7 @@ async fn document_addition_with_primary_key() {
     assert_eq!(response["type"], "documentAdditionOrUpdate");
     assert_eq!(response["details"]["receivedDocuments"], 1);
     assert_eq!(response["details"]["indexedDocuments"], 1);
+    assert!(response["details"].get("inferredPrimaryKey").is_none());
 
     let (response, code) = index.get().await;
     assert_eq!(code, 200);
@@ -1200,3 +1740,166 @@ async fn batch_several_documents_addition() {
     assert_eq!(code, 200, "failed with `{}`", response);
     assert_eq!(response["results"].as_array().unwrap().len(), 120);
 }
+
+#[actix_rt::test]
+async fn documents_addition_on_different_indexes_are_batched_separately() {
+    let server = Server::new().await;
+    let catto = server.index("catto");
+    let doggo = server.index("doggo");
+    catto.create(Some("docid")).await;
+    catto.wait_task(0).await;
+
+    // The documents of `catto` miss their primary key, which must not fail the ones of `doggo`.
+    catto.add_documents(json!([{ "id": 1 }]), None).await;
+    doggo
+        .add_documents(json!([{ "id": 1, "name": "kefir" }]), None)
+        .await;
+    catto.wait_task(1).await;
+    doggo.wait_task(2).await;
+
+    let (response, code) = catto.get_task(1).await;
+    assert_eq!(code, 200);
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(response["error"]["code"], "missing_document_id");
+
+    let (response, code) = doggo.get_task(2).await;
+    assert_eq!(code, 200);
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["details"]["indexedDocuments"], 1);
+
+    let (response, code) = doggo.get_document(1, None).await;
+    assert_eq!(code, 200);
+    assert_eq!(response, json!({ "id": 1, "name": "kefir" }));
+}
+
+#[actix_rt::test]
+async fn document_addition_reports_throughput() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "content": "foo" },
+        { "id": 2, "content": "bar" },
+    ]);
+    let (_response, code) = index.add_documents(documents, None).await;
+    assert_eq!(code, 202);
+    let response = index.wait_task(0).await;
+
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["details"]["indexedDocuments"], 2);
+    let throughput = response["details"]["throughputDocsPerSec"]
+        .as_f64()
+        .unwrap();
+    assert!(throughput > 0.0);
+
+    // nothing is indexed so there is no throughput.
+    let (_response, code) = index.add_documents(json!([]), None).await;
+    assert_eq!(code, 202);
+    let response = index.wait_task(1).await;
+    assert_eq!(response["details"]["throughputDocsPerSec"], json!(null));
+}
+
+#[actix_rt::test]
+async fn document_additions_are_limited_by_the_total_documents_quota() {
+    let temp = tempfile::tempdir().unwrap();
+    let mut options = default_settings(temp.path());
+    options.indexer_options.max_total_documents = Some(3);
+    let server = Server::new_with_options(options).await.unwrap();
+    let movies = server.index("movies");
+    let books = server.index("books");
+
+    let (task, code) = movies
+        .add_documents(json!([{ "id": 1 }, { "id": 2 }]), None)
+        .await;
+    assert_eq!(code, 202);
+    let response = movies.wait_task(task["taskUid"].as_u64().unwrap()).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    // the quota is reached, but not exceeded.
+    let (task, code) = books.add_documents(json!([{ "id": 1 }]), None).await;
+    assert_eq!(code, 202);
+    let response = books.wait_task(task["taskUid"].as_u64().unwrap()).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (task, code) = movies.add_documents(json!([{ "id": 3 }]), None).await;
+    assert_eq!(code, 202);
+    let response = movies.wait_task(task["taskUid"].as_u64().unwrap()).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(response["error"]["code"], "total_documents_quota_exceeded");
+    assert_eq!(
+        response["error"]["message"],
+        "Adding these documents would exceed the maximum of `3` documents across all indexes."
+    );
+
+    // the deletions are always allowed, and make room for new documents.
+    let (task, code) = movies.delete_document(1).await;
+    assert_eq!(code, 202);
+    let response = movies.wait_task(task["taskUid"].as_u64().unwrap()).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (task, code) = movies.add_documents(json!([{ "id": 3 }]), None).await;
+    assert_eq!(code, 202);
+    let response = movies.wait_task(task["taskUid"].as_u64().unwrap()).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (response, code) = movies
+        .get_all_documents(GetAllDocumentsOptions::default())
+        .await;
+    assert_eq!(code, 200);
+    assert_eq!(response["results"], json!([{ "id": 2 }, { "id": 3 }]));
+}
+
+#[actix_rt::test]
+async fn error_document_with_too_many_fields() {
+    let temp = tempfile::tempdir().unwrap();
+    let mut options = default_settings(temp.path());
+    options.indexer_options.max_fields_per_document = 3;
+    let server = Server::new_with_options(options).await.unwrap();
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "name": "kefir" },
+        { "id": 2, "name": "intel", "age": 4, "breed": "labrador" },
+        { "id": 3 },
+    ]);
+    let (response, code) = index.add_documents(documents, None).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "too_many_document_fields");
+    assert_eq!(
+        response["message"],
+        "The document at index 1 with the id `2` has 4 fields, more than the maximum of 3 fields per document."
+    );
+
+    // the width of a csv payload is decided by its header.
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+    let req = test::TestRequest::post()
+        .uri("/indexes/test/documents")
+        .set_payload("id,name,age,breed\n2,intel,4,labrador\n")
+        .insert_header(("content-type", "text/csv"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    let status_code = res.status();
+    let body = test::read_body(res).await;
+    let response: Value = serde_json::from_slice(&body).unwrap_or_default();
+    assert_eq!(status_code, 400);
+    assert_eq!(response["code"], "too_many_document_fields");
+    assert_eq!(
+        response["message"],
+        "The header of the `csv` payload provided has 4 columns, more than the maximum of 3 fields per document."
+    );
+
+    // the documents within the limit are accepted.
+    let (response, code) = index
+        .add_documents(json!([{ "id": 1, "name": "kefir", "age": 4 }]), None)
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "succeeded");
+}
@@ -92,6 +92,52 @@ async fn clear_all_documents_empty_index() {
     assert!(response["results"].as_array().unwrap().is_empty());
 }
 
+#[actix_rt::test]
+async fn clear_documents_matching_filter() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({ "filterableAttributes": ["color"] }))
+        .await;
+    index
+        .add_documents(
+            json!([
+                { "id": 0, "color": "red" },
+                { "id": 1, "color": "blue" },
+                { "id": 2, "color": "red" },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(1).await;
+
+    let (response, code) = index
+        .service
+        .delete("/indexes/test/documents?filter=color%20%3D%20red")
+        .await;
+    assert_eq!(code, 202, "{}", response);
+
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["type"], "documentDeletion");
+    assert_eq!(response["details"]["filter"], "color = red");
+    assert_eq!(response["details"]["deletedDocuments"], 2);
+
+    let (response, code) = index
+        .get_all_documents(GetAllDocumentsOptions::default())
+        .await;
+    assert_eq!(code, 200);
+    assert_eq!(response["results"], json!([{ "id": 1, "color": "blue" }]));
+
+    // an invalid filter is rejected before any task is registered.
+    let (response, code) = index
+        .service
+        .delete("/indexes/test/documents?filter=color%20%3D")
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_filter");
+}
+
 #[actix_rt::test]
 async fn error_delete_batch_unexisting_index() {
     let server = Server::new().await;
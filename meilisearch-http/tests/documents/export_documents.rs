@@ -0,0 +1,52 @@
+use std::io::BufRead;
+
+use meilisearch_lib::index_controller::Update;
+use serde_json::{json, Value};
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn export_documents_matching_a_filter() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.load_test_set().await;
+    let (response, _code) = index
+        .update_settings(json!({"filterableAttributes": ["gender"]}))
+        .await;
+    index.wait_task(response["taskUid"].as_u64().unwrap()).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("export").join("males.ndjson");
+    let (task, _) = server
+        .service
+        .meilisearch
+        .register_update(
+            "test".to_string(),
+            Update::ExportDocuments {
+                path: path.clone(),
+                filter: Some("gender = male".to_string()),
+            },
+        )
+        .await
+        .unwrap();
+
+    let response = index.wait_task(task.id as u64).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["type"], "documentsExport");
+    assert_eq!(response["details"]["filter"], "gender = male");
+    assert_eq!(response["details"]["exportedDocuments"], 37);
+
+    let file = std::io::BufReader::new(std::fs::File::open(&path).unwrap());
+    let documents: Vec<Value> = file
+        .lines()
+        .map(|line| serde_json::from_str(&line.unwrap()).unwrap())
+        .collect();
+    assert_eq!(documents.len(), 37);
+    assert!(documents
+        .iter()
+        .all(|document| document["gender"] == json!("male")));
+    // every exported document is complete.
+    let id = documents[0]["id"].as_u64().unwrap();
+    let (expected, _code) = index.get_document(id, None).await;
+    assert_eq!(documents[0], expected);
+}
@@ -195,6 +195,138 @@ async fn test_get_all_documents_offset() {
     assert_eq!(response["total"], json!(77));
 }
 
+#[actix_rt::test]
+async fn test_get_all_documents_offset_out_of_range() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.load_test_set().await;
+
+    let (response, code) = index
+        .get_all_documents(GetAllDocumentsOptions {
+            offset: Some(76),
+            ..Default::default()
+        })
+        .await;
+    assert_eq!(code, 200);
+    assert_eq!(response["results"].as_array().unwrap().len(), 1);
+    assert_eq!(response["effectiveOffset"], json!(76));
+    assert_eq!(response["offsetOutOfRange"], json!(false));
+
+    let (response, code) = index
+        .get_all_documents(GetAllDocumentsOptions {
+            offset: Some(100),
+            ..Default::default()
+        })
+        .await;
+    assert_eq!(code, 200);
+    assert!(response["results"].as_array().unwrap().is_empty());
+    assert_eq!(response["offset"], json!(100));
+    assert_eq!(response["total"], json!(77));
+    assert_eq!(response["effectiveOffset"], json!(77));
+    assert_eq!(response["offsetOutOfRange"], json!(true));
+}
+
+#[actix_rt::test]
+async fn test_get_all_documents_from_cursor() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.load_test_set().await;
+
+    let mut ids = Vec::new();
+    let mut from = None;
+    loop {
+        let (response, code) = index
+            .get_all_documents(GetAllDocumentsOptions {
+                limit: Some(30),
+                from,
+                ..Default::default()
+            })
+            .await;
+        assert_eq!(code, 200);
+        assert_eq!(response["total"], json!(77));
+        for document in response["results"].as_array().unwrap() {
+            ids.push(document["id"].as_u64().unwrap());
+        }
+        match response["next"].as_u64() {
+            Some(next) => from = Some(next),
+            None => break,
+        }
+    }
+
+    // the pages follow each other without overlapping.
+    let (response, _code) = index
+        .get_all_documents(GetAllDocumentsOptions {
+            limit: Some(100),
+            ..Default::default()
+        })
+        .await;
+    let all_ids: Vec<_> = response["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|document| document["id"].as_u64().unwrap())
+        .collect();
+    assert_eq!(ids, all_ids);
+    assert!(response.get("next").is_none());
+}
+
+#[actix_rt::test]
+async fn test_get_all_documents_filter() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.load_test_set().await;
+
+    let (_response, code) = index
+        .get_all_documents(GetAllDocumentsOptions {
+            filter: Some("gender = male"),
+            ..Default::default()
+        })
+        .await;
+    assert_eq!(code, 400);
+
+    let (response, _code) = index
+        .update_settings(json!({"filterableAttributes": ["gender"]}))
+        .await;
+    index.wait_task(response["taskUid"].as_u64().unwrap()).await;
+
+    let (response, code) = index
+        .get_all_documents(GetAllDocumentsOptions {
+            limit: Some(10),
+            filter: Some("gender = male"),
+            ..Default::default()
+        })
+        .await;
+    assert_eq!(code, 200);
+    assert_eq!(response["total"], json!(37));
+    let results = response["results"].as_array().unwrap();
+    assert_eq!(results.len(), 10);
+    assert!(results
+        .iter()
+        .all(|document| document["gender"] == json!("male")));
+}
+
+#[actix_rt::test]
+async fn fetch_documents_by_ids() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.load_test_set().await;
+
+    let (response, code) = index
+        .fetch_documents(json!({ "ids": [3, "1", 1000, 0], "fields": ["id", "name"] }))
+        .await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        response,
+        json!({
+            "results": [
+                { "id": 3, "name": "Adeline Flynn" },
+                { "id": 1, "name": "Cherry Orr" },
+                { "id": 0, "name": "Lucas Hess" },
+            ]
+        })
+    );
+}
+
 #[actix_rt::test]
 async fn test_get_all_documents_attributes_to_retrieve() {
     let server = Server::new().await;
@@ -371,6 +503,67 @@ async fn get_document_s_nested_attributes_to_retrieve() {
     );
 }
 
+#[actix_rt::test]
+async fn get_documents_deep_nested_attributes_to_retrieve() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    let documents = json!([
+        {
+            "id": 0,
+            "author": {
+                "name": "kefir",
+                "address": { "city": "paris", "country": "france" },
+            },
+            "reviews": [
+                { "user": { "name": "intel", "age": 3 }, "score": 5 },
+                { "user": { "name": "bobby", "age": 2 }, "score": 4 },
+            ],
+        },
+        {
+            "id": 1,
+            "author.address": { "city": "lyon", "country": "france" },
+        },
+    ]);
+    let (response, code) = index.add_documents(documents, None).await;
+    assert_eq!(code, 202);
+    index.wait_task(response["taskUid"].as_u64().unwrap()).await;
+
+    let (response, code) = index
+        .get_document(
+            0,
+            Some(GetDocumentOptions {
+                fields: Some(vec!["author.address.city", "reviews.user.name"]),
+            }),
+        )
+        .await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        response,
+        json!({
+            "author": { "address": { "city": "paris" } },
+            "reviews": [
+                { "user": { "name": "intel" } },
+                { "user": { "name": "bobby" } },
+            ],
+        })
+    );
+
+    let (response, code) = index
+        .get_all_documents(GetAllDocumentsOptions {
+            attributes_to_retrieve: Some(vec!["author.address", "author.address.city"]),
+            ..Default::default()
+        })
+        .await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        response["results"],
+        json!([
+            { "author": { "address": { "city": "paris", "country": "france" } } },
+            { "author.address": { "city": "lyon", "country": "france" } },
+        ])
+    );
+}
+
 #[actix_rt::test]
 async fn get_documents_displayed_attributes_is_ignored() {
     let server = Server::new().await;
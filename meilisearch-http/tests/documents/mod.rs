@@ -1,3 +1,4 @@
 mod add_documents;
 mod delete_documents;
+mod export_documents;
 mod get_documents;
@@ -68,12 +68,12 @@ async fn error_update_existing_primary_key() {
         }
     ]);
     index.add_documents(documents, None).await;
+    index.wait_task(1).await;
 
-    let (_, code) = index.update(Some("primary")).await;
+    // the index has documents, so the update is rejected before being registered.
+    let (response, code) = index.update(Some("primary")).await;
 
-    assert_eq!(code, 202);
-
-    let response = index.wait_task(2).await;
+    assert_eq!(code, 400);
 
     let expected_response = json!({
         "message": "Index already has a primary key: `id`.",
@@ -82,7 +82,13 @@ async fn error_update_existing_primary_key() {
         "link": "https://docs.meilisearch.com/errors#index_primary_key_already_exists"
     });
 
-    assert_eq!(response["error"], expected_response);
+    assert_eq!(response, expected_response);
+
+    // keeping the same primary key is allowed.
+    let (_, code) = index.update(Some("id")).await;
+    assert_eq!(code, 202);
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
 }
 
 #[actix_rt::test]
@@ -103,3 +109,43 @@ async fn error_update_unexisting_index() {
 
     assert_eq!(response["error"], expected_response);
 }
+
+#[actix_rt::test]
+async fn touch_index() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(Some("id")).await;
+    index
+        .add_documents(json!([{ "id": 1, "content": "foo" }]), None)
+        .await;
+    index.wait_task(1).await;
+
+    let updated_at = |response: &serde_json::Value| {
+        OffsetDateTime::parse(response["updatedAt"].as_str().unwrap(), &Rfc3339).unwrap()
+    };
+    let (before, code) = index.get().await;
+    assert_eq!(code, 200);
+
+    server
+        .service
+        .meilisearch
+        .touch_index("test".to_string())
+        .await
+        .unwrap();
+
+    let (after, code) = index.get().await;
+    assert_eq!(code, 200);
+    assert!(updated_at(&after) > updated_at(&before));
+    assert_eq!(after["createdAt"], before["createdAt"]);
+    assert_eq!(after["primaryKey"], "id");
+    let (stats, _) = index.stats().await;
+    assert_eq!(stats["numberOfDocuments"], 1);
+
+    let error = server
+        .service
+        .meilisearch
+        .touch_index("unknown".to_string())
+        .await
+        .unwrap_err();
+    assert_eq!(error.to_string(), "Index `unknown` not found.");
+}
@@ -119,6 +119,51 @@ async fn test_partial_update() {
     assert_eq!(response["searchableAttributes"], json!(["bar"]));
 }
 
+#[actix_rt::test]
+async fn consecutive_settings_updates() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({"displayedAttributes": ["foo"], "stopWords": ["the"]}))
+        .await;
+    index
+        .update_settings(json!({"rankingRules": ["manyTheFish"]}))
+        .await;
+    index
+        .update_settings(json!({"searchableAttributes": ["bar"], "stopWords": ["a"]}))
+        .await;
+    index
+        .update_settings(json!({"typoTolerance": {"minWordSizeForTypos": {"oneTypo": 2}}}))
+        .await;
+    index.wait_task(3).await;
+
+    // Every update is processed on its own, even the ones that may have been batched together.
+    for (uid, status) in [
+        (0, "succeeded"),
+        (1, "failed"),
+        (2, "succeeded"),
+        (3, "succeeded"),
+    ] {
+        let (response, code) = index.get_task(uid).await;
+        assert_eq!(code, 200);
+        assert_eq!(response["status"], status, "{}", response);
+    }
+
+    let (response, code) = index.settings().await;
+    assert_eq!(code, 200);
+    assert_eq!(response["displayedAttributes"], json!(["foo"]));
+    assert_eq!(response["searchableAttributes"], json!(["bar"]));
+    assert_eq!(response["stopWords"], json!(["a"]));
+    assert_eq!(
+        response["rankingRules"],
+        DEFAULT_SETTINGS_VALUES["ranking_rules"]
+    );
+    assert_eq!(
+        response["typoTolerance"]["minWordSizeForTypos"],
+        json!({"oneTypo": 2, "twoTypos": 9})
+    );
+}
+
 #[actix_rt::test]
 async fn error_delete_settings_unexisting_index() {
     let server = Server::new().await;
@@ -1,4 +1,11 @@
+use std::time::Duration;
+
 use crate::common::Server;
+use actix_web::web::Bytes;
+use meilisearch_lib::index_controller::{DocumentAdditionFormat, Payload, Update};
+use meilisearch_lib::milli::update::IndexDocumentsMethod;
+use meilisearch_lib::tasks::HeldKind;
+use meilisearch_types::error::{Code, ErrorCode};
 use serde_json::json;
 use time::format_description::well_known::Rfc3339;
 use time::OffsetDateTime;
@@ -42,6 +49,27 @@ async fn get_task_status() {
     // TODO check response format, as per #48
 }
 
+#[actix_rt::test]
+async fn get_task_batch_uid() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+    index
+        .add_documents(json!([{ "id": 1, "content": "foobar" }]), None)
+        .await;
+    index.wait_task(1).await;
+
+    let (response, code) = index.get_task(1).await;
+    assert_eq!(code, 200);
+    // The batch of a task is identified by the uid of its first task.
+    assert_eq!(response["batchUid"], 1);
+
+    let (response, code) = server.tasks().await;
+    assert_eq!(code, 200);
+    assert_eq!(response["results"][1]["batchUid"], 0);
+}
+
 #[actix_rt::test]
 async fn list_tasks() {
     let server = Server::new().await;
@@ -57,6 +85,27 @@ async fn list_tasks() {
     let (response, code) = index.list_tasks().await;
     assert_eq!(code, 200);
     assert_eq!(response["results"].as_array().unwrap().len(), 2);
+    assert_eq!(response["total"], 2);
+
+    let (response, code) = index.service.get("/tasks?indexUid=test&limit=1").await;
+    assert_eq!(code, 200);
+    assert_eq!(response["results"].as_array().unwrap().len(), 1);
+    assert_eq!(response["total"], 2);
+    assert_eq!(response["from"], 1);
+    assert_eq!(response["next"], 0);
+}
+
+#[actix_rt::test]
+async fn list_tasks_limit_is_capped() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index.service.get("/tasks?limit=4000000000").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 1);
+    assert_eq!(response["limit"], 1000);
 }
 
 #[actix_rt::test]
@@ -189,6 +238,255 @@ async fn list_tasks_status_and_type_filtered() {
     assert_eq!(response["results"].as_array().unwrap().len(), 2);
 }
 
+#[actix_rt::test]
+async fn list_tasks_error_filtered() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+    // The index already exists, so this task fails.
+    index.create(None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index
+        .service
+        .get("/tasks?status=failed&hasError=true")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 1);
+    assert_eq!(response["results"][0]["uid"], 1);
+    assert_eq!(
+        response["results"][0]["error"]["code"],
+        "index_already_exists"
+    );
+
+    let (response, code) = index.service.get("/tasks?hasError=false").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 1);
+    assert_eq!(response["results"][0]["uid"], 0);
+
+    let (response, code) = index
+        .service
+        .get("/tasks?status=succeeded&hasError=true")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 0);
+}
+
+#[actix_rt::test]
+async fn task_stats() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+    // The index already exists, so this task fails.
+    index.create(None).await;
+    index.wait_task(1).await;
+    index.delete().await;
+    index.wait_task(2).await;
+
+    let (response, code) = index.service.get("/tasks/stats").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response,
+        json!({
+            "statuses": {
+                "enqueued": 0,
+                "processing": 0,
+                "succeeded": 2,
+                "failed": 1,
+                "canceled": 0,
+            },
+            "types": {
+                "indexCreation": 2,
+                "indexDeletion": 1,
+            },
+        })
+    );
+}
+
+#[actix_rt::test]
+async fn list_tasks_uid_filtered() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+    index
+        .add_documents(
+            serde_json::from_str(include_str!("../assets/test_set.json")).unwrap(),
+            None,
+        )
+        .await;
+    index.wait_task(1).await;
+
+    let (response, code) = index.service.get("/tasks?uids=1").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 1);
+    assert_eq!(response["results"][0]["uid"], 1);
+
+    let (response, code) = index.service.get("/tasks?uids=0,1,42").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 2);
+
+    // the plural forms of the other filters are accepted as well.
+    let (response, code) = index
+        .service
+        .get("/tasks?uids=0,1&types=indexCreation&statuses=succeeded&indexUids=test")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 1);
+    assert_eq!(response["results"][0]["uid"], 0);
+
+    let (response, code) = index.service.get("/tasks?statuses=done").await;
+    assert_eq!(code, 400, "{}", response);
+}
+
+#[actix_rt::test]
+async fn list_tasks_date_filtered() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index
+        .service
+        .get("/tasks?afterEnqueuedAt=2000-01-01T00:00:00Z&beforeFinishedAt=3000-01-01T00:00:00Z")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 1);
+
+    let (response, code) = index
+        .service
+        .get("/tasks?afterStartedAt=3000-01-01T00:00:00Z")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 0);
+
+    let (response, code) = index
+        .service
+        .get("/tasks?beforeEnqueuedAt=2000-01-01T00:00:00Z")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 0);
+
+    let (response, code) = index.service.get("/tasks?afterEnqueuedAt=yesterday").await;
+    assert_eq!(code, 400, "{}", response);
+}
+
+#[actix_rt::test]
+async fn cancel_tasks() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index
+        .service
+        .post("/tasks/cancel?uids=0&reason=wrong%20index", json!(null))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    assert_eq!(response["type"], "taskCancelation");
+    assert_eq!(response["indexUid"], json!(null));
+
+    let task_uid = response["taskUid"].as_u64().unwrap();
+    let response = index.wait_task(task_uid).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["details"]["matchedTasks"], 1);
+    assert_eq!(response["details"]["reason"], "wrong index");
+    // the index creation was already processed and can't be canceled anymore.
+    assert_eq!(response["details"]["canceledTasks"], 0);
+
+    let (response, code) = index.get_task(0).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["status"], "succeeded");
+
+    let (response, code) = index.filtered_tasks(&[], &["canceled"]).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 0);
+
+    let (response, code) = index
+        .service
+        .post("/tasks/cancel?uids=42", json!(null))
+        .await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], "task_not_found");
+}
+
+#[actix_rt::test]
+async fn cancel_tasks_by_query() {
+    let server = Server::new().await;
+    let meilisearch = &server.service.meilisearch;
+    let index = server.index("test");
+    index.create(None).await;
+    index.add_documents(json!([{ "id": 1 }]), None).await;
+    index.wait_task(1).await;
+
+    // the dumps stay enqueued until they are canceled.
+    meilisearch.hold_kind(HeldKind::Dump).await;
+    let mut dumps = Vec::new();
+    for _ in 0..2 {
+        dumps.push(meilisearch.register_dump_task(None).await.unwrap().id);
+    }
+
+    let (response, code) = index
+        .service
+        .post("/tasks/cancel?types=dumpCreation", json!(null))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    assert_eq!(response["type"], "taskCancelation");
+
+    let task_uid = response["taskUid"].as_u64().unwrap();
+    let response = index.wait_task(task_uid).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["details"]["matchedTasks"], 2);
+    assert_eq!(response["details"]["canceledTasks"], 2);
+
+    for dump in dumps {
+        let (response, code) = index.get_task(dump as u64).await;
+        assert_eq!(code, 200, "{}", response);
+        assert_eq!(response["status"], "canceled", "{}", response);
+        assert_eq!(response["canceledBy"], task_uid);
+    }
+
+    let (response, code) = index.service.get("/tasks?status=canceled").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 2);
+
+    // the tasks of the index never matched.
+    for uid in 0..2 {
+        let (response, _code) = index.get_task(uid).await;
+        assert_eq!(response["status"], "succeeded");
+    }
+
+    let (response, code) = index.service.post("/tasks/cancel", json!(null)).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "bad_request");
+}
+
+#[actix_rt::test]
+async fn document_addition_enqueue_position() {
+    let server = Server::new().await;
+    let meilisearch = &server.service.meilisearch;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index.add_documents(json!([{ "id": 1 }]), None).await;
+    assert_eq!(code, 202, "{}", response);
+    assert_eq!(response["enqueuePosition"], 0);
+    index.wait_task(1).await;
+
+    // the held dumps stay ahead of the next additions.
+    meilisearch.hold_kind(HeldKind::Dump).await;
+    for _ in 0..2 {
+        meilisearch.register_dump_task(None).await.unwrap();
+    }
+
+    let (response, code) = index.add_documents(json!([{ "id": 2 }]), None).await;
+    assert_eq!(code, 202, "{}", response);
+    assert_eq!(response["enqueuePosition"], 2);
+}
+
 macro_rules! assert_valid_summarized_task {
     ($response:expr, $task_type:literal, $index:literal) => {{
         assert_eq!($response.as_object().unwrap().len(), 5);
@@ -216,10 +514,15 @@ async fn test_summarized_task_view() {
     let (response, _) = index.update_settings(json!({})).await;
     assert_valid_summarized_task!(response, "settingsUpdate", "test");
 
-    let (response, _) = index.update_documents(json!([{"id": 1}]), None).await;
+    // The document additions also return their position in the queue.
+    let (mut response, _) = index.update_documents(json!([{"id": 1}]), None).await;
+    let position = response.as_object_mut().unwrap().remove("enqueuePosition");
+    assert!(position.unwrap().as_u64().is_some());
     assert_valid_summarized_task!(response, "documentAdditionOrUpdate", "test");
 
-    let (response, _) = index.add_documents(json!([{"id": 1}]), None).await;
+    let (mut response, _) = index.add_documents(json!([{"id": 1}]), None).await;
+    let position = response.as_object_mut().unwrap().remove("enqueuePosition");
+    assert!(position.unwrap().as_u64().is_some());
     assert_valid_summarized_task!(response, "documentAdditionOrUpdate", "test");
 
     let (response, _) = index.delete_document(1).await;
@@ -231,3 +534,92 @@ async fn test_summarized_task_view() {
     let (response, _) = index.delete().await;
     assert_valid_summarized_task!(response, "indexDeletion", "test");
 }
+
+#[actix_rt::test]
+async fn wait_for_task() {
+    let server = Server::new().await;
+    let meilisearch = &server.service.meilisearch;
+    let (task, code) = server.index("test").create(None).await;
+    assert_eq!(code, 202);
+    let uid = task["taskUid"].as_u64().unwrap() as u32;
+
+    let task = meilisearch
+        .wait_for_task(uid, Duration::from_secs(10))
+        .await
+        .unwrap();
+    assert_eq!(task.id, uid);
+    assert!(task.is_finished());
+
+    // a finished task is returned right away.
+    let task = meilisearch
+        .wait_for_task(uid, Duration::from_millis(1))
+        .await
+        .unwrap();
+    assert!(task.is_finished());
+
+    // a held dump is never processed.
+    meilisearch.hold_kind(HeldKind::Dump).await;
+    let dump = meilisearch.register_dump_task(None).await.unwrap();
+    let error = meilisearch
+        .wait_for_task(dump.id, Duration::from_millis(100))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        format!("Task `{}` is still not finished after 100ms.", dump.id)
+    );
+
+    let error = meilisearch
+        .wait_for_task(42, Duration::from_secs(1))
+        .await
+        .unwrap_err();
+    assert_eq!(error.to_string(), "Task `42` not found.");
+}
+
+#[actix_rt::test]
+async fn read_your_writes() {
+    let server = Server::new().await;
+    let meilisearch = &server.service.meilisearch;
+    let addition = |allow_index_creation| {
+        let payload: Payload = Box::new(futures::stream::iter(vec![Ok(Bytes::from(
+            r#"[{ "id": 1, "name": "kefir" }]"#,
+        ))]));
+        Update::DocumentAddition {
+            payload,
+            primary_key: None,
+            method: IndexDocumentsMethod::ReplaceDocuments,
+            format: DocumentAdditionFormat::Json,
+            allow_index_creation,
+        }
+    };
+
+    let (_, token) = meilisearch
+        .register_update("test".to_string(), addition(true))
+        .await
+        .unwrap();
+    meilisearch
+        .wait_for_consistency(token, Duration::from_secs(10))
+        .await
+        .unwrap();
+    let (document, code) = server.index("test").get_document(1, None).await;
+    assert_eq!(code, 200, "{}", document);
+    assert_eq!(document, json!({ "id": 1, "name": "kefir" }));
+
+    // the write of a failed task is never visible.
+    let (task, token) = meilisearch
+        .register_update("missing".to_string(), addition(false))
+        .await
+        .unwrap();
+    let error = meilisearch
+        .wait_for_consistency(token, Duration::from_secs(10))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "Task `{}` failed or was canceled, its write was not applied.",
+            task.id
+        )
+    );
+    assert!(matches!(error.error_code(), Code::WriteNotApplied));
+}
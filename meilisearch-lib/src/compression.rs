@@ -1,26 +1,89 @@
 use std::fs::{create_dir_all, File};
-use std::io::Write;
+use std::io::{BufRead, BufReader, Read, Write};
 use std::path::Path;
 
 use flate2::{read::GzDecoder, write::GzEncoder, Compression};
 use tar::{Archive, Builder};
 
+use crate::options::CompressionLevel;
+
+/// The first bytes of a zstd frame.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
 pub fn to_tar_gz(src: impl AsRef<Path>, dest: impl AsRef<Path>) -> anyhow::Result<()> {
+    to_tar(src, dest, CompressionLevel::Gzip)
+}
+
+/// Archives the directory `src` into the file `dest`, compressed as described by `level`.
+pub fn to_tar(
+    src: impl AsRef<Path>,
+    dest: impl AsRef<Path>,
+    level: CompressionLevel,
+) -> anyhow::Result<()> {
     let mut f = File::create(dest)?;
-    let gz_encoder = GzEncoder::new(&mut f, Compression::default());
-    let mut tar_encoder = Builder::new(gz_encoder);
-    tar_encoder.append_dir_all(".", src)?;
-    let gz_encoder = tar_encoder.into_inner()?;
-    gz_encoder.finish()?;
+    match level {
+        CompressionLevel::Gzip => {
+            let gz_encoder = GzEncoder::new(&mut f, Compression::default());
+            let mut tar_encoder = Builder::new(gz_encoder);
+            tar_encoder.append_dir_all(".", src)?;
+            let gz_encoder = tar_encoder.into_inner()?;
+            gz_encoder.finish()?;
+        }
+        CompressionLevel::Zstd(level) => {
+            let zstd_encoder = zstd::Encoder::new(&mut f, level)?;
+            let mut tar_encoder = Builder::new(zstd_encoder);
+            tar_encoder.append_dir_all(".", src)?;
+            let zstd_encoder = tar_encoder.into_inner()?;
+            zstd_encoder.finish()?;
+        }
+    }
     f.flush()?;
     Ok(())
 }
 
+/// Unpacks the archive `src` into the directory `dest`. The archive is either compressed with
+/// gzip or with zstd, which is detected from its first bytes.
 pub fn from_tar_gz(src: impl AsRef<Path>, dest: impl AsRef<Path>) -> anyhow::Result<()> {
-    let f = File::open(&src)?;
-    let gz = GzDecoder::new(f);
-    let mut ar = Archive::new(gz);
+    let mut f = BufReader::new(File::open(&src)?);
+    let is_zstd = f.fill_buf()?.starts_with(&ZSTD_MAGIC);
+    let decoder: Box<dyn Read> = if is_zstd {
+        Box::new(zstd::Decoder::with_buffer(f)?)
+    } else {
+        Box::new(GzDecoder::new(f))
+    };
+    let mut ar = Archive::new(decoder);
     create_dir_all(&dest)?;
     ar.unpack(&dest)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn unpack_archives_of_any_compression() {
+        let src = tempfile::tempdir().unwrap();
+        fs::write(
+            src.path().join("metadata.json"),
+            r#"{ "dumpVersion": "V5" }"#,
+        )
+        .unwrap();
+
+        for level in [CompressionLevel::Gzip, CompressionLevel::Zstd(1)] {
+            let archive = tempfile::NamedTempFile::new().unwrap();
+            to_tar(src.path(), archive.path(), level).unwrap();
+
+            let dest = tempfile::tempdir().unwrap();
+            from_tar_gz(archive.path(), dest.path()).unwrap();
+            assert_eq!(
+                fs::read_to_string(dest.path().join("metadata.json")).unwrap(),
+                r#"{ "dumpVersion": "V5" }"#,
+                "{}",
+                level
+            );
+        }
+    }
+}
@@ -1,22 +1,24 @@
 use std::borrow::Borrow;
 use std::fmt::{self, Debug, Display};
-use std::io::{self, BufReader, Read, Seek, Write};
+use std::io::{self, BufRead, BufReader, Cursor, Read, Seek, Write};
 
 use either::Either;
 use meilisearch_types::error::{Code, ErrorCode};
 use meilisearch_types::internal_error;
 use milli::documents::{DocumentsBatchBuilder, Error};
 use milli::Object;
-use serde::Deserialize;
+use serde::de::{Error as _, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
 use serde_json::error::Category;
 
 type Result<T> = std::result::Result<T, DocumentFormatError>;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum PayloadType {
     Ndjson,
     Json,
     Csv,
+    Tsv,
 }
 
 impl fmt::Display for PayloadType {
@@ -25,6 +27,7 @@ impl fmt::Display for PayloadType {
             PayloadType::Ndjson => f.write_str("ndjson"),
             PayloadType::Json => f.write_str("json"),
             PayloadType::Csv => f.write_str("csv"),
+            PayloadType::Tsv => f.write_str("tsv"),
         }
     }
 }
@@ -33,6 +36,52 @@ impl fmt::Display for PayloadType {
 pub enum DocumentFormatError {
     Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
     MalformedPayload(Error, PayloadType),
+    /// The record at index `record` of an NDJSON payload is malformed, `snippet` is the content
+    /// of the line where the error was found, which starts at the byte `line_offset` of the
+    /// payload.
+    MalformedNdjsonRecord {
+        error: serde_json::Error,
+        record: usize,
+        line_offset: u64,
+        snippet: String,
+    },
+    /// The document at index `document` of a payload has `fields` fields, more than the `max`
+    /// allowed per document. `id` is the value of its primary key, when one can be inferred.
+    TooManyFields {
+        document: usize,
+        id: Option<String>,
+        fields: usize,
+        max: usize,
+    },
+    /// The header of a CSV or TSV payload has `columns` columns, more than the `max` fields allowed
+    /// per document.
+    TooManyColumns {
+        payload_type: PayloadType,
+        columns: usize,
+        max: usize,
+    },
+}
+
+fn json_error_message(se: &serde_json::Error) -> String {
+    let mut message = match se.classify() {
+        Category::Data => "data are neither an object nor a list of objects".to_string(),
+        _ => se.to_string(),
+    };
+
+    // https://github.com/meilisearch/meilisearch/issues/2107
+    // The user input maybe insanely long. We need to truncate it.
+    let ellipsis = "...";
+    let trim_input_prefix_len = 50;
+    let trim_input_suffix_len = 85;
+
+    if message.len() > trim_input_prefix_len + trim_input_suffix_len + ellipsis.len() {
+        message.replace_range(
+            trim_input_prefix_len..message.len() - trim_input_suffix_len,
+            ellipsis,
+        );
+    }
+
+    message
 }
 
 impl Display for DocumentFormatError {
@@ -40,37 +89,56 @@ impl Display for DocumentFormatError {
         match self {
             Self::Internal(e) => write!(f, "An internal error has occurred: `{}`.", e),
             Self::MalformedPayload(me, b) => match me.borrow() {
-                Error::Json(se) => {
-                    let mut message = match se.classify() {
-                        Category::Data => {
-                            "data are neither an object nor a list of objects".to_string()
-                        }
-                        _ => se.to_string(),
-                    };
-
-                    // https://github.com/meilisearch/meilisearch/issues/2107
-                    // The user input maybe insanely long. We need to truncate it.
-                    let ellipsis = "...";
-                    let trim_input_prefix_len = 50;
-                    let trim_input_suffix_len = 85;
-
-                    if message.len()
-                        > trim_input_prefix_len + trim_input_suffix_len + ellipsis.len()
-                    {
-                        message.replace_range(
-                            trim_input_prefix_len..message.len() - trim_input_suffix_len,
-                            ellipsis,
-                        );
-                    }
-
-                    write!(
-                        f,
-                        "The `{}` payload provided is malformed. `Couldn't serialize document value: {}`.",
-                        b, message
-                )
-                }
+                Error::Json(se) => write!(
+                    f,
+                    "The `{}` payload provided is malformed. `Couldn't serialize document value: {}`.",
+                    b,
+                    json_error_message(se)
+                ),
                 _ => write!(f, "The `{}` payload provided is malformed: `{}`.", b, me),
             },
+            Self::MalformedNdjsonRecord {
+                error,
+                record,
+                line_offset,
+                snippet,
+            } => write!(
+                f,
+                "The `ndjson` payload provided is malformed. `Couldn't serialize document value: {}`. The malformed record is at index {}, on the line starting at byte {}: `{}`.",
+                json_error_message(error),
+                record,
+                line_offset,
+                snippet
+            ),
+            Self::TooManyFields {
+                document,
+                id: Some(id),
+                fields,
+                max,
+            } => write!(
+                f,
+                "The document at index {} with the id `{}` has {} fields, more than the maximum of {} fields per document.",
+                document, id, fields, max
+            ),
+            Self::TooManyFields {
+                document,
+                id: None,
+                fields,
+                max,
+            } => write!(
+                f,
+                "The document at index {} has {} fields, more than the maximum of {} fields per document.",
+                document, fields, max
+            ),
+            Self::TooManyColumns {
+                payload_type,
+                columns,
+                max,
+            } => write!(
+                f,
+                "The header of the `{}` payload provided has {} columns, more than the maximum of {} fields per document.",
+                payload_type, columns, max
+            ),
         }
     }
 }
@@ -91,6 +159,9 @@ impl ErrorCode for DocumentFormatError {
         match self {
             DocumentFormatError::Internal(_) => Code::Internal,
             DocumentFormatError::MalformedPayload(_, _) => Code::MalformedPayload,
+            DocumentFormatError::MalformedNdjsonRecord { .. } => Code::MalformedPayload,
+            DocumentFormatError::TooManyFields { .. } => Code::TooManyDocumentFields,
+            DocumentFormatError::TooManyColumns { .. } => Code::TooManyDocumentFields,
         }
     }
 }
@@ -98,11 +169,89 @@ impl ErrorCode for DocumentFormatError {
 internal_error!(DocumentFormatError: io::Error);
 
 /// Reads CSV from input and write an obkv batch to writer.
-pub fn read_csv(input: impl Read, writer: impl Write + Seek) -> Result<usize> {
+///
+/// The fields are separated by `delimiter` and can be quoted with `quote`, a quoted field can
+/// contain delimiters and newlines. When `has_header` is `false`, the first row is considered as
+/// data and the fields are named `col0`, `col1`, etc. A payload with more than `max_fields` columns
+/// is rejected.
+pub fn read_csv(
+    input: impl Read,
+    writer: impl Write + Seek,
+    delimiter: u8,
+    quote: u8,
+    has_header: bool,
+    max_fields: Option<usize>,
+) -> Result<usize> {
+    read_delimited(
+        input,
+        writer,
+        delimiter,
+        quote,
+        has_header,
+        max_fields,
+        PayloadType::Csv,
+    )
+}
+
+/// Reads TSV from input and write an obkv batch to writer.
+///
+/// The header supports the same type annotations as the CSV one, e.g. `price:number`.
+pub fn read_tsv(
+    input: impl Read,
+    writer: impl Write + Seek,
+    has_header: bool,
+    max_fields: Option<usize>,
+) -> Result<usize> {
+    read_delimited(
+        input,
+        writer,
+        b'\t',
+        b'"',
+        has_header,
+        max_fields,
+        PayloadType::Tsv,
+    )
+}
+
+/// Reads delimiter-separated values from input and write an obkv batch to writer.
+fn read_delimited(
+    input: impl Read,
+    writer: impl Write + Seek,
+    delimiter: u8,
+    quote: u8,
+    has_header: bool,
+    max_fields: Option<usize>,
+    payload_type: PayloadType,
+) -> Result<usize> {
     let mut builder = DocumentsBatchBuilder::new(writer);
 
-    let csv = csv::Reader::from_reader(input);
-    builder.append_csv(csv).map_err(|e| (PayloadType::Csv, e))?;
+    let input = BufReader::new(input);
+    let input = if has_header {
+        Either::Left(input)
+    } else {
+        Either::Right(prepend_generated_header(
+            input,
+            delimiter,
+            quote,
+            payload_type,
+        )?)
+    };
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .quote(quote)
+        .from_reader(input);
+    // Every row has the fields of the header.
+    if let (Some(max), Ok(headers)) = (max_fields, reader.headers()) {
+        if headers.len() > max {
+            return Err(DocumentFormatError::TooManyColumns {
+                payload_type,
+                columns: headers.len(),
+                max,
+            });
+        }
+    }
+    builder.append_csv(reader).map_err(|e| (payload_type, e))?;
 
     let count = builder.documents_count();
     let _ = builder
@@ -113,19 +262,75 @@ pub fn read_csv(input: impl Read, writer: impl Write + Seek) -> Result<usize> {
     Ok(count as usize)
 }
 
+/// Returns a reader over the headerless `input`, preceded by a `col0,col1,...` header with as
+/// many columns as the first row.
+fn prepend_generated_header(
+    mut input: impl BufRead,
+    delimiter: u8,
+    quote: u8,
+    payload_type: PayloadType,
+) -> Result<impl Read> {
+    // a quoted field can contain newlines, the first row only ends on a newline that is preceded
+    // by an even number of quotes, escaped quotes being doubled.
+    let mut first_row = Vec::new();
+    loop {
+        let read = input.read_until(b'\n', &mut first_row)?;
+        let quotes = first_row.iter().filter(|&&byte| byte == quote).count();
+        if read == 0 || quotes % 2 == 0 {
+            break;
+        }
+    }
+
+    let columns = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .quote(quote)
+        .has_headers(false)
+        .from_reader(first_row.as_slice())
+        .records()
+        .next()
+        .transpose()
+        .map_err(|e| (payload_type, Error::Csv(e)))?
+        .map_or(0, |record| record.len());
+
+    let mut header = (0..columns)
+        .map(|i| format!("col{}", i))
+        .collect::<Vec<_>>()
+        .join(&(delimiter as char).to_string());
+    header.push('\n');
+
+    Ok(Cursor::new(header)
+        .chain(Cursor::new(first_row))
+        .chain(input))
+}
+
 /// Reads JSON Lines from input and write an obkv batch to writer.
-pub fn read_ndjson(input: impl Read, writer: impl Write + Seek) -> Result<usize> {
+pub fn read_ndjson(
+    input: impl Read,
+    writer: impl Write + Seek,
+    max_fields: Option<usize>,
+) -> Result<usize> {
     let mut builder = DocumentsBatchBuilder::new(writer);
-    let reader = BufReader::new(input);
+    let mut reader = LineTracker::new(BufReader::new(input));
 
-    for result in serde_json::Deserializer::from_reader(reader).into_iter() {
-        let object = result
-            .map_err(Error::Json)
-            .map_err(|e| (PayloadType::Ndjson, e))?;
-        builder
-            .append_json_object(&object)
-            .map_err(Into::into)
-            .map_err(DocumentFormatError::Internal)?;
+    let mut malformed = None;
+    let stream = serde_json::Deserializer::from_reader(&mut reader).into_iter::<Object>();
+    for (record, result) in stream.enumerate() {
+        match result {
+            Ok(object) => append_object(&mut builder, &object, max_fields)?,
+            Err(error) => {
+                malformed = Some((record, error));
+                break;
+            }
+        }
+    }
+
+    if let Some((record, error)) = malformed {
+        return Err(DocumentFormatError::MalformedNdjsonRecord {
+            error,
+            record,
+            line_offset: reader.line_offset,
+            snippet: reader.into_current_line()?,
+        });
     }
 
     let count = builder.documents_count();
@@ -137,29 +342,102 @@ pub fn read_ndjson(input: impl Read, writer: impl Write + Seek) -> Result<usize>
     Ok(count as usize)
 }
 
+/// The maximum number of characters of a malformed line shown in an error.
+const SNIPPET_MAX_CHARS: usize = 100;
+/// The number of bytes of a line kept to build its snippet, enough to hold the longest snippet and
+/// to tell whether the line is longer.
+const LINE_MAX_BYTES: usize = SNIPPET_MAX_CHARS * 4 + 1;
+
+/// A reader keeping track of the start of the line being read, and of the offset it starts at.
+struct LineTracker<R> {
+    inner: R,
+    line: Vec<u8>,
+    line_offset: u64,
+    offset: u64,
+}
+
+impl<R: BufRead> LineTracker<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            inner,
+            line: Vec::new(),
+            line_offset: 0,
+            offset: 0,
+        }
+    }
+
+    /// Returns the line being read, up to its end, truncated if it is too long.
+    fn into_current_line(mut self) -> io::Result<String> {
+        let remaining = LINE_MAX_BYTES.saturating_sub(self.line.len());
+        (&mut self.inner)
+            .take(remaining as u64)
+            .read_until(b'\n', &mut self.line)?;
+        let line = String::from_utf8_lossy(&self.line);
+        let line = line.trim_end();
+        let mut snippet: String = line.chars().take(SNIPPET_MAX_CHARS).collect();
+        if line.chars().count() > SNIPPET_MAX_CHARS {
+            snippet.push_str("...");
+        }
+
+        Ok(snippet)
+    }
+}
+
+impl<R: Read> Read for LineTracker<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        for &byte in &buf[..read] {
+            self.offset += 1;
+            if byte == b'\n' {
+                self.line.clear();
+                self.line_offset = self.offset;
+            } else if self.line.len() < LINE_MAX_BYTES {
+                self.line.push(byte);
+            }
+        }
+
+        Ok(read)
+    }
+}
+
 /// Reads JSON from input and write an obkv batch to writer.
-pub fn read_json(input: impl Read, writer: impl Write + Seek) -> Result<usize> {
+///
+/// The payload is either a single object or an array of objects, which is told apart by its first
+/// non-whitespace byte. The objects of an array are written one by one as they are read.
+pub fn read_json(
+    input: impl Read,
+    writer: impl Write + Seek,
+    max_fields: Option<usize>,
+) -> Result<usize> {
     let mut builder = DocumentsBatchBuilder::new(writer);
-    let reader = BufReader::new(input);
+    let mut reader = BufReader::new(input);
 
-    #[derive(Deserialize, Debug)]
-    #[serde(transparent)]
-    struct ArrayOrSingleObject {
-        #[serde(with = "either::serde_untagged")]
-        inner: Either<Vec<Object>, Object>,
-    }
+    let is_array = first_non_whitespace_byte(&mut reader)? == Some(b'[');
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    let mut append_error = None;
+    let result = if is_array {
+        deserializer.deserialize_seq(ObjectsAppender {
+            builder: &mut builder,
+            max_fields,
+            error: &mut append_error,
+        })
+    } else {
+        Object::deserialize(&mut deserializer).map(|object| {
+            if let Err(e) = append_object(&mut builder, &object, max_fields) {
+                append_error = Some(e);
+            }
+        })
+    };
 
-    let content: ArrayOrSingleObject = serde_json::from_reader(reader)
+    if let Some(e) = append_error {
+        return Err(e);
+    }
+    // Anything but whitespace after the array or the object is an error.
+    result
+        .and_then(|()| deserializer.end())
         .map_err(Error::Json)
         .map_err(|e| (PayloadType::Json, e))?;
 
-    for object in content.inner.map_right(|o| vec![o]).into_inner() {
-        builder
-            .append_json_object(&object)
-            .map_err(Into::into)
-            .map_err(DocumentFormatError::Internal)?;
-    }
-
     let count = builder.documents_count();
     let _ = builder
         .into_inner()
@@ -168,3 +446,86 @@ pub fn read_json(input: impl Read, writer: impl Write + Seek) -> Result<usize> {
 
     Ok(count as usize)
 }
+
+/// Returns the first non-whitespace byte of `reader` without consuming it.
+fn first_non_whitespace_byte(reader: &mut impl BufRead) -> io::Result<Option<u8>> {
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            return Ok(None);
+        }
+        match buf.iter().position(|b| !b.is_ascii_whitespace()) {
+            Some(position) => return Ok(Some(buf[position])),
+            None => {
+                let len = buf.len();
+                reader.consume(len);
+            }
+        }
+    }
+}
+
+/// Appends `object` to the batch, unless it has more than `max_fields` fields.
+fn append_object<W: Write>(
+    builder: &mut DocumentsBatchBuilder<W>,
+    object: &Object,
+    max_fields: Option<usize>,
+) -> Result<()> {
+    if let Some(max) = max_fields {
+        if object.len() > max {
+            return Err(DocumentFormatError::TooManyFields {
+                document: builder.documents_count() as usize,
+                id: inferred_id(object),
+                fields: object.len(),
+                max,
+            });
+        }
+    }
+
+    builder
+        .append_json_object(object)
+        .map_err(Into::into)
+        .map_err(DocumentFormatError::Internal)
+}
+
+/// Returns the value of the field of `object` that would be inferred as its primary key, i.e. the
+/// first one whose name contains `id`, if any.
+fn inferred_id(object: &Object) -> Option<String> {
+    object
+        .iter()
+        .find(|(name, _)| name.to_lowercase().contains("id"))
+        .map(|(_, value)| match value {
+            serde_json::Value::String(id) => id.clone(),
+            value => value.to_string(),
+        })
+}
+
+/// Appends the objects of a JSON array to a batch as they are deserialized. The error raised
+/// while appending an object, if any, is stored in `error`, since it isn't a deserialization
+/// error.
+struct ObjectsAppender<'a, W> {
+    builder: &'a mut DocumentsBatchBuilder<W>,
+    max_fields: Option<usize>,
+    error: &'a mut Option<DocumentFormatError>,
+}
+
+impl<'de, W: Write> Visitor<'de> for ObjectsAppender<'_, W> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a list of objects")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> std::result::Result<(), A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        while let Some(object) = seq.next_element::<Object>()? {
+            if let Err(e) = append_object(self.builder, &object, self.max_fields) {
+                *self.error = Some(e);
+                return Err(A::Error::custom("the documents could not be written"));
+            }
+        }
+
+        Ok(())
+    }
+}
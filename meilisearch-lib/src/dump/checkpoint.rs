@@ -0,0 +1,91 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+const CHECKPOINT_FILE_NAME: &str = "dump-checkpoint.json";
+
+/// Records the steps of a dump import that were completed, so that an import that failed can be
+/// resumed without redoing them. It is stored in the directory the dump is imported into.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Checkpoint {
+    #[serde(skip)]
+    path: PathBuf,
+    /// Identifies the dump being imported, a checkpoint is never resumed with another dump.
+    dump: String,
+    /// The number of documents loaded in each imported index, by index directory name.
+    indexes: BTreeMap<String, u64>,
+    tasks_replayed: Option<usize>,
+}
+
+impl Checkpoint {
+    /// Opens the checkpoint of the import of the dump identified by `dump` into `dst`. When `dst`
+    /// contains the partial import of another dump, it is cleared and the import starts over.
+    pub fn open(dst: impl AsRef<Path>, dump: String) -> anyhow::Result<Self> {
+        let path = dst.as_ref().join(CHECKPOINT_FILE_NAME);
+
+        if path.exists() {
+            let mut checkpoint: Self = serde_json::from_reader(File::open(&path)?)?;
+            if checkpoint.dump == dump {
+                checkpoint.path = path;
+                return Ok(checkpoint);
+            }
+        }
+
+        if dst.as_ref().exists() {
+            fs::remove_dir_all(&dst)?;
+        }
+        fs::create_dir_all(&dst)?;
+
+        let checkpoint = Self {
+            path,
+            dump,
+            indexes: BTreeMap::new(),
+            tasks_replayed: None,
+        };
+        checkpoint.persist()?;
+
+        Ok(checkpoint)
+    }
+
+    /// Returns the number of documents loaded in the index if it was already imported.
+    pub fn imported_index(&self, name: &str) -> Option<u64> {
+        self.indexes.get(name).copied()
+    }
+
+    pub fn index_imported(&mut self, name: String, documents: u64) -> anyhow::Result<()> {
+        self.indexes.insert(name, documents);
+        self.persist()
+    }
+
+    /// Returns the number of replayed tasks if the tasks were already imported.
+    pub fn replayed_tasks(&self) -> Option<usize> {
+        self.tasks_replayed
+    }
+
+    pub fn tasks_replayed(&mut self, count: usize) -> anyhow::Result<()> {
+        self.tasks_replayed = Some(count);
+        self.persist()
+    }
+
+    /// Removes the checkpoint once the import is complete.
+    pub fn remove(self) -> anyhow::Result<()> {
+        fs::remove_file(&self.path)?;
+        Ok(())
+    }
+
+    /// Writes the checkpoint in a temporary file first so that a failure never leaves it
+    /// half-written.
+    fn persist(&self) -> anyhow::Result<()> {
+        let tmp_path = self.path.with_extension("tmp");
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        serde_json::to_writer(&mut writer, self)?;
+        writer.flush()?;
+        fs::rename(tmp_path, &self.path)?;
+
+        Ok(())
+    }
+}
@@ -37,6 +37,7 @@ impl From<v2::UpdateResult> for TaskResult {
         match other {
             v2::UpdateResult::DocumentsAddition(result) => TaskResult::DocumentAddition {
                 indexed_documents: result.nb_documents as u64,
+                inferred_primary_key: None,
             },
             v2::UpdateResult::DocumentDeletion { deleted } => TaskResult::DocumentDeletion {
                 deleted_documents: deleted,
@@ -25,6 +25,8 @@ impl From<Task> for crate::tasks::task::Task {
             id: other.id,
             content: NewTaskContent::from((other.index_uid, other.content)),
             events: other.events.into_iter().map(Into::into).collect(),
+            retry_of: None,
+            priority: 0,
         }
     }
 }
@@ -139,7 +141,7 @@ impl From<(IndexUid, TaskContent)> for NewTaskContent {
                 index_uid,
                 primary_key,
             },
-            TaskContent::Dump { uid } => NewTaskContent::Dump { uid },
+            TaskContent::Dump { uid } => NewTaskContent::Dump { uid, indexes: None },
         }
     }
 }
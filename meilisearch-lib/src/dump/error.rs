@@ -12,6 +12,12 @@ pub enum DumpError {
     Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
     #[error("{0}")]
     IndexResolver(Box<IndexResolverError>),
+    #[error("Dump `{0}` not found.")]
+    DumpNotFound(String),
+    #[error(
+        "The dump is corrupted, the checksum of its content is `{actual}` instead of `{expected}`."
+    )]
+    CorruptedDump { expected: String, actual: String },
 }
 
 internal_error!(
@@ -37,6 +43,8 @@ impl ErrorCode for DumpError {
         match self {
             DumpError::Internal(_) => Code::Internal,
             DumpError::IndexResolver(e) => e.error_code(),
+            DumpError::DumpNotFound(_) => Code::DumpNotFound,
+            DumpError::CorruptedDump { .. } => Code::Internal,
         }
     }
 }
@@ -26,12 +26,13 @@ mod real {
     use tokio::io::AsyncWriteExt;
 
     use crate::analytics;
-    use crate::compression::to_tar_gz;
+    use crate::compression::to_tar;
     use crate::dump::error::{DumpError, Result};
-    use crate::dump::{MetadataVersion, META_FILE_NAME};
+    use crate::dump::{content_checksum, MetadataVersion, META_FILE_NAME};
     use crate::index_resolver::{
         index_store::IndexStore, meta_store::IndexMetaStore, IndexResolver,
     };
+    use crate::options::CompressionLevel;
     use crate::tasks::TaskStore;
     use crate::update_file_store::UpdateFileStore;
 
@@ -43,6 +44,7 @@ mod real {
         index_db_size: usize,
         env: Arc<Env>,
         index_resolver: Arc<IndexResolver<U, I>>,
+        compression: CompressionLevel,
     }
 
     impl<U, I> DumpHandler<U, I>
@@ -67,10 +69,19 @@ mod real {
                 index_db_size,
                 env,
                 index_resolver,
+                compression: CompressionLevel::default(),
             }
         }
 
-        pub async fn run(&self, uid: String) -> Result<()> {
+        /// Sets how the archives of the dumps are compressed.
+        pub fn with_compression(mut self, compression: CompressionLevel) -> Self {
+            self.compression = compression;
+            self
+        }
+
+        /// Creates the dump `uid`, and returns the number of dumped indexes and documents. When
+        /// `indexes` is set, only these indexes and the tasks targeting them are dumped.
+        pub async fn run(&self, uid: String, indexes: Option<Vec<String>>) -> Result<(usize, u64)> {
             trace!("Performing dump.");
 
             create_dir_all(&self.dump_path).await?;
@@ -78,13 +89,6 @@ mod real {
             let temp_dump_dir = tokio::task::spawn_blocking(tempfile::TempDir::new).await??;
             let temp_dump_path = temp_dump_dir.path().to_owned();
 
-            let meta = MetadataVersion::new_v5(self.index_db_size, self.task_store_size);
-            let meta_path = temp_dump_path.join(META_FILE_NAME);
-
-            let meta_bytes = serde_json::to_vec(&meta)?;
-            let mut meta_file = tokio::fs::File::create(&meta_path).await?;
-            meta_file.write_all(&meta_bytes).await?;
-
             analytics::copy_user_id(&self.db_path, &temp_dump_path);
 
             create_dir_all(&temp_dump_path.join("indexes")).await?;
@@ -100,18 +104,34 @@ mod real {
                 self.env.clone(),
                 &temp_dump_path,
                 self.update_file_store.clone(),
+                indexes.clone(),
             )
             .await?;
-            self.index_resolver.dump(&temp_dump_path).await?;
+            let (index_count, total_documents) =
+                self.index_resolver.dump(&temp_dump_path, indexes).await?;
+
+            // the metadata are written last, since they hold the checksum of everything else.
+            let content_path = temp_dump_path.clone();
+            let checksum =
+                tokio::task::spawn_blocking(move || content_checksum(&content_path)).await??;
+            let meta = MetadataVersion::new_current(self.index_db_size, self.task_store_size)
+                .with_checksum(checksum);
+            let meta_path = temp_dump_path.join(META_FILE_NAME);
+
+            let meta_bytes = serde_json::to_vec(&meta)?;
+            let mut meta_file = tokio::fs::File::create(&meta_path).await?;
+            meta_file.write_all(&meta_bytes).await?;
+            meta_file.flush().await?;
 
             let dump_path = self.dump_path.clone();
+            let compression = self.compression;
             let dump_path = tokio::task::spawn_blocking(move || -> Result<PathBuf> {
                 // for now we simply copy the updates/updates_files
                 // FIXME: We may copy more files than necessary, if new files are added while we are
                 // performing the dump. We need a way to filter them out.
 
                 let temp_dump_file = tempfile::NamedTempFile::new_in(&dump_path)?;
-                to_tar_gz(temp_dump_path, temp_dump_file.path())
+                to_tar(temp_dump_path, temp_dump_file.path(), compression)
                     .map_err(|e| DumpError::Internal(e.into()))?;
 
                 let dump_path = dump_path.join(uid).with_extension("dump");
@@ -123,7 +143,7 @@ mod real {
 
             info!("Created dump in {:?}.", dump_path);
 
-            Ok(())
+            Ok((index_count, total_documents))
         }
     }
 }
@@ -139,6 +159,7 @@ mod test {
     use crate::dump::error::Result;
     use crate::index_resolver::IndexResolver;
     use crate::index_resolver::{index_store::IndexStore, meta_store::IndexMetaStore};
+    use crate::options::CompressionLevel;
     use crate::update_file_store::UpdateFileStore;
 
     use super::*;
@@ -178,10 +199,17 @@ mod test {
                 index_resolver,
             ))
         }
-        pub async fn run(&self, uid: String) -> Result<()> {
+        pub fn with_compression(self, compression: CompressionLevel) -> Self {
+            match self {
+                Self::Real(real) => Self::Real(real.with_compression(compression)),
+                Self::Mock(mocker) => Self::Mock(mocker),
+            }
+        }
+
+        pub async fn run(&self, uid: String, indexes: Option<Vec<String>>) -> Result<(usize, u64)> {
             match self {
-                DumpHandler::Real(real) => real.run(uid).await,
-                DumpHandler::Mock(mocker) => unsafe { mocker.get("run").call(uid) },
+                DumpHandler::Real(real) => real.run(uid, indexes).await,
+                DumpHandler::Mock(mocker) => unsafe { mocker.get("run").call((uid, indexes)) },
             }
         }
     }
@@ -6,7 +6,7 @@ use serde_json::{Deserializer, Value};
 use tempfile::NamedTempFile;
 
 use crate::dump::compat::{self, v2, v3};
-use crate::dump::Metadata;
+use crate::dump::{Checkpoint, ImportProgress, Metadata};
 use crate::options::IndexerOpts;
 
 /// The dump v2 reads the dump folder and patches all the needed file to make it compatible with a
@@ -18,6 +18,8 @@ pub fn load_dump(
     index_db_size: usize,
     update_db_size: usize,
     indexing_options: &IndexerOpts,
+    checkpoint: &mut Checkpoint,
+    progress: &mut dyn FnMut(ImportProgress),
 ) -> anyhow::Result<()> {
     log::info!("Patching dump V2 to dump V3...");
     let indexes_path = src.as_ref().join("indexes");
@@ -48,6 +50,8 @@ pub fn load_dump(
         index_db_size,
         update_db_size,
         indexing_options,
+        checkpoint,
+        progress,
     )
 }
 
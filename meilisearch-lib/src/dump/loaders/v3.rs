@@ -10,7 +10,7 @@ use tempfile::tempdir;
 use uuid::Uuid;
 
 use crate::dump::compat::{self, v3};
-use crate::dump::Metadata;
+use crate::dump::{Checkpoint, ImportProgress, Metadata};
 use crate::index_resolver::meta_store::{DumpEntry, IndexMeta};
 use crate::options::IndexerOpts;
 use crate::tasks::task::TaskId;
@@ -34,6 +34,8 @@ pub fn load_dump(
     index_db_size: usize,
     meta_env_size: usize,
     indexing_options: &IndexerOpts,
+    checkpoint: &mut Checkpoint,
+    progress: &mut dyn FnMut(ImportProgress),
 ) -> anyhow::Result<()> {
     info!("Patching dump V3 to dump V4...");
 
@@ -66,6 +68,8 @@ pub fn load_dump(
         index_db_size,
         meta_env_size,
         indexing_options,
+        checkpoint,
+        progress,
     )
 }
 
@@ -8,7 +8,7 @@ use serde_json::{Deserializer, Map, Value};
 use tempfile::tempdir;
 use uuid::Uuid;
 
-use crate::dump::{compat, Metadata};
+use crate::dump::{compat, Checkpoint, ImportProgress, Metadata};
 use crate::options::IndexerOpts;
 use crate::tasks::task::Task;
 
@@ -19,6 +19,8 @@ pub fn load_dump(
     index_db_size: usize,
     meta_env_size: usize,
     indexing_options: &IndexerOpts,
+    checkpoint: &mut Checkpoint,
+    progress: &mut dyn FnMut(ImportProgress),
 ) -> anyhow::Result<()> {
     info!("Patching dump V4 to dump V5...");
 
@@ -50,6 +52,8 @@ pub fn load_dump(
         index_db_size,
         meta_env_size,
         indexing_options,
+        checkpoint,
+        progress,
     )
 }
 
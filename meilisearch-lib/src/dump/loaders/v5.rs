@@ -1,11 +1,11 @@
 use std::{path::Path, sync::Arc};
 
-use log::info;
+use log::{info, warn};
 use meilisearch_auth::AuthController;
 use milli::heed::EnvOpenOptions;
 
 use crate::analytics;
-use crate::dump::Metadata;
+use crate::dump::{Checkpoint, ImportProgress, Metadata};
 use crate::index_resolver::IndexResolver;
 use crate::options::IndexerOpts;
 use crate::tasks::TaskStore;
@@ -18,6 +18,8 @@ pub fn load_dump(
     index_db_size: usize,
     meta_env_size: usize,
     indexing_options: &IndexerOpts,
+    checkpoint: &mut Checkpoint,
+    progress: &mut dyn FnMut(ImportProgress),
 ) -> anyhow::Result<()> {
     info!(
         "Loading dump from {}, dump database version: {}, dump version: V5",
@@ -29,15 +31,37 @@ pub fn load_dump(
     options.max_dbs(100);
     let env = Arc::new(options.open(&dst)?);
 
+    let mut current = ImportProgress::default();
+
     IndexResolver::load_dump(
         src.as_ref(),
         &dst,
         index_db_size,
         env.clone(),
         indexing_options,
+        checkpoint,
+        &mut |documents| {
+            current.indexes_imported += 1;
+            current.documents_loaded += documents;
+            progress(current);
+        },
     )?;
     UpdateFileStore::load_dump(src.as_ref(), &dst)?;
-    TaskStore::load_dump(&src, env)?;
+    current.tasks_replayed = match checkpoint.replayed_tasks() {
+        Some(count) => count,
+        None => {
+            let loaded = TaskStore::load_dump(&src, env)?;
+            if loaded.skipped > 0 {
+                warn!(
+                    "{} tasks of the dump were skipped, their kind is unknown to this version.",
+                    loaded.skipped
+                );
+            }
+            checkpoint.tasks_replayed(loaded.replayed)?;
+            loaded.replayed
+        }
+    };
+    progress(current);
     AuthController::load_dump(&src, &dst)?;
     analytics::copy_user_id(src.as_ref(), dst.as_ref());
 
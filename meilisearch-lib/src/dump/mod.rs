@@ -1,26 +1,40 @@
+use std::fmt;
 use std::fs::File;
-use std::path::Path;
+use std::io;
+use std::path::{Path, PathBuf};
 
-use anyhow::bail;
+use anyhow::{anyhow, bail, Context};
 use log::info;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
 use time::OffsetDateTime;
+use walkdir::WalkDir;
 
 use tempfile::TempDir;
 
 use crate::compression::from_tar_gz;
+use crate::index::{read_dump_settings, Checked, Settings};
+use crate::index_resolver::meta_store::{DumpEntry, HeedMetaStore};
 use crate::options::IndexerOpts;
 
 use self::loaders::{v2, v3, v4, v5};
 
+pub use checkpoint::Checkpoint;
 pub use handler::{generate_uid, DumpHandler};
 
+mod checkpoint;
 mod compat;
 pub mod error;
 mod handler;
 mod loaders;
 
 const META_FILE_NAME: &str = "metadata.json";
+/// The version of the dumps created by this version of Meilisearch. It's the only place to bump
+/// when the dump format changes, once the new version has its variant in `MetadataVersion` and
+/// its loader, the loaders of the previous versions being kept to import the older dumps.
+pub const CURRENT_DUMP_VERSION: &str = "V5";
+const DUMP_IMPORT_DIR_NAME: &str = "dump-import";
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -30,6 +44,10 @@ pub struct Metadata {
     update_db_size: usize,
     #[serde(with = "time::serde::rfc3339")]
     dump_date: OffsetDateTime,
+    /// The SHA-256 of the content of the dump, see `content_checksum`. The dumps created before it
+    /// was introduced don't have one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    checksum: Option<String>,
 }
 
 impl Metadata {
@@ -39,10 +57,116 @@ impl Metadata {
             index_db_size,
             update_db_size,
             dump_date: OffsetDateTime::now_utc(),
+            checksum: None,
         }
     }
 }
 
+/// Computes the SHA-256 of the content of the dump directory `dir`, which is every file but the
+/// metadata, since they hold the checksum. The files are hashed in order along with their path and
+/// size, so that a missing, renamed, or truncated file changes the checksum.
+pub fn content_checksum(dir: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    for entry in WalkDir::new(dir).sort_by_file_name() {
+        let entry = entry?;
+        if !entry.file_type().is_file()
+            || (entry.depth() == 1 && entry.file_name() == META_FILE_NAME)
+        {
+            continue;
+        }
+
+        let path = entry
+            .path()
+            .strip_prefix(dir)
+            .unwrap_or_else(|_| entry.path());
+        let path: Vec<_> = path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect();
+        hasher.update(path.join("/").as_bytes());
+        hasher.update(entry.metadata()?.len().to_le_bytes());
+        io::copy(&mut File::open(entry.path())?, &mut hasher)?;
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Tracks how far the import of a dump went. A snapshot of it is handed to the progress callback
+/// of [`load_dump`] every time an index is imported and once the tasks have been replayed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ImportProgress {
+    pub indexes_imported: usize,
+    pub documents_loaded: u64,
+    pub tasks_replayed: usize,
+}
+
+impl fmt::Display for ImportProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} indexes imported, {} documents loaded, {} tasks replayed",
+            self.indexes_imported, self.documents_loaded, self.tasks_replayed
+        )
+    }
+}
+
+/// A dump file present in the dumps directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DumpInfo {
+    pub uid: String,
+    pub created_at: OffsetDateTime,
+    pub size: u64,
+}
+
+/// Lists the dumps of `dump_path`, sorted by uid, which is also their creation order. A missing
+/// directory means that no dump was created yet.
+pub fn list_dumps(dump_path: &Path) -> error::Result<Vec<DumpInfo>> {
+    let entries = match std::fs::read_dir(dump_path) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut dumps = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().map_or(true, |ext| ext != "dump") {
+            continue;
+        }
+        let uid = match path.file_stem().and_then(|stem| stem.to_str()) {
+            Some(uid) => uid.to_string(),
+            None => continue,
+        };
+        let metadata = std::fs::metadata(&path)?;
+        if !metadata.is_file() {
+            continue;
+        }
+        // the creation date isn't available on every platform, but a dump is never modified once
+        // persisted.
+        let created_at = metadata.created().or_else(|_| metadata.modified())?;
+        dumps.push(DumpInfo {
+            uid,
+            created_at: created_at.into(),
+            size: metadata.len(),
+        });
+    }
+    dumps.sort_unstable_by(|a, b| a.uid.cmp(&b.uid));
+
+    Ok(dumps)
+}
+
+/// Returns the path of the dump file of `uid` in `dump_path`, failing if there is no such dump.
+pub fn dump_file_path(dump_path: &Path, uid: &str) -> error::Result<PathBuf> {
+    // the uids are generated by `generate_uid`, anything else, like a path, can't be a dump.
+    let is_valid_uid = !uid.is_empty() && uid.chars().all(|c| c.is_ascii_digit() || c == '-');
+    let path = dump_path.join(uid).with_extension("dump");
+    if is_valid_uid && path.is_file() {
+        Ok(path)
+    } else {
+        Err(error::DumpError::DumpNotFound(uid.to_string()))
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct MetadataV1 {
@@ -61,6 +185,27 @@ pub enum MetadataVersion {
 }
 
 impl MetadataVersion {
+    const SUPPORTED_VERSIONS: &'static [&'static str] = &["V1", "V2", "V3", "V4", "V5"];
+
+    /// Parses the content of a dump `metadata.json`, returning a clear error when the dump was
+    /// produced with a dump version this version of Meilisearch doesn't know about.
+    pub fn from_json(meta: Value) -> anyhow::Result<Self> {
+        let version = meta
+            .get("dumpVersion")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned);
+
+        serde_json::from_value(meta).map_err(|e| match version {
+            Some(version) if !Self::SUPPORTED_VERSIONS.contains(&version.as_str()) => anyhow!(
+                "The dump version `{}` is not supported by Meilisearch v{}. Supported dump versions are: {}.",
+                version,
+                env!("CARGO_PKG_VERSION"),
+                Self::SUPPORTED_VERSIONS.join(", "),
+            ),
+            _ => e.into(),
+        })
+    }
+
     pub fn load_dump(
         self,
         src: impl AsRef<Path>,
@@ -68,6 +213,8 @@ impl MetadataVersion {
         index_db_size: usize,
         meta_env_size: usize,
         indexing_options: &IndexerOpts,
+        checkpoint: &mut Checkpoint,
+        progress: &mut dyn FnMut(ImportProgress),
     ) -> anyhow::Result<()> {
         match self {
             MetadataVersion::V1(_meta) => {
@@ -80,6 +227,8 @@ impl MetadataVersion {
                 index_db_size,
                 meta_env_size,
                 indexing_options,
+                checkpoint,
+                progress,
             )?,
             MetadataVersion::V3(meta) => v3::load_dump(
                 meta,
@@ -88,6 +237,8 @@ impl MetadataVersion {
                 index_db_size,
                 meta_env_size,
                 indexing_options,
+                checkpoint,
+                progress,
             )?,
             MetadataVersion::V4(meta) => v4::load_dump(
                 meta,
@@ -96,6 +247,8 @@ impl MetadataVersion {
                 index_db_size,
                 meta_env_size,
                 indexing_options,
+                checkpoint,
+                progress,
             )?,
             MetadataVersion::V5(meta) => v5::load_dump(
                 meta,
@@ -104,15 +257,40 @@ impl MetadataVersion {
                 index_db_size,
                 meta_env_size,
                 indexing_options,
+                checkpoint,
+                progress,
             )?,
         }
 
         Ok(())
     }
 
-    pub fn new_v5(index_db_size: usize, update_db_size: usize) -> Self {
+    /// Returns the metadata of a dump created with the `CURRENT_DUMP_VERSION`.
+    pub fn new_current(index_db_size: usize, update_db_size: usize) -> Self {
         let meta = Metadata::new(index_db_size, update_db_size);
-        Self::V5(meta)
+        let mut meta = serde_json::to_value(meta).expect("metadata are always serializable");
+        meta["dumpVersion"] = Value::from(CURRENT_DUMP_VERSION);
+        Self::from_json(meta).expect("the current dump version must be supported")
+    }
+
+    /// Sets the checksum of the content of the dump, which is ignored by the dumps before V2.
+    pub fn with_checksum(mut self, checksum: String) -> Self {
+        match &mut self {
+            Self::V1(_) => (),
+            Self::V2(meta) | Self::V3(meta) | Self::V4(meta) | Self::V5(meta) => {
+                meta.checksum = Some(checksum)
+            }
+        }
+        self
+    }
+
+    pub fn checksum(&self) -> Option<&str> {
+        match self {
+            Self::V1(_) => None,
+            Self::V2(meta) | Self::V3(meta) | Self::V4(meta) | Self::V5(meta) => {
+                meta.checksum.as_deref()
+            }
+        }
     }
 
     pub fn db_version(&self) -> &str {
@@ -159,20 +337,37 @@ pub fn load_dump(
     index_db_size: usize,
     update_db_size: usize,
     indexer_opts: &IndexerOpts,
+    mut progress: impl FnMut(ImportProgress),
 ) -> anyhow::Result<()> {
-    let empty_db = crate::is_empty_db(&dst_path);
+    let empty_db = crate::is_empty_db(&dst_path) || has_interrupted_import(&dst_path);
     let src_path_exists = src_path.as_ref().exists();
 
     if empty_db && src_path_exists {
-        let (tmp_src, tmp_dst, meta) = extract_dump(&dst_path, &src_path)?;
-        meta.load_dump(
+        let (tmp_src, import_path, meta) = extract_dump(&dst_path, &src_path)?;
+        // a previous import of the same dump that failed is resumed where it stopped.
+        let mut checkpoint = Checkpoint::open(&import_path, serde_json::to_string(&meta)?)?;
+        // keep track of the last reported progress so we can tell how far we got on failure.
+        let mut last_progress = ImportProgress::default();
+        let result = meta.load_dump(
             tmp_src.path(),
-            tmp_dst.path(),
+            &import_path,
             index_db_size,
             update_db_size,
             indexer_opts,
-        )?;
-        persist_dump(&dst_path, tmp_dst)?;
+            &mut checkpoint,
+            &mut |current| {
+                last_progress = current;
+                progress(current);
+            },
+        );
+        result.with_context(|| {
+            format!(
+                "dump import failed after {}, importing the same dump again resumes it",
+                last_progress
+            )
+        })?;
+        checkpoint.remove()?;
+        persist_dump(&dst_path, import_path)?;
         Ok(())
     } else if !empty_db && !ignore_dump_if_db_exists {
         bail!(
@@ -190,10 +385,58 @@ pub fn load_dump(
     }
 }
 
+/// The primary key and the settings of an index of a dump, see `read_indexes_settings`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DumpedIndexSettings {
+    pub uid: String,
+    pub primary_key: Option<String>,
+    pub settings: Settings<Checked>,
+}
+
+/// Reads the settings of the indexes of the dump extracted in `src`, one index at a time and
+/// without reading their documents, e.g. to only migrate the settings of the indexes. The dump
+/// must be of the current version.
+pub fn read_indexes_settings(
+    src: impl AsRef<Path>,
+) -> anyhow::Result<impl Iterator<Item = anyhow::Result<DumpedIndexSettings>>> {
+    let src = src.as_ref().to_path_buf();
+    let meta = File::open(src.join(META_FILE_NAME))?;
+    let meta = MetadataVersion::from_json(serde_json::from_reader(meta)?)?;
+    if meta.version() != CURRENT_DUMP_VERSION {
+        bail!(
+            "Only the settings of the dumps of version {} can be read, this dump is of version {}.",
+            CURRENT_DUMP_VERSION,
+            meta.version()
+        );
+    }
+
+    Ok(HeedMetaStore::read_dump(&src)?.map(move |entry| {
+        let DumpEntry { uid, index_meta } = entry?;
+        let index_path = src.join("indexes").join(index_meta.uuid.to_string());
+        let (primary_key, settings) = read_dump_settings(index_path)?;
+        Ok(DumpedIndexSettings {
+            uid,
+            primary_key,
+            settings,
+        })
+    }))
+}
+
+/// Returns whether the only content of the database is the directory of a dump import that failed.
+fn has_interrupted_import(db_path: impl AsRef<Path>) -> bool {
+    match db_path.as_ref().read_dir() {
+        Ok(mut entries) => matches!(
+            (entries.next(), entries.next()),
+            (Some(Ok(entry)), None) if entry.file_name() == DUMP_IMPORT_DIR_NAME
+        ),
+        Err(_) => false,
+    }
+}
+
 fn extract_dump(
     dst_path: impl AsRef<Path>,
     src_path: impl AsRef<Path>,
-) -> anyhow::Result<(TempDir, TempDir, MetadataVersion)> {
+) -> anyhow::Result<(TempDir, PathBuf, MetadataVersion)> {
     // Setup a temp directory path in the same path as the database, to prevent cross devices
     // references.
     let temp_path = dst_path
@@ -205,17 +448,31 @@ fn extract_dump(
     let tmp_src = tempfile::tempdir_in(temp_path)?;
     let tmp_src_path = tmp_src.path();
 
-    from_tar_gz(&src_path, tmp_src_path)?;
+    from_tar_gz(&src_path, tmp_src_path)
+        .context("the dump archive could not be extracted, it may be truncated or corrupted")?;
 
     let meta_path = tmp_src_path.join(META_FILE_NAME);
     let mut meta_file = File::open(&meta_path)?;
-    let meta: MetadataVersion = serde_json::from_reader(&mut meta_file)?;
+    let meta = MetadataVersion::from_json(serde_json::from_reader(&mut meta_file)?)?;
+
+    // nothing is imported from a corrupted dump.
+    if let Some(expected) = meta.checksum() {
+        let actual = content_checksum(tmp_src_path)?;
+        if actual != expected {
+            return Err(error::DumpError::CorruptedDump {
+                expected: expected.to_string(),
+                actual,
+            }
+            .into());
+        }
+    }
 
     if !dst_path.as_ref().exists() {
         std::fs::create_dir_all(dst_path.as_ref())?;
     }
 
-    let tmp_dst = tempfile::tempdir_in(dst_path.as_ref())?;
+    // unlike a temporary directory, the import directory is kept when the import fails.
+    let import_path = dst_path.as_ref().join(DUMP_IMPORT_DIR_NAME);
 
     info!(
         "Loading dump {}, dump database version: {}, dump version: {}",
@@ -226,13 +483,11 @@ fn extract_dump(
         meta.version()
     );
 
-    Ok((tmp_src, tmp_dst, meta))
+    Ok((tmp_src, import_path, meta))
 }
 
-fn persist_dump(dst_path: impl AsRef<Path>, tmp_dst: TempDir) -> anyhow::Result<()> {
-    let persisted_dump = tmp_dst.into_path();
-
-    // Delete everything in the `data.ms` except the tempdir.
+fn persist_dump(dst_path: impl AsRef<Path>, persisted_dump: PathBuf) -> anyhow::Result<()> {
+    // Delete everything in the `data.ms` except the import directory.
     if dst_path.as_ref().exists() {
         for file in dst_path.as_ref().read_dir().unwrap() {
             let file = file.unwrap().path();
@@ -248,15 +503,239 @@ fn persist_dump(dst_path: impl AsRef<Path>, tmp_dst: TempDir) -> anyhow::Result<
         }
     }
 
-    // Move the whole content of the tempdir into the `data.ms`.
+    // Move the whole content of the import directory into the `data.ms`.
     for file in persisted_dump.read_dir().unwrap() {
         let file = file.unwrap().path();
 
         std::fs::rename(&file, &dst_path.as_ref().join(file.file_name().unwrap()))?;
     }
 
-    // Delete the empty tempdir.
+    // Delete the empty import directory.
     std::fs::remove_dir_all(&persisted_dump)?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use crate::compression::to_tar_gz;
+    use crate::index::Unchecked;
+
+    use super::*;
+
+    /// Returns the path of a v5 dump of two indexes of 10 documents each, and of 5 tasks.
+    fn test_dump_path() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/assets/v5_v0.28.0_test_dump.dump")
+    }
+
+    #[test]
+    fn load_dump_reports_progress_in_order() {
+        let dump_path = test_dump_path();
+        let tmp = tempfile::tempdir().unwrap();
+        let db_path = tmp.path().join("data.ms");
+
+        let mut steps = Vec::new();
+        load_dump(
+            &db_path,
+            &dump_path,
+            false,
+            false,
+            4096 * 100000,
+            4096 * 100000,
+            &IndexerOpts::default(),
+            |progress| steps.push(progress),
+        )
+        .unwrap();
+
+        let expected = |indexes_imported, documents_loaded, tasks_replayed| ImportProgress {
+            indexes_imported,
+            documents_loaded,
+            tasks_replayed,
+        };
+
+        assert_eq!(
+            steps,
+            vec![expected(1, 10, 0), expected(2, 20, 0), expected(2, 20, 5)]
+        );
+    }
+
+    #[test]
+    fn resume_failed_dump_import() {
+        let dump_path = test_dump_path();
+        let tmp = tempfile::tempdir().unwrap();
+        let dump_dir = tmp.path().join("dump");
+        from_tar_gz(&dump_path, &dump_dir).unwrap();
+        let documents = |index: &str| dump_dir.join("indexes").join(index).join("documents.jsonl");
+        // the indexes are imported in this order.
+        let first = "da4c3275-0803-4235-bf6a-fbdb33451b0d";
+        let second = "f41ded81-d56c-4cad-95bf-3c2ae09fcd41";
+        let second_documents = std::fs::read(documents(second)).unwrap();
+
+        let db_path = tmp.path().join("data.ms");
+        let import = |name: &str| {
+            let path = tmp.path().join(name);
+            to_tar_gz(&dump_dir, &path).unwrap();
+            let mut steps = Vec::new();
+            let result = load_dump(
+                &db_path,
+                &path,
+                false,
+                false,
+                4096 * 100000,
+                4096 * 100000,
+                &IndexerOpts::default(),
+                |progress| steps.push(progress),
+            );
+            (result, steps)
+        };
+
+        // the import fails after the first index.
+        std::fs::write(documents(second), "not json").unwrap();
+        let (result, _) = import("broken.dump");
+        let error = result.unwrap_err().to_string();
+        assert!(error.starts_with("dump import failed after 1 indexes imported"));
+
+        // the first index is not imported again, so breaking it doesn't prevent the import.
+        std::fs::write(documents(first), "not json").unwrap();
+        std::fs::write(documents(second), second_documents).unwrap();
+        let (result, steps) = import("resumed.dump");
+        result.unwrap();
+
+        let expected = |indexes_imported, documents_loaded, tasks_replayed| ImportProgress {
+            indexes_imported,
+            documents_loaded,
+            tasks_replayed,
+        };
+        assert_eq!(
+            steps,
+            vec![expected(1, 10, 0), expected(2, 20, 0), expected(2, 20, 5)]
+        );
+        assert!(db_path.join("indexes").join(first).exists());
+        assert!(db_path.join("indexes").join(second).exists());
+        assert!(!db_path.join(DUMP_IMPORT_DIR_NAME).exists());
+    }
+
+    #[test]
+    fn corrupted_dumps_are_rejected() {
+        let dump_path = test_dump_path();
+        let tmp = tempfile::tempdir().unwrap();
+        let dump_dir = tmp.path().join("dump");
+        from_tar_gz(&dump_path, &dump_dir).unwrap();
+
+        let meta_path = dump_dir.join(META_FILE_NAME);
+        let meta = File::open(&meta_path).unwrap();
+        let meta = MetadataVersion::from_json(serde_json::from_reader(meta).unwrap()).unwrap();
+        let meta = meta.with_checksum(content_checksum(&dump_dir).unwrap());
+        std::fs::write(&meta_path, serde_json::to_vec(&meta).unwrap()).unwrap();
+
+        let archive = tmp.path().join("valid.dump");
+        to_tar_gz(&dump_dir, &archive).unwrap();
+        let (_, _, meta) = extract_dump(tmp.path().join("valid.ms"), &archive).unwrap();
+        assert!(meta.checksum().is_some());
+
+        // truncate the documents of an index.
+        let documents = dump_dir
+            .join("indexes/da4c3275-0803-4235-bf6a-fbdb33451b0d")
+            .join("documents.jsonl");
+        let content = std::fs::read(&documents).unwrap();
+        std::fs::write(&documents, &content[..content.len() / 2]).unwrap();
+
+        let archive = tmp.path().join("corrupted.dump");
+        to_tar_gz(&dump_dir, &archive).unwrap();
+        let result = extract_dump(tmp.path().join("corrupted.ms"), &archive);
+        assert!(matches!(
+            result.unwrap_err().downcast_ref::<error::DumpError>(),
+            Some(error::DumpError::CorruptedDump { .. })
+        ));
+    }
+
+    #[test]
+    fn read_settings_without_documents() {
+        let dump_path = test_dump_path();
+        let tmp = tempfile::tempdir().unwrap();
+        let dump_dir = tmp.path().join("dump");
+        from_tar_gz(&dump_path, &dump_dir).unwrap();
+
+        let index_dir = |uuid: &str| dump_dir.join("indexes").join(uuid);
+        let test = index_dir("f41ded81-d56c-4cad-95bf-3c2ae09fcd41");
+        let test2 = index_dir("da4c3275-0803-4235-bf6a-fbdb33451b0d");
+        let expected_settings = |index_dir: &Path| {
+            let meta: Value =
+                serde_json::from_slice(&std::fs::read(index_dir.join("meta.json")).unwrap())
+                    .unwrap();
+            serde_json::from_value::<Settings<Unchecked>>(meta["settings"].clone())
+                .unwrap()
+                .check()
+        };
+        let expected = vec![
+            DumpedIndexSettings {
+                uid: "test".to_string(),
+                primary_key: Some("id".to_string()),
+                settings: expected_settings(&test),
+            },
+            DumpedIndexSettings {
+                uid: "test2".to_string(),
+                primary_key: Some("id".to_string()),
+                settings: expected_settings(&test2),
+            },
+        ];
+        assert_ne!(expected[0].settings, expected[1].settings);
+
+        // the documents are never read.
+        std::fs::remove_file(test.join("documents.jsonl")).unwrap();
+        std::fs::write(test2.join("documents.jsonl"), "not json").unwrap();
+
+        let settings = read_indexes_settings(&dump_dir)
+            .unwrap()
+            .collect::<anyhow::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(settings, expected);
+    }
+
+    #[test]
+    fn unsupported_dump_version() {
+        let meta = serde_json::json!({
+            "dumpVersion": "V6",
+            "dbVersion": "1.0.0",
+        });
+
+        let error = MetadataVersion::from_json(meta).unwrap_err().to_string();
+        assert!(error.starts_with("The dump version `V6` is not supported"));
+        assert!(error.ends_with("Supported dump versions are: V1, V2, V3, V4, V5."));
+
+        let meta = serde_json::json!({
+            "dumpVersion": "V5",
+            "dbVersion": "0.29.0",
+            "indexDbSize": 1024,
+            "updateDbSize": 1024,
+            "dumpDate": "2022-09-01T00:00:00Z",
+        });
+        let meta = MetadataVersion::from_json(meta).unwrap();
+        assert_eq!(meta.version(), "V5");
+    }
+
+    #[test]
+    fn current_dump_version() {
+        assert_eq!(
+            MetadataVersion::SUPPORTED_VERSIONS.last(),
+            Some(&CURRENT_DUMP_VERSION)
+        );
+
+        let meta = MetadataVersion::new_current(1024, 1024);
+        let written = serde_json::to_value(&meta).unwrap();
+        assert_eq!(written["dumpVersion"], CURRENT_DUMP_VERSION);
+        let meta = MetadataVersion::from_json(written).unwrap();
+        assert_eq!(meta.version(), CURRENT_DUMP_VERSION);
+
+        // the dumps of the next version are rejected until it's supported.
+        let current: u32 = CURRENT_DUMP_VERSION[1..].parse().unwrap();
+        let next_version = format!("V{}", current + 1);
+        let mut written = serde_json::to_value(&meta).unwrap();
+        written["dumpVersion"] = Value::from(next_version.clone());
+        let error = MetadataVersion::from_json(written).unwrap_err().to_string();
+        assert!(error.starts_with(&format!(
+            "The dump version `{}` is not supported",
+            next_version
+        )));
+    }
+}
@@ -1,19 +1,21 @@
 use std::fs::{create_dir_all, File};
-use std::io::{BufReader, Seek, SeekFrom, Write};
+use std::io::{BufReader, BufWriter, Seek, SeekFrom, Write};
 use std::path::Path;
 
 use anyhow::Context;
-use indexmap::IndexMap;
 use milli::documents::DocumentsBatchReader;
 use milli::heed::{EnvOpenOptions, RoTxn};
 use milli::update::{IndexDocumentsConfig, IndexerConfig};
+use milli::{obkv_to_json, Filter};
 use serde::{Deserialize, Serialize};
+use tempfile::NamedTempFile;
 
 use crate::document_formats::read_ndjson;
 use crate::index::updates::apply_settings_to_builder;
 
 use super::error::Result;
-use super::{index::Index, Settings, Unchecked};
+use super::index::{Document, Index};
+use super::{Checked, Settings, Unchecked};
 
 #[derive(Serialize, Deserialize)]
 struct DumpMeta {
@@ -24,45 +26,130 @@ struct DumpMeta {
 const META_FILE_NAME: &str = "meta.json";
 const DATA_FILE_NAME: &str = "documents.jsonl";
 
+/// Reads the primary key and the settings of the index dumped in `src`, without reading its
+/// documents.
+pub fn read_dump_settings(src: impl AsRef<Path>) -> Result<(Option<String>, Settings<Checked>)> {
+    let meta_file = File::open(src.as_ref().join(META_FILE_NAME))?;
+    let DumpMeta {
+        settings,
+        primary_key,
+    } = serde_json::from_reader(BufReader::new(meta_file))?;
+
+    Ok((primary_key, settings.check()))
+}
+
+/// Writes documents as NDJSON as soon as they are produced, so that the documents of a huge index
+/// never have to be collected in memory.
+pub struct DocumentsDumper<W> {
+    writer: W,
+    count: u64,
+}
+
+impl<W: Write> DocumentsDumper<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer, count: 0 }
+    }
+
+    pub fn push_document(&mut self, document: &Document) -> Result<()> {
+        serde_json::to_writer(&mut self.writer, document)?;
+        self.writer.write_all(b"\n")?;
+        self.count += 1;
+
+        Ok(())
+    }
+
+    /// Writes the documents yielded by `documents` one by one, stopping at the first error.
+    pub fn push_documents(
+        &mut self,
+        documents: impl IntoIterator<Item = Result<Document>>,
+    ) -> Result<()> {
+        for document in documents {
+            self.push_document(&document?)?;
+        }
+
+        Ok(())
+    }
+
+    /// The number of documents written so far.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
 impl Index {
-    pub fn dump(&self, path: impl AsRef<Path>) -> Result<()> {
+    /// Dumps the index in `path`, and returns the number of dumped documents.
+    pub fn dump(&self, path: impl AsRef<Path>) -> Result<u64> {
         // acquire write txn make sure any ongoing write is finished before we start.
         let txn = self.write_txn()?;
         let path = path.as_ref().join(format!("indexes/{}", self.uuid));
 
         create_dir_all(&path)?;
 
-        self.dump_documents(&txn, &path)?;
+        let documents_count = self.dump_documents(&txn, &path)?;
         self.dump_meta(&txn, &path)?;
 
-        Ok(())
+        Ok(documents_count)
     }
 
-    fn dump_documents(&self, txn: &RoTxn, path: impl AsRef<Path>) -> Result<()> {
+    fn dump_documents(&self, txn: &RoTxn, path: impl AsRef<Path>) -> Result<u64> {
         let document_file_path = path.as_ref().join(DATA_FILE_NAME);
-        let mut document_file = File::create(&document_file_path)?;
+        let document_file = BufWriter::new(File::create(&document_file_path)?);
 
-        let documents = self.all_documents(txn)?;
         let fields_ids_map = self.fields_ids_map(txn)?;
+        let all_fields: Vec<_> = fields_ids_map.iter().map(|(id, _)| id).collect();
+        let documents = self.all_documents(txn)?.map(|entry| -> Result<Document> {
+            let (_, obkv) = entry?;
+            Ok(obkv_to_json(&all_fields, &fields_ids_map, obkv)?)
+        });
+
+        let mut dumper = DocumentsDumper::new(document_file);
+        dumper.push_documents(documents)?;
+        let documents_count = dumper.count();
+        dumper.into_inner().flush()?;
+
+        Ok(documents_count)
+    }
 
-        // dump documents
-        let mut json_map = IndexMap::new();
-        for document in documents {
-            let (_, reader) = document?;
+    /// Writes the documents of the index matching `filter` as NDJSON in the file at `path`, and
+    /// returns the number of exported documents. The file is only created once all the documents
+    /// have been written.
+    pub fn export_documents(&self, path: impl AsRef<Path>, filter: Option<&str>) -> Result<u64> {
+        let txn = self.read_txn()?;
 
-            for (fid, bytes) in reader.iter() {
-                if let Some(name) = fields_ids_map.name(fid) {
-                    json_map.insert(name, serde_json::from_slice::<serde_json::Value>(bytes)?);
-                }
-            }
+        let candidates = match filter.map(Filter::from_str).transpose()?.flatten() {
+            Some(filter) => filter.evaluate(&txn, self)?,
+            None => self.documents_ids(&txn)?,
+        };
 
-            serde_json::to_writer(&mut document_file, &json_map)?;
-            document_file.write_all(b"\n")?;
+        let fields_ids_map = self.fields_ids_map(&txn)?;
+        let all_fields: Vec<_> = fields_ids_map.iter().map(|(id, _)| id).collect();
 
-            json_map.clear();
+        let dir = match path.as_ref().parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        create_dir_all(dir)?;
+        let mut dumper = DocumentsDumper::new(BufWriter::new(NamedTempFile::new_in(dir)?));
+
+        for id in candidates {
+            for (_, obkv) in self.documents(&txn, std::iter::once(id))? {
+                dumper.push_document(&obkv_to_json(&all_fields, &fields_ids_map, obkv)?)?;
+            }
         }
+        let count = dumper.count();
 
-        Ok(())
+        dumper
+            .into_inner()
+            .into_inner()
+            .map_err(|e| e.into_error())?
+            .persist(path)
+            .map_err(|e| e.error)?;
+
+        Ok(count)
     }
 
     fn dump_meta(&self, txn: &RoTxn, path: impl AsRef<Path>) -> Result<()> {
@@ -86,7 +173,7 @@ impl Index {
         dst: impl AsRef<Path>,
         size: usize,
         indexer_config: &IndexerConfig,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<u64> {
         let dir_name = src
             .as_ref()
             .file_name()
@@ -126,14 +213,10 @@ impl Index {
 
         let mut tmp_doc_file = tempfile::tempfile()?;
 
-        let empty = match read_ndjson(reader, &mut tmp_doc_file) {
-            // if there was no document in the file it's because the index was empty
-            Ok(0) => true,
-            Ok(_) => false,
-            Err(e) => return Err(e.into()),
-        };
+        let documents_count = read_ndjson(reader, &mut tmp_doc_file, None)?;
 
-        if !empty {
+        // if there was no document in the file it's because the index was empty
+        if documents_count != 0 {
             tmp_doc_file.seek(SeekFrom::Start(0))?;
 
             let documents_reader = DocumentsBatchReader::from_reader(tmp_doc_file)?;
@@ -156,6 +239,50 @@ impl Index {
         txn.commit()?;
         index.prepare_for_closing().wait();
 
-        Ok(())
+        Ok(documents_count as u64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::index::error::IndexError;
+
+    fn document(value: serde_json::Value) -> Document {
+        match value {
+            serde_json::Value::Object(document) => document,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn push_documents_from_an_iterator() {
+        let documents = (0..3).map(|id| Ok(document(json!({ "id": id, "doggo": "bork" }))));
+
+        let mut dumper = DocumentsDumper::new(Vec::new());
+        dumper.push_documents(documents).unwrap();
+        assert_eq!(dumper.count(), 3);
+        assert_eq!(
+            String::from_utf8(dumper.into_inner()).unwrap(),
+            "{\"id\":0,\"doggo\":\"bork\"}\n\
+             {\"id\":1,\"doggo\":\"bork\"}\n\
+             {\"id\":2,\"doggo\":\"bork\"}\n"
+        );
+    }
+
+    #[test]
+    fn push_documents_stops_at_the_first_error() {
+        let documents = vec![
+            Ok(document(json!({ "id": 0 }))),
+            Err(IndexError::DocumentNotFound("1".to_string())),
+            Ok(document(json!({ "id": 2 }))),
+        ];
+
+        let mut dumper = DocumentsDumper::new(Vec::new());
+        assert!(dumper.push_documents(documents).is_err());
+        assert_eq!(dumper.count(), 1);
+        assert_eq!(dumper.into_inner(), b"{\"id\":0}\n");
     }
 }
@@ -18,6 +18,8 @@ pub enum IndexError {
     Facet(#[from] FacetError),
     #[error("{0}")]
     Milli(#[from] milli::Error),
+    #[error("The indexation was aborted.")]
+    AbortedIndexation,
 }
 
 internal_error!(
@@ -36,6 +38,7 @@ impl ErrorCode for IndexError {
             IndexError::DocumentNotFound(_) => Code::DocumentNotFound,
             IndexError::Facet(e) => e.error_code(),
             IndexError::Milli(e) => MilliError(e).error_code(),
+            IndexError::AbortedIndexation => Code::IndexationAborted,
         }
     }
 }
@@ -3,12 +3,12 @@ use std::fs::create_dir_all;
 use std::marker::PhantomData;
 use std::ops::Deref;
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use fst::IntoStreamer;
 use milli::heed::{CompactionOption, EnvOpenOptions, RoTxn};
 use milli::update::{IndexerConfig, Setting};
-use milli::{obkv_to_json, FieldDistribution, DEFAULT_VALUES_PER_FACET};
+use milli::{obkv_to_json, DocumentId, FieldDistribution, Filter, DEFAULT_VALUES_PER_FACET};
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use time::OffsetDateTime;
@@ -55,8 +55,8 @@ impl IndexMeta {
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct IndexStats {
-    #[serde(skip)]
-    pub size: u64,
+    /// The size of the files of the index on disk, in bytes.
+    pub database_size: u64,
     pub number_of_documents: u64,
     /// Whether the current index is performing an update. It is initially `None` when the
     /// index returns it, since it is the `UpdateStore` that knows what index is currently indexing. It is
@@ -73,6 +73,11 @@ pub struct Index {
     pub inner: Arc<milli::Index>,
     #[derivative(Debug = "ignore")]
     pub indexer_config: Arc<IndexerConfig>,
+    /// The last computed field distribution, along with the `updated_at` of the index it was
+    /// computed for. It is shared between all the instances of the index, so that it is kept
+    /// across requests as long as the index store holds the index.
+    #[derivative(Debug = "ignore")]
+    field_distribution_cache: Arc<Mutex<Option<(OffsetDateTime, FieldDistribution)>>>,
 }
 
 impl Deref for Index {
@@ -100,6 +105,7 @@ impl Index {
             inner,
             uuid,
             indexer_config: update_handler,
+            field_distribution_cache: Default::default(),
         })
     }
 
@@ -108,17 +114,48 @@ impl Index {
         self.inner.as_ref().clone().prepare_for_closing();
     }
 
+    /// Returns the number of documents of the index, without computing the rest of its stats.
+    pub fn documents_count(&self) -> Result<u64> {
+        let rtxn = self.read_txn()?;
+        Ok(self.number_of_documents(&rtxn)?)
+    }
+
     pub fn stats(&self) -> Result<IndexStats> {
         let rtxn = self.read_txn()?;
 
         Ok(IndexStats {
-            size: self.size(),
+            database_size: self.size(),
             number_of_documents: self.number_of_documents(&rtxn)?,
             is_indexing: None,
-            field_distribution: self.field_distribution(&rtxn)?,
+            field_distribution: self.cached_field_distribution(&rtxn)?,
         })
     }
 
+    /// Returns the field distribution of the index, which is only computed again when the index
+    /// was updated since the last call, since computing it scans the whole index.
+    fn cached_field_distribution(&self, rtxn: &RoTxn) -> Result<FieldDistribution> {
+        let updated_at = self.updated_at(rtxn)?;
+        let mut cache = self.field_distribution_cache.lock().unwrap();
+
+        match &*cache {
+            Some((computed_for, distribution)) if *computed_for == updated_at => {
+                Ok(distribution.clone())
+            }
+            _ => {
+                let distribution = self.field_distribution(rtxn)?;
+                *cache = Some((updated_at, distribution.clone()));
+                Ok(distribution)
+            }
+        }
+    }
+
+    /// Drops the cached stats of the index, and computes them again from the index itself.
+    pub fn recompute_stats(&self) -> Result<()> {
+        self.field_distribution_cache.lock().unwrap().take();
+        self.stats()?;
+        Ok(())
+    }
+
     pub fn meta(&self) -> Result<IndexMeta> {
         IndexMeta::new(self)
     }
@@ -234,21 +271,39 @@ impl Index {
         })
     }
 
-    /// Return the total number of documents contained in the index + the selected documents.
+    /// Return the total number of documents matching `filter` + the selected documents + the
+    /// internal id of the document following them, if any.
+    ///
+    /// The documents are ordered by internal id. When `from` is set, the documents start at the
+    /// internal id `from`, which lets a client page through the documents without skipping them.
+    /// The attributes used in `filter` must be filterable.
     pub fn retrieve_documents<S: AsRef<str>>(
         &self,
         offset: usize,
         limit: usize,
+        from: Option<DocumentId>,
+        filter: Option<&str>,
         attributes_to_retrieve: Option<Vec<S>>,
-    ) -> Result<(u64, Vec<Document>)> {
+    ) -> Result<(u64, Vec<Document>, Option<DocumentId>)> {
         let txn = self.read_txn()?;
 
         let fields_ids_map = self.fields_ids_map(&txn)?;
         let all_fields: Vec<_> = fields_ids_map.iter().map(|(id, _)| id).collect();
 
+        let mut documents_ids = match filter.map(Filter::from_str).transpose()?.flatten() {
+            Some(filter) => filter.evaluate(&txn, self)?,
+            None => self.documents_ids(&txn)?,
+        };
+        let number_of_documents = documents_ids.len();
+        if let Some(from) = from {
+            documents_ids.remove_range(..from);
+        }
+        let mut documents_ids = documents_ids.into_iter().skip(offset);
+        let page: Vec<_> = documents_ids.by_ref().take(limit).collect();
+        let next = documents_ids.next();
+
         let mut documents = Vec::new();
-        for entry in self.all_documents(&txn)?.skip(offset).take(limit) {
-            let (_id, obkv) = entry?;
+        for (_id, obkv) in self.documents(&txn, page)? {
             let document = obkv_to_json(&all_fields, &fields_ids_map, obkv)?;
             let document = match &attributes_to_retrieve {
                 Some(attributes_to_retrieve) => permissive_json_pointer::select_values(
@@ -260,9 +315,41 @@ impl Index {
             documents.push(document);
         }
 
-        let number_of_documents = self.number_of_documents(&txn)?;
+        Ok((number_of_documents, documents, next))
+    }
+
+    /// Return the documents with the given external ids, in the order of `doc_ids`. The ids that
+    /// don't match any document are skipped.
+    pub fn retrieve_documents_by_ids<S: AsRef<str>>(
+        &self,
+        doc_ids: Vec<String>,
+        attributes_to_retrieve: Option<Vec<S>>,
+    ) -> Result<Vec<Document>> {
+        let txn = self.read_txn()?;
+
+        let fields_ids_map = self.fields_ids_map(&txn)?;
+        let all_fields: Vec<_> = fields_ids_map.iter().map(|(id, _)| id).collect();
 
-        Ok((number_of_documents, documents))
+        let external_documents_ids = self.external_documents_ids(&txn)?;
+        let internal_ids: Vec<_> = doc_ids
+            .iter()
+            .filter_map(|doc_id| external_documents_ids.get(doc_id.as_bytes()))
+            .collect();
+
+        let mut documents = Vec::new();
+        for (_id, obkv) in self.documents(&txn, internal_ids)? {
+            let document = obkv_to_json(&all_fields, &fields_ids_map, obkv)?;
+            let document = match &attributes_to_retrieve {
+                Some(attributes_to_retrieve) => permissive_json_pointer::select_values(
+                    &document,
+                    attributes_to_retrieve.iter().map(|s| s.as_ref()),
+                ),
+                None => document,
+            };
+            documents.push(document);
+        }
+
+        Ok(documents)
     }
 
     pub fn retrieve_document<S: AsRef<str>>(
@@ -331,3 +418,61 @@ impl Drop for Index {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::AtomicBool;
+
+    use milli::update::IndexDocumentsMethod;
+
+    use super::*;
+    use crate::document_formats::read_ndjson;
+    use crate::update_file_store::UpdateFileStore;
+
+    #[test]
+    fn recompute_stats_restores_a_desynced_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = Index::open(
+            dir.path().join("index"),
+            100 * 1024 * 1024,
+            Uuid::new_v4(),
+            Arc::new(IndexerConfig::default()),
+        )
+        .unwrap();
+        let file_store = UpdateFileStore::new(dir.path()).unwrap();
+        let (uuid, mut file) = file_store.new_update().unwrap();
+        read_ndjson(
+            "{\"id\": 1, \"name\": \"kefir\"}\n".as_bytes(),
+            &mut *file,
+            None,
+        )
+        .unwrap();
+        file.persist().unwrap();
+        index
+            .update_documents(
+                IndexDocumentsMethod::ReplaceDocuments,
+                Some("id".to_string()),
+                file_store,
+                vec![uuid],
+                &AtomicBool::new(false),
+            )
+            .unwrap();
+
+        // the cache holds a wrong distribution for the current version of the index.
+        let updated_at = index.updated_at(&index.read_txn().unwrap()).unwrap();
+        *index.field_distribution_cache.lock().unwrap() = Some((updated_at, Default::default()));
+        assert!(index.stats().unwrap().field_distribution.is_empty());
+
+        index.recompute_stats().unwrap();
+        let distribution: Vec<_> = index
+            .stats()
+            .unwrap()
+            .field_distribution
+            .into_iter()
+            .collect();
+        assert_eq!(
+            distribution,
+            vec![("id".to_string(), 1), ("name".to_string(), 1)]
+        );
+    }
+}
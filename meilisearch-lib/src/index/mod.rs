@@ -12,6 +12,7 @@ pub mod updates;
 #[allow(clippy::module_inception)]
 mod index;
 
+pub use dump::{read_dump_settings, DocumentsDumper};
 pub use index::{Document, IndexMeta, IndexStats};
 
 #[cfg(not(test))]
@@ -25,11 +26,13 @@ pub use test::MockIndex as Index;
 #[cfg(test)]
 pub mod test {
     use std::path::{Path, PathBuf};
+    use std::sync::atomic::AtomicBool;
     use std::sync::Arc;
 
     use milli::update::{
         DocumentAdditionResult, DocumentDeletionResult, IndexDocumentsMethod, IndexerConfig,
     };
+    use milli::DocumentId;
     use nelson::Mocker;
     use uuid::Uuid;
 
@@ -65,7 +68,7 @@ pub mod test {
             dst: impl AsRef<Path>,
             size: usize,
             update_handler: &IndexerConfig,
-        ) -> anyhow::Result<()> {
+        ) -> anyhow::Result<u64> {
             Index::load_dump(src, dst, size, update_handler)
         }
 
@@ -76,6 +79,13 @@ pub mod test {
             }
         }
 
+        pub fn documents_count(&self) -> Result<u64> {
+            match self {
+                MockIndex::Real(index) => index.documents_count(),
+                MockIndex::Mock(m) => unsafe { m.get("documents_count").call(()) },
+            }
+        }
+
         pub fn stats(&self) -> Result<IndexStats> {
             match self {
                 MockIndex::Real(index) => index.stats(),
@@ -83,6 +93,13 @@ pub mod test {
             }
         }
 
+        pub fn recompute_stats(&self) -> Result<()> {
+            match self {
+                MockIndex::Real(index) => index.recompute_stats(),
+                MockIndex::Mock(m) => unsafe { m.get("recompute_stats").call(()) },
+            }
+        }
+
         pub fn meta(&self) -> Result<IndexMeta> {
             match self {
                 MockIndex::Real(index) => index.meta(),
@@ -100,11 +117,26 @@ pub mod test {
             &self,
             offset: usize,
             limit: usize,
+            from: Option<DocumentId>,
+            filter: Option<&str>,
+            attributes_to_retrieve: Option<Vec<S>>,
+        ) -> Result<(u64, Vec<Document>, Option<DocumentId>)> {
+            match self {
+                MockIndex::Real(index) => {
+                    index.retrieve_documents(offset, limit, from, filter, attributes_to_retrieve)
+                }
+                MockIndex::Mock(_) => todo!(),
+            }
+        }
+
+        pub fn retrieve_documents_by_ids<S: AsRef<str>>(
+            &self,
+            doc_ids: Vec<String>,
             attributes_to_retrieve: Option<Vec<S>>,
-        ) -> Result<(u64, Vec<Document>)> {
+        ) -> Result<Vec<Document>> {
             match self {
                 MockIndex::Real(index) => {
-                    index.retrieve_documents(offset, limit, attributes_to_retrieve)
+                    index.retrieve_documents_by_ids(doc_ids, attributes_to_retrieve)
                 }
                 MockIndex::Mock(_) => todo!(),
             }
@@ -149,23 +181,37 @@ pub mod test {
             }
         }
 
-        pub fn dump(&self, path: impl AsRef<Path>) -> Result<()> {
+        pub fn dump(&self, path: impl AsRef<Path>) -> Result<u64> {
             match self {
                 MockIndex::Real(index) => index.dump(path),
                 MockIndex::Mock(m) => unsafe { m.get("dump").call(path.as_ref()) },
             }
         }
 
+        pub fn export_documents(
+            &self,
+            path: impl AsRef<Path>,
+            filter: Option<&str>,
+        ) -> Result<u64> {
+            match self {
+                MockIndex::Real(index) => index.export_documents(path, filter),
+                MockIndex::Mock(m) => unsafe {
+                    m.get("export_documents").call((path.as_ref(), filter))
+                },
+            }
+        }
+
         pub fn update_documents(
             &self,
             method: IndexDocumentsMethod,
             primary_key: Option<String>,
             file_store: UpdateFileStore,
             contents: impl Iterator<Item = Uuid>,
+            must_stop: &AtomicBool,
         ) -> Result<Vec<Result<DocumentAdditionResult>>> {
             match self {
                 MockIndex::Real(index) => {
-                    index.update_documents(method, primary_key, file_store, contents)
+                    index.update_documents(method, primary_key, file_store, contents, must_stop)
                 }
                 MockIndex::Mock(mocker) => unsafe {
                     mocker
@@ -189,6 +235,13 @@ pub mod test {
             }
         }
 
+        pub fn touch(&self) -> Result<IndexMeta> {
+            match self {
+                MockIndex::Real(index) => index.touch(),
+                MockIndex::Mock(m) => unsafe { m.get("touch").call(()) },
+            }
+        }
+
         pub fn delete_documents(&self, ids: &[String]) -> Result<DocumentDeletionResult> {
             match self {
                 MockIndex::Real(index) => index.delete_documents(ids),
@@ -202,6 +255,13 @@ pub mod test {
                 MockIndex::Mock(m) => unsafe { m.get("clear_documents").call(()) },
             }
         }
+
+        pub fn clear_documents_matching(&self, filter: &str) -> Result<u64> {
+            match self {
+                MockIndex::Real(index) => index.clear_documents_matching(filter),
+                MockIndex::Mock(m) => unsafe { m.get("clear_documents_matching").call(filter) },
+            }
+        }
     }
 
     #[test]
@@ -1,6 +1,7 @@
 use std::collections::{BTreeMap, BTreeSet};
 use std::marker::PhantomData;
 use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use log::{debug, info, trace};
 use milli::documents::DocumentsBatchReader;
@@ -8,6 +9,7 @@ use milli::update::{
     DocumentAdditionResult, DocumentDeletionResult, IndexDocumentsConfig, IndexDocumentsMethod,
     Setting,
 };
+use milli::Filter;
 use serde::{Deserialize, Serialize, Serializer};
 use uuid::Uuid;
 
@@ -236,6 +238,134 @@ impl Settings<Unchecked> {
             _kind: PhantomData,
         }
     }
+
+    /// Merges two successive updates of the settings into one, with the same effect as applying
+    /// `self` and then `later`. The values of `later` take precedence.
+    pub fn merge(self, later: Self) -> Self {
+        Settings {
+            displayed_attributes: merge_setting(
+                self.displayed_attributes,
+                later.displayed_attributes,
+            ),
+            searchable_attributes: merge_setting(
+                self.searchable_attributes,
+                later.searchable_attributes,
+            ),
+            filterable_attributes: merge_setting(
+                self.filterable_attributes,
+                later.filterable_attributes,
+            ),
+            sortable_attributes: merge_setting(self.sortable_attributes, later.sortable_attributes),
+            ranking_rules: merge_setting(self.ranking_rules, later.ranking_rules),
+            stop_words: merge_setting(self.stop_words, later.stop_words),
+            synonyms: merge_setting(self.synonyms, later.synonyms),
+            distinct_attribute: merge_setting(self.distinct_attribute, later.distinct_attribute),
+            typo_tolerance: merge_nested_setting(self.typo_tolerance, later.typo_tolerance),
+            faceting: merge_nested_setting(self.faceting, later.faceting),
+            pagination: merge_nested_setting(self.pagination, later.pagination),
+            _kind: PhantomData,
+        }
+    }
+}
+
+/// A setting made of several sub-settings, that are updated independently from each other.
+trait NestedSetting: Sized {
+    /// Returns the value resetting all the sub-settings.
+    fn cleared() -> Self;
+
+    /// Merges the sub-settings of `later` into `self`, see `Settings::merge`.
+    fn merge(self, later: Self) -> Self;
+}
+
+impl NestedSetting for MinWordSizeTyposSetting {
+    fn cleared() -> Self {
+        Self {
+            one_typo: Setting::Reset,
+            two_typos: Setting::Reset,
+        }
+    }
+
+    fn merge(self, later: Self) -> Self {
+        Self {
+            one_typo: merge_setting(self.one_typo, later.one_typo),
+            two_typos: merge_setting(self.two_typos, later.two_typos),
+        }
+    }
+}
+
+impl NestedSetting for TypoSettings {
+    fn cleared() -> Self {
+        Self {
+            enabled: Setting::Reset,
+            min_word_size_for_typos: Setting::Reset,
+            disable_on_words: Setting::Reset,
+            disable_on_attributes: Setting::Reset,
+        }
+    }
+
+    fn merge(self, later: Self) -> Self {
+        Self {
+            enabled: merge_setting(self.enabled, later.enabled),
+            min_word_size_for_typos: merge_nested_setting(
+                self.min_word_size_for_typos,
+                later.min_word_size_for_typos,
+            ),
+            disable_on_words: merge_setting(self.disable_on_words, later.disable_on_words),
+            disable_on_attributes: merge_setting(
+                self.disable_on_attributes,
+                later.disable_on_attributes,
+            ),
+        }
+    }
+}
+
+impl NestedSetting for FacetingSettings {
+    fn cleared() -> Self {
+        Self {
+            max_values_per_facet: Setting::Reset,
+        }
+    }
+
+    fn merge(self, later: Self) -> Self {
+        Self {
+            max_values_per_facet: merge_setting(
+                self.max_values_per_facet,
+                later.max_values_per_facet,
+            ),
+        }
+    }
+}
+
+impl NestedSetting for PaginationSettings {
+    fn cleared() -> Self {
+        Self {
+            max_total_hits: Setting::Reset,
+        }
+    }
+
+    fn merge(self, later: Self) -> Self {
+        Self {
+            max_total_hits: merge_setting(self.max_total_hits, later.max_total_hits),
+        }
+    }
+}
+
+fn merge_setting<T>(earlier: Setting<T>, later: Setting<T>) -> Setting<T> {
+    match later {
+        Setting::NotSet => earlier,
+        later => later,
+    }
+}
+
+fn merge_nested_setting<T: NestedSetting>(earlier: Setting<T>, later: Setting<T>) -> Setting<T> {
+    match (earlier, later) {
+        (earlier, Setting::NotSet) => earlier,
+        (_, Setting::Reset) => Setting::Reset,
+        (Setting::NotSet, later) => later,
+        (Setting::Set(earlier), Setting::Set(later)) => Setting::Set(earlier.merge(later)),
+        // The sub-settings that are not set by `later` must stay reset.
+        (Setting::Reset, Setting::Set(later)) => Setting::Set(T::cleared().merge(later)),
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -268,6 +398,18 @@ impl Index {
         Ok(res)
     }
 
+    /// Sets the `updated_at` of the index to now, without changing anything else.
+    pub fn touch(&self) -> Result<IndexMeta> {
+        let mut txn = self.write_txn()?;
+        // the settings builder sets the update date even when no setting is changed.
+        let builder = milli::update::Settings::new(&mut txn, self, self.indexer_config.as_ref());
+        builder.execute(|_| ())?;
+        let meta = IndexMeta::new_txn(self, &txn)?;
+        txn.commit()?;
+
+        Ok(meta)
+    }
+
     /// Deletes `ids` from the index, and returns how many documents were deleted.
     pub fn delete_documents(&self, ids: &[String]) -> Result<DocumentDeletionResult> {
         let mut txn = self.write_txn()?;
@@ -285,6 +427,24 @@ impl Index {
         Ok(deleted)
     }
 
+    /// Deletes the documents matching `filter`, and returns how many documents were deleted.
+    pub fn clear_documents_matching(&self, filter: &str) -> Result<u64> {
+        let mut txn = self.write_txn()?;
+        let candidates = match Filter::from_str(filter)? {
+            Some(filter) => filter.evaluate(&txn, self)?,
+            None => return Ok(0),
+        };
+
+        let mut builder = milli::update::DeleteDocuments::new(&mut txn, self)?;
+        builder.delete_documents(&candidates);
+        let DocumentDeletionResult {
+            deleted_documents, ..
+        } = builder.execute()?;
+        txn.commit()?;
+
+        Ok(deleted_documents)
+    }
+
     pub fn clear_documents(&self) -> Result<()> {
         let mut txn = self.write_txn()?;
         milli::update::ClearDocuments::new(&mut txn, self).execute()?;
@@ -293,12 +453,15 @@ impl Index {
         Ok(())
     }
 
+    /// Indexes the documents of all the `contents` in a single write transaction, so that readers
+    /// keep seeing the documents as they were before the batch until it is fully committed.
     pub fn update_documents(
         &self,
         method: IndexDocumentsMethod,
         primary_key: Option<String>,
         file_store: UpdateFileStore,
         contents: impl IntoIterator<Item = Uuid>,
+        must_stop: &AtomicBool,
     ) -> Result<Vec<Result<DocumentAdditionResult>>> {
         trace!("performing document addition");
         let mut txn = self.write_txn()?;
@@ -325,6 +488,10 @@ impl Index {
 
         let mut results = Vec::new();
         for content_uuid in contents.into_iter() {
+            if must_stop.load(Ordering::Acquire) {
+                return Err(IndexError::AbortedIndexation);
+            }
+
             let content_file = file_store.get_update(content_uuid)?;
             let reader = DocumentsBatchReader::from_reader(content_file)?;
             let (new_builder, user_result) = builder.add_documents(reader)?;
@@ -341,6 +508,10 @@ impl Index {
             results.push(user_result);
         }
 
+        if must_stop.load(Ordering::Acquire) {
+            return Err(IndexError::AbortedIndexation);
+        }
+
         if results.iter().any(Result::is_ok) {
             let addition = builder.execute()?;
             txn.commit()?;
@@ -498,9 +669,13 @@ pub fn apply_settings_to_builder(
 
 #[cfg(test)]
 pub(crate) mod test {
+    use std::sync::Arc;
+
+    use milli::update::IndexerConfig;
     use proptest::prelude::*;
 
     use super::*;
+    use crate::document_formats::read_ndjson;
 
     pub(super) fn setting_strategy<T: Arbitrary + Clone>() -> impl Strategy<Value = Setting<T>> {
         prop_oneof![
@@ -556,4 +731,169 @@ pub(crate) mod test {
         assert_eq!(checked.displayed_attributes, Setting::Reset);
         assert_eq!(checked.searchable_attributes, Setting::Reset);
     }
+
+    #[test]
+    fn merge_settings() {
+        let earlier = Settings::<Unchecked> {
+            ranking_rules: Setting::Set(vec![String::from("words")]),
+            stop_words: Setting::Set(BTreeSet::from([String::from("the")])),
+            typo_tolerance: Setting::Set(TypoSettings {
+                enabled: Setting::Set(false),
+                min_word_size_for_typos: Setting::Set(MinWordSizeTyposSetting {
+                    one_typo: Setting::Set(3),
+                    two_typos: Setting::Set(7),
+                }),
+                ..Default::default()
+            }),
+            pagination: Setting::Reset,
+            ..Default::default()
+        };
+        let later = Settings::<Unchecked> {
+            stop_words: Setting::Reset,
+            distinct_attribute: Setting::Set(String::from("id")),
+            typo_tolerance: Setting::Set(TypoSettings {
+                min_word_size_for_typos: Setting::Set(MinWordSizeTyposSetting {
+                    two_typos: Setting::Set(9),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            pagination: Setting::Set(PaginationSettings {
+                max_total_hits: Setting::Set(10),
+            }),
+            ..Default::default()
+        };
+
+        let merged = earlier.merge(later);
+        assert_eq!(
+            merged.ranking_rules,
+            Setting::Set(vec![String::from("words")])
+        );
+        assert_eq!(merged.stop_words, Setting::Reset);
+        assert_eq!(merged.distinct_attribute, Setting::Set(String::from("id")));
+        assert_eq!(merged.displayed_attributes, Setting::NotSet);
+        assert_eq!(
+            merged.typo_tolerance,
+            Setting::Set(TypoSettings {
+                enabled: Setting::Set(false),
+                min_word_size_for_typos: Setting::Set(MinWordSizeTyposSetting {
+                    one_typo: Setting::Set(3),
+                    two_typos: Setting::Set(9),
+                }),
+                ..Default::default()
+            })
+        );
+        // The pagination was reset before being updated.
+        assert_eq!(
+            merged.pagination,
+            Setting::Set(PaginationSettings {
+                max_total_hits: Setting::Set(10),
+            })
+        );
+    }
+
+    #[test]
+    fn readers_never_see_a_partial_document_addition() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = Index::open(
+            dir.path().join("index"),
+            100 * 1024 * 1024,
+            Uuid::new_v4(),
+            Arc::new(IndexerConfig::default()),
+        )
+        .unwrap();
+        let file_store = UpdateFileStore::new(dir.path()).unwrap();
+        let new_update = |ids: std::ops::Range<u32>| {
+            let documents: String = ids.map(|id| format!("{{\"id\": {}}}\n", id)).collect();
+            let (uuid, mut file) = file_store.new_update().unwrap();
+            read_ndjson(documents.as_bytes(), &mut *file, None).unwrap();
+            file.persist().unwrap();
+            uuid
+        };
+        let add_documents = |contents: Vec<Uuid>| {
+            index
+                .update_documents(
+                    IndexDocumentsMethod::ReplaceDocuments,
+                    Some("id".to_string()),
+                    file_store.clone(),
+                    contents,
+                    &AtomicBool::new(false),
+                )
+                .unwrap()
+        };
+
+        add_documents(vec![new_update(0..100)]);
+        let contents: Vec<_> = (1..11)
+            .map(|i| new_update(i * 100..(i + 1) * 100))
+            .collect();
+
+        let done = Arc::new(AtomicBool::new(false));
+        let reader = std::thread::spawn({
+            let index = index.clone();
+            let done = done.clone();
+            move || {
+                let mut counts = BTreeSet::new();
+                while !done.load(Ordering::Acquire) {
+                    counts.insert(index.stats().unwrap().number_of_documents);
+                }
+                counts.insert(index.stats().unwrap().number_of_documents);
+                counts
+            }
+        });
+
+        let results = add_documents(contents);
+        done.store(true, Ordering::Release);
+        assert!(results.iter().all(Result::is_ok));
+
+        let counts = reader.join().unwrap();
+        assert!(counts.iter().all(|count| [100, 1100].contains(count)));
+        assert!(counts.contains(&1100));
+    }
+
+    #[test]
+    fn field_distribution_follows_the_index_updates() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = Index::open(
+            dir.path().join("index"),
+            100 * 1024 * 1024,
+            Uuid::new_v4(),
+            Arc::new(IndexerConfig::default()),
+        )
+        .unwrap();
+        let file_store = UpdateFileStore::new(dir.path()).unwrap();
+        let add_documents = |documents: &str| {
+            let (uuid, mut file) = file_store.new_update().unwrap();
+            read_ndjson(documents.as_bytes(), &mut *file, None).unwrap();
+            file.persist().unwrap();
+            let results = index
+                .update_documents(
+                    IndexDocumentsMethod::ReplaceDocuments,
+                    Some("id".to_string()),
+                    file_store.clone(),
+                    vec![uuid],
+                    &AtomicBool::new(false),
+                )
+                .unwrap();
+            assert!(results.iter().all(Result::is_ok));
+        };
+        let distribution = |index: &Index| {
+            index
+                .stats()
+                .unwrap()
+                .field_distribution
+                .into_iter()
+                .collect::<Vec<_>>()
+        };
+
+        add_documents("{\"id\": 1}\n");
+        assert_eq!(distribution(&index), vec![("id".to_string(), 1)]);
+        // served from the cache by another instance of the index.
+        assert_eq!(distribution(&index.clone()), vec![("id".to_string(), 1)]);
+
+        add_documents("{\"id\": 2, \"name\": \"kefir\"}\n");
+        assert_eq!(
+            distribution(&index),
+            vec![("id".to_string(), 2), ("name".to_string(), 1)]
+        );
+    }
 }
@@ -1,4 +1,5 @@
 use std::error::Error;
+use std::time::Duration;
 
 use meilisearch_types::error::{Code, ErrorCode};
 use meilisearch_types::index_uid::IndexUidFormatError;
@@ -10,6 +11,7 @@ use crate::document_formats::DocumentFormatError;
 use crate::dump::error::DumpError;
 use crate::index::error::IndexError;
 use crate::tasks::error::TaskError;
+use crate::tasks::task::TaskId;
 use crate::update_file_store::UpdateFileStoreError;
 
 use crate::index_resolver::error::IndexResolverError;
@@ -36,9 +38,21 @@ pub enum IndexControllerError {
     MissingPayload(DocumentAdditionFormat),
     #[error("The provided payload reached the size limit.")]
     PayloadTooLarge,
+    #[error("Index `{0}` is busy processing tasks, try again once they are processed.")]
+    IndexBusy(String),
+    #[error("Task `{0}` is still not finished after {1:?}.")]
+    TaskTimeout(TaskId, Duration),
+    #[error("Task `{0}` failed or was canceled, its write was not applied.")]
+    WriteNotApplied(TaskId),
 }
 
-internal_error!(IndexControllerError: JoinError, UpdateFileStoreError);
+internal_error!(
+    IndexControllerError: JoinError,
+    UpdateFileStoreError,
+    std::io::Error,
+    milli::documents::Error,
+    milli::documents::DocumentsBatchCursorError
+);
 
 impl From<actix_web::error::PayloadError> for IndexControllerError {
     fn from(other: actix_web::error::PayloadError) -> Self {
@@ -61,6 +75,9 @@ impl ErrorCode for IndexControllerError {
             IndexControllerError::MissingPayload(_) => Code::MissingPayload,
             IndexControllerError::PayloadTooLarge => Code::PayloadTooLarge,
             IndexControllerError::DumpError(e) => e.error_code(),
+            IndexControllerError::IndexBusy(_) => Code::IndexBusy,
+            IndexControllerError::TaskTimeout(..) => Code::Internal,
+            IndexControllerError::WriteNotApplied(_) => Code::WriteNotApplied,
         }
     }
 }
@@ -1,8 +1,9 @@
 use meilisearch_auth::SearchRules;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
-use std::io::Cursor;
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
+use std::slice;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
@@ -12,26 +13,35 @@ use bytes::Bytes;
 use futures::Stream;
 use futures::StreamExt;
 use meilisearch_types::index_uid::IndexUid;
+use milli::documents::DocumentsBatchReader;
 use milli::update::IndexDocumentsMethod;
+use milli::{DocumentId, Filter};
+use roaring::RoaringBitmap;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use time::OffsetDateTime;
-use tokio::sync::RwLock;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{broadcast, RwLock};
 use tokio::task::spawn_blocking;
 use tokio::time::sleep;
 use uuid::Uuid;
 
-use crate::document_formats::{read_csv, read_json, read_ndjson};
+use crate::document_formats::{read_csv, read_json, read_ndjson, read_tsv, DocumentFormatError};
 use crate::dump::{self, load_dump, DumpHandler};
+use crate::index::error::{FacetError, IndexError};
 use crate::index::{
     Checked, Document, IndexMeta, IndexStats, SearchQuery, SearchResult, Settings, Unchecked,
 };
 use crate::index_resolver::error::IndexResolverError;
-use crate::options::{IndexerOpts, SchedulerConfig};
+use crate::options::{CompressionLevel, IndexerOpts, SchedulerConfig, SyncMode};
 use crate::snapshot::{load_snapshot, SnapshotService};
 use crate::tasks::error::TaskError;
-use crate::tasks::task::{DocumentDeletion, Task, TaskContent, TaskId};
+use crate::tasks::task::{
+    DocumentDeletion, Task, TaskContent, TaskEvent, TaskId, TaskList, TaskStatus,
+};
 use crate::tasks::{
-    BatchHandler, EmptyBatchHandler, Scheduler, SnapshotHandler, TaskFilter, TaskStore,
+    BatchHandler, DaySummary, EmptyBatchHandler, HeldKind, Scheduler, SnapshotHandler, TaskFilter,
+    TaskStore,
 };
 use error::Result;
 
@@ -44,6 +54,8 @@ use crate::update_file_store::UpdateFileStore;
 pub mod error;
 pub mod versioning;
 
+pub use crate::dump::{read_indexes_settings, DumpInfo, DumpedIndexSettings};
+
 /// Concrete implementation of the IndexController, exposed by meilisearch-lib
 pub type MeiliSearch = IndexController<HeedMetaStore, MapIndexStore>;
 
@@ -51,10 +63,15 @@ pub type Payload = Box<
     dyn Stream<Item = std::result::Result<Bytes, PayloadError>> + Send + Sync + 'static + Unpin,
 >;
 
-pub fn open_meta_env(path: &Path, size: usize) -> milli::heed::Result<milli::heed::Env> {
+pub fn open_meta_env(
+    path: &Path,
+    size: usize,
+    sync_mode: SyncMode,
+) -> milli::heed::Result<milli::heed::Env> {
     let mut options = milli::heed::EnvOpenOptions::new();
     options.map_size(size);
     options.max_dbs(20);
+    sync_mode.apply(&mut options);
     options.open(path)
 }
 
@@ -79,6 +96,20 @@ pub struct IndexController<U, I> {
     scheduler: Arc<RwLock<Scheduler>>,
     task_store: TaskStore,
     pub update_file_store: UpdateFileStore,
+    deduplicate_document_additions: bool,
+    /// The maximum number of fields of the documents of an addition.
+    max_fields_per_document: usize,
+    /// The maximum number of tasks a listing may return.
+    max_tasks_limit: usize,
+    /// Shared while persisting the update file of a document addition and registering it, so that
+    /// an update file is never seen without the task referencing it. Held exclusively while
+    /// looking for a duplicate of the addition too, so that concurrent identical uploads can't
+    /// both be registered.
+    registration_lock: Arc<RwLock<()>>,
+    /// The directory the dumps are created in.
+    dump_path: PathBuf,
+    /// Whether a last dump is created by `shutdown`.
+    dump_on_shutdown: bool,
 }
 
 /// Need a custom implementation for clone because deriving require that U and I are clone.
@@ -89,14 +120,31 @@ impl<U, I> Clone for IndexController<U, I> {
             scheduler: self.scheduler.clone(),
             update_file_store: self.update_file_store.clone(),
             task_store: self.task_store.clone(),
+            deduplicate_document_additions: self.deduplicate_document_additions,
+            max_fields_per_document: self.max_fields_per_document,
+            max_tasks_limit: self.max_tasks_limit,
+            registration_lock: self.registration_lock.clone(),
+            dump_path: self.dump_path.clone(),
+            dump_on_shutdown: self.dump_on_shutdown,
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum DocumentAdditionFormat {
     Json,
-    Csv,
+    Csv {
+        /// The byte separating the fields, `,` by default.
+        delimiter: u8,
+        /// The byte quoting the fields, `"` by default.
+        quote: u8,
+        /// Whether the first row of the payload holds the field names.
+        has_header: bool,
+    },
+    Tsv {
+        /// Whether the first row of the payload holds the field names.
+        has_header: bool,
+    },
     Ndjson,
 }
 
@@ -105,7 +153,8 @@ impl fmt::Display for DocumentAdditionFormat {
         match self {
             DocumentAdditionFormat::Json => write!(f, "json"),
             DocumentAdditionFormat::Ndjson => write!(f, "ndjson"),
-            DocumentAdditionFormat::Csv => write!(f, "csv"),
+            DocumentAdditionFormat::Csv { .. } => write!(f, "csv"),
+            DocumentAdditionFormat::Tsv { .. } => write!(f, "tsv"),
         }
     }
 }
@@ -119,12 +168,42 @@ pub struct Stats {
     pub indexes: BTreeMap<String, IndexStats>,
 }
 
+/// The result of the validation of a document addition payload.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentsValidation {
+    pub documents_count: usize,
+    /// The primary key the documents were checked against, if it is known.
+    pub primary_key: Option<String>,
+    pub errors: Vec<String>,
+}
+
+/// An update file of the update file store, along with the task referencing it.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateFileInfo {
+    pub uuid: Uuid,
+    pub size: u64,
+    /// The id of the task using this update file, `None` if the file is an orphan.
+    pub task_id: Option<TaskId>,
+}
+
+impl UpdateFileInfo {
+    pub fn is_orphan(&self) -> bool {
+        self.task_id.is_none()
+    }
+}
+
 #[allow(clippy::large_enum_variant)]
 #[derive(derivative::Derivative)]
 #[derivative(Debug)]
 pub enum Update {
     DeleteDocuments(Vec<String>),
     ClearDocuments,
+    /// Clears the documents matching the filter.
+    ClearDocumentsMatching {
+        filter: String,
+    },
     Settings {
         settings: Settings<Unchecked>,
         /// Indicates whether the update was a deletion
@@ -146,8 +225,17 @@ pub enum Update {
     UpdateIndex {
         primary_key: Option<String>,
     },
+    ExportDocuments {
+        path: PathBuf,
+        filter: Option<String>,
+    },
 }
 
+/// Identifies the write made by a registered task, so that a client can wait for the write to be
+/// visible before reading, see `IndexController::wait_for_consistency`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsistencyToken(TaskId);
+
 #[derive(Default, Debug)]
 pub struct IndexControllerBuilder {
     max_index_size: Option<usize>,
@@ -160,6 +248,8 @@ pub struct IndexControllerBuilder {
     schedule_snapshot: bool,
     dump_src: Option<PathBuf>,
     dump_dst: Option<PathBuf>,
+    dump_compression: CompressionLevel,
+    dump_on_shutdown: bool,
     ignore_dump_if_db_exists: bool,
     ignore_missing_dump: bool,
 }
@@ -195,6 +285,7 @@ impl IndexControllerBuilder {
                 index_size,
                 task_store_size,
                 &indexer_options,
+                |progress| log::info!("Dump import progress: {}.", progress),
             )?;
         } else if db_path.as_ref().exists() {
             // Directory could be pre-created without any database in.
@@ -206,7 +297,11 @@ impl IndexControllerBuilder {
 
         std::fs::create_dir_all(db_path.as_ref())?;
 
-        let meta_env = Arc::new(open_meta_env(db_path.as_ref(), task_store_size)?);
+        let meta_env = Arc::new(open_meta_env(
+            db_path.as_ref(),
+            task_store_size,
+            scheduler_config.tasks_sync_mode,
+        )?);
 
         let update_file_store = UpdateFileStore::new(&db_path)?;
         // Create or overwrite the version file for this DB
@@ -224,16 +319,27 @@ impl IndexControllerBuilder {
             .dump_dst
             .ok_or_else(|| anyhow::anyhow!("Missing dump directory path"))?;
 
-        let dump_handler = Arc::new(DumpHandler::new(
-            dump_path,
-            db_path.as_ref().into(),
-            update_file_store.clone(),
-            task_store_size,
-            index_size,
-            meta_env.clone(),
-            index_resolver.clone(),
-        ));
+        let dump_handler = Arc::new(
+            DumpHandler::new(
+                dump_path.clone(),
+                db_path.as_ref().into(),
+                update_file_store.clone(),
+                task_store_size,
+                index_size,
+                meta_env.clone(),
+                index_resolver.clone(),
+            )
+            .with_compression(self.dump_compression),
+        );
         let task_store = TaskStore::new(meta_env)?;
+        // the tasks that were processing when Meilisearch stopped are processed again.
+        let requeued = task_store.requeue_interrupted_tasks()?;
+        if requeued > 0 {
+            log::warn!("{} interrupted tasks were enqueued again.", requeued);
+        }
+        let deduplicate_document_additions = scheduler_config.deduplicate_document_additions;
+        let max_tasks_limit = scheduler_config.max_tasks_limit;
+        let sync_mode = scheduler_config.tasks_sync_mode;
 
         // register all the batch handlers for use with the scheduler.
         let handlers: Vec<Arc<dyn BatchHandler + Sync + Send + 'static>> = vec![
@@ -259,6 +365,7 @@ impl IndexControllerBuilder {
                 snapshot_path,
                 index_size,
                 meta_env_size: task_store_size,
+                sync_mode,
                 scheduler: scheduler.clone(),
             };
 
@@ -270,6 +377,12 @@ impl IndexControllerBuilder {
             scheduler,
             update_file_store,
             task_store,
+            deduplicate_document_additions,
+            max_fields_per_document: indexer_options.max_fields_per_document,
+            max_tasks_limit,
+            registration_lock: Arc::default(),
+            dump_path,
+            dump_on_shutdown: self.dump_on_shutdown,
         })
     }
 
@@ -337,6 +450,18 @@ impl IndexControllerBuilder {
         self
     }
 
+    /// Sets how the archives of the dumps are compressed.
+    pub fn set_dump_compression(&mut self, dump_compression: CompressionLevel) -> &mut Self {
+        self.dump_compression = dump_compression;
+        self
+    }
+
+    /// Sets whether a last dump is created when Meilisearch is shut down gracefully.
+    pub fn set_dump_on_shutdown(&mut self, dump_on_shutdown: bool) -> &mut Self {
+        self.dump_on_shutdown = dump_on_shutdown;
+        self
+    }
+
     /// Set the index controller builder's ignore dump if db exists.
     pub fn set_ignore_dump_if_db_exists(&mut self, ignore_dump_if_db_exists: bool) -> &mut Self {
         self.ignore_dump_if_db_exists = ignore_dump_if_db_exists;
@@ -359,8 +484,27 @@ where
         IndexControllerBuilder::default()
     }
 
-    pub async fn register_update(&self, uid: String, update: Update) -> Result<Task> {
+    /// Registers the `update`, and returns its task with the token to wait for its write, see
+    /// `IndexController::wait_for_consistency`.
+    pub async fn register_update(
+        &self,
+        uid: String,
+        update: Update,
+    ) -> Result<(Task, ConsistencyToken)> {
+        let task = self.register_update_with_priority(uid, update, 0).await?;
+        let token = ConsistencyToken(task.id);
+        Ok((task, token))
+    }
+
+    /// Registers the `update` with the given `priority`, see `TaskStore::register_with_priority`.
+    pub async fn register_update_with_priority(
+        &self,
+        uid: String,
+        update: Update,
+        priority: u8,
+    ) -> Result<Task> {
         let index_uid = IndexUid::from_str(&uid).map_err(IndexResolverError::from)?;
+        let mut unpersisted_update_file = None;
         let content = match update {
             Update::DeleteDocuments(ids) => TaskContent::DocumentDeletion {
                 index_uid,
@@ -370,6 +514,24 @@ where
                 index_uid,
                 deletion: DocumentDeletion::Clear,
             },
+            Update::ClearDocumentsMatching { filter } => {
+                // an invalid filter is rejected before the task is registered.
+                if Filter::from_str(&filter)
+                    .map_err(IndexError::from)?
+                    .is_none()
+                {
+                    return Err(IndexError::Facet(FacetError::InvalidExpression(
+                        &["non-empty String"],
+                        Value::String(filter),
+                    ))
+                    .into());
+                }
+
+                TaskContent::DocumentDeletion {
+                    index_uid,
+                    deletion: DocumentDeletion::Filter(filter),
+                }
+            }
             Update::Settings {
                 settings,
                 is_deletion,
@@ -381,36 +543,30 @@ where
                 index_uid,
             },
             Update::DocumentAddition {
-                mut payload,
+                payload,
                 primary_key,
                 format,
                 method,
                 allow_index_creation,
             } => {
-                let mut buffer = Vec::new();
-                while let Some(bytes) = payload.next().await {
-                    let bytes = bytes?;
-                    buffer.extend_from_slice(&bytes);
-                }
+                let (payload_file, payload_size) =
+                    receive_payload(payload, &self.update_file_store).await?;
                 let (content_uuid, mut update_file) = self.update_file_store.new_update()?;
-                let documents_count = tokio::task::spawn_blocking(move || -> Result<_> {
-                    // check if the payload is empty, and return an error
-                    if buffer.is_empty() {
-                        return Err(IndexControllerError::MissingPayload(format));
-                    }
-
-                    let reader = Cursor::new(buffer);
-                    let count = match format {
-                        DocumentAdditionFormat::Json => read_json(reader, &mut *update_file)?,
-                        DocumentAdditionFormat::Csv => read_csv(reader, &mut *update_file)?,
-                        DocumentAdditionFormat::Ndjson => read_ndjson(reader, &mut *update_file)?,
-                    };
-
-                    update_file.persist()?;
-
-                    Ok(count)
-                })
-                .await??;
+                let max_fields = self.max_fields_per_document;
+                let (documents_count, update_file) =
+                    tokio::task::spawn_blocking(move || -> Result<_> {
+                        // check if the payload is empty, and return an error
+                        if payload_size == 0 {
+                            return Err(IndexControllerError::MissingPayload(format));
+                        }
+
+                        let count =
+                            read_documents(format, payload_file, &mut *update_file, max_fields)?;
+
+                        Ok((count, update_file))
+                    })
+                    .await??;
+                unpersisted_update_file = Some(update_file);
 
                 TaskContent::DocumentAddition {
                     content_uuid,
@@ -426,26 +582,468 @@ where
                 primary_key,
                 index_uid,
             },
-            Update::UpdateIndex { primary_key } => TaskContent::IndexUpdate {
-                primary_key,
+            Update::UpdateIndex { primary_key } => {
+                if let Some(ref primary_key) = primary_key {
+                    self.check_primary_key_update(index_uid.as_str(), primary_key)
+                        .await?;
+                }
+
+                TaskContent::IndexUpdate {
+                    primary_key,
+                    index_uid,
+                }
+            }
+            Update::ExportDocuments { path, filter } => TaskContent::DocumentsExport {
                 index_uid,
+                path,
+                filter,
             },
         };
 
-        let task = self.task_store.register(content).await?;
-        self.scheduler.read().await.notify();
+        let _registration_guard = match content {
+            TaskContent::DocumentAddition { content_uuid, .. }
+                if self.deduplicate_document_additions =>
+            {
+                let guard = self.registration_lock.write().await;
+                if let Some(update_file) = unpersisted_update_file {
+                    update_file.persist()?;
+                }
+                if let Some(task) = self.find_duplicate_document_addition(&content).await? {
+                    self.update_file_store.delete(content_uuid).await?;
+                    return Ok(task);
+                }
+                // another duplicate can't be looked for until this addition is registered.
+                Some(guard.downgrade())
+            }
+            TaskContent::DocumentAddition { .. } => {
+                let guard = self.registration_lock.read().await;
+                if let Some(update_file) = unpersisted_update_file {
+                    update_file.persist()?;
+                }
+                Some(guard)
+            }
+            _ => None,
+        };
+
+        let content_uuid = match content {
+            TaskContent::DocumentAddition { content_uuid, .. } => Some(content_uuid),
+            _ => None,
+        };
+        let task = match self
+            .task_store
+            .register_with_priority(content, priority)
+            .await
+        {
+            Ok(task) => task,
+            Err(e) => {
+                // Nothing would ever remove the update file of a task that was not registered.
+                if let Some(content_uuid) = content_uuid {
+                    if let Err(delete_error) = self.update_file_store.delete(content_uuid).await {
+                        log::warn!(
+                            "The update file {} of an unregistered task could not be removed: {}",
+                            content_uuid,
+                            delete_error
+                        );
+                    }
+                }
+                return Err(e.into());
+            }
+        };
+        let scheduler = self.scheduler.read().await;
+        scheduler.publish(slice::from_ref(&task));
+        scheduler.notify();
 
         Ok(task)
     }
 
-    pub async fn register_dump_task(&self) -> Result<Task> {
+    /// Returns the enqueued document addition that has the same parameters as `content` and whose
+    /// update file has the same content, if any.
+    async fn find_duplicate_document_addition(
+        &self,
+        content: &TaskContent,
+    ) -> Result<Option<Task>> {
+        let (content_uuid, index_uid) = match content {
+            TaskContent::DocumentAddition {
+                content_uuid,
+                index_uid,
+                ..
+            } => (*content_uuid, index_uid),
+            _ => return Ok(None),
+        };
+
+        // Only the enqueued document additions of the index are loaded.
+        let mut filter = TaskFilter::default();
+        filter.filter_index(index_uid.to_string());
+        filter.filter_kind("DocumentAddition");
+        filter.filter_status(TaskStatus::Enqueued);
+        let candidates: Vec<_> = self
+            .list_tasks(Some(filter), None, None)
+            .await?
+            .into_iter()
+            .filter(|task| matches!(task.events.last(), Some(TaskEvent::Created(_))))
+            .filter(|task| same_document_addition_parameters(&task.content, content))
+            .collect();
+
+        if candidates.is_empty() {
+            return Ok(None);
+        }
+
+        let update_file_store = self.update_file_store.clone();
+        let duplicate = spawn_blocking(move || -> Result<_> {
+            let size = update_file_store.get_size(content_uuid)?;
+            let mut hash = None;
+            for task in candidates {
+                let candidate_uuid = match task.content {
+                    TaskContent::DocumentAddition { content_uuid, .. } => content_uuid,
+                    _ => continue,
+                };
+                // A missing update file can't be a duplicate.
+                match update_file_store.get_size(candidate_uuid) {
+                    Ok(candidate_size) if candidate_size == size => (),
+                    _ => continue,
+                }
+
+                let hash = match hash {
+                    Some(hash) => hash,
+                    None => *hash.insert(update_file_store.hash(content_uuid)?),
+                };
+                if update_file_store.hash(candidate_uuid)? == hash {
+                    return Ok(Some(task));
+                }
+            }
+
+            Ok(None)
+        })
+        .await??;
+
+        Ok(duplicate)
+    }
+
+    /// Parses the documents of `payload` without registering any task, and checks that all of them
+    /// have a primary key. The primary key is either `primary_key` or the one of the index `uid`,
+    /// if it exists. Parsing errors are reported in the returned summary.
+    pub async fn validate_documents(
+        &self,
+        uid: String,
+        payload: Payload,
+        format: DocumentAdditionFormat,
+        primary_key: Option<String>,
+    ) -> Result<DocumentsValidation> {
+        let primary_key = match primary_key {
+            Some(primary_key) => Some(primary_key),
+            None => match self.get_index(uid).await {
+                Ok(index) => index.meta.primary_key,
+                Err(IndexControllerError::IndexResolver(IndexResolverError::UnexistingIndex(
+                    _,
+                ))) => None,
+                Err(e) => return Err(e),
+            },
+        };
+
+        let (payload_file, payload_size) =
+            receive_payload(payload, &self.update_file_store).await?;
+        let max_fields = self.max_fields_per_document;
+        let mut documents = self.update_file_store.new_payload_file()?;
+        spawn_blocking(move || -> Result<_> {
+            if payload_size == 0 {
+                return Err(IndexControllerError::MissingPayload(format));
+            }
+
+            let documents_count = match read_documents(
+                format,
+                payload_file,
+                &mut documents,
+                max_fields,
+            ) {
+                Ok(count) => count,
+                Err(e @ DocumentFormatError::Internal(_)) => return Err(e.into()),
+                Err(e) => {
+                    return Ok(DocumentsValidation {
+                        documents_count: 0,
+                        primary_key,
+                        errors: vec![e.to_string()],
+                    })
+                }
+            };
+
+            let mut errors = Vec::new();
+            if let Some(ref primary_key) = primary_key {
+                documents.seek(SeekFrom::Start(0))?;
+                let (mut cursor, fields_index) =
+                    DocumentsBatchReader::from_reader(documents)?.into_cursor_and_fields_index();
+                let primary_key_id = fields_index.id(primary_key);
+
+                let mut missing = Vec::new();
+                let mut position = 0;
+                while let Some(document) = cursor.next_document()? {
+                    if primary_key_id.map_or(true, |id| document.get(id).is_none()) {
+                        missing.push(position);
+                    }
+                    position += 1;
+                }
+
+                if let Some(first) = missing.first() {
+                    errors.push(format!(
+                        "{} document(s) are missing the primary key `{}`, the first one is at position {}.",
+                        missing.len(),
+                        primary_key,
+                        first
+                    ));
+                }
+            }
+
+            Ok(DocumentsValidation {
+                documents_count,
+                primary_key,
+                errors,
+            })
+        })
+        .await?
+    }
+
+    /// Registers the creation of a dump. When `indexes` is set, only these indexes and the tasks
+    /// targeting them are dumped.
+    pub async fn register_dump_task(&self, indexes: Option<Vec<String>>) -> Result<Task> {
         let uid = dump::generate_uid();
-        let content = TaskContent::Dump { uid };
+        let content = TaskContent::Dump { uid, indexes };
         let task = self.task_store.register(content).await?;
-        self.scheduler.read().await.notify();
+        let scheduler = self.scheduler.read().await;
+        scheduler.publish(slice::from_ref(&task));
+        scheduler.notify();
+        Ok(task)
+    }
+
+    /// Prepares a graceful shutdown. When dumping on shutdown is enabled, creates a last dump and
+    /// waits for it, which returns the finished dump task. Since the dumps are processed one after
+    /// the other, it also waits for the dumps that were already enqueued or processing, and the
+    /// dumps that were held are released.
+    pub async fn shutdown(&self) -> Result<Option<Task>> {
+        if !self.dump_on_shutdown {
+            return Ok(None);
+        }
+
+        self.release_kind(HeldKind::Dump).await;
+        // subscribe before registering, so that the end of the dump can't be missed.
+        let events = self.scheduler.read().await.subscribe();
+        let dump = self.register_dump_task(None).await?;
+        log::info!("Creating dump {} before shutting down.", dump.id);
+        self.wait_task_finished(dump.id, events).await.map(Some)
+    }
+
+    /// Waits until the task `uid` is finished, i.e. succeeded, failed or canceled, and returns it.
+    /// Fails with `TaskTimeout` if the task is still not finished after `timeout`.
+    pub async fn wait_for_task(&self, uid: TaskId, timeout: Duration) -> Result<Task> {
+        // subscribe before getting the task, so that its end can't be missed.
+        let events = self.scheduler.read().await.subscribe();
+        match tokio::time::timeout(timeout, self.wait_task_finished(uid, events)).await {
+            Ok(result) => result,
+            Err(_) => Err(IndexControllerError::TaskTimeout(uid, timeout)),
+        }
+    }
+
+    /// Waits until the write identified by `token` is visible to the reads, i.e. until its task
+    /// succeeded. Fails with `WriteNotApplied` if the task failed or was canceled, and with
+    /// `TaskTimeout` if the task is still not finished after `timeout`.
+    pub async fn wait_for_consistency(
+        &self,
+        token: ConsistencyToken,
+        timeout: Duration,
+    ) -> Result<()> {
+        let task = self.wait_for_task(token.0, timeout).await?;
+        match task.events.last() {
+            Some(TaskEvent::Succeeded { .. }) => Ok(()),
+            _ => Err(IndexControllerError::WriteNotApplied(task.id)),
+        }
+    }
+
+    /// Waits for the end of the task `uid` on `events`, which must have been subscribed to before
+    /// the task is registered or got.
+    async fn wait_task_finished(
+        &self,
+        uid: TaskId,
+        mut events: broadcast::Receiver<Task>,
+    ) -> Result<Task> {
+        let task = self.get_task(uid, None).await?;
+        if task.is_finished() {
+            return Ok(task);
+        }
+
+        loop {
+            match events.recv().await {
+                Ok(task) if task.id == uid && task.is_finished() => return Ok(task),
+                Ok(_) => (),
+                Err(broadcast::error::RecvError::Lagged(_)) => {
+                    let task = self.get_task(uid, None).await?;
+                    if task.is_finished() {
+                        return Ok(task);
+                    }
+                }
+                Err(broadcast::error::RecvError::Closed) => return self.get_task(uid, None).await,
+            }
+        }
+    }
+
+    /// Lists the dumps that were created in the dumps directory.
+    pub async fn list_dumps(&self) -> Result<Vec<DumpInfo>> {
+        let dump_path = self.dump_path.clone();
+        let dumps = spawn_blocking(move || dump::list_dumps(&dump_path)).await??;
+        Ok(dumps)
+    }
+
+    /// Returns the task creating the dump `dump_uid`, if there is one.
+    pub async fn dump_task_status(&self, dump_uid: &str) -> Result<Option<Task>> {
+        let dump_uid = dump_uid.to_string();
+        let mut filter = TaskFilter::default();
+        filter.filter_fn(Box::new(
+            move |task| matches!(&task.content, TaskContent::Dump { uid, .. } if *uid == dump_uid),
+        ));
+        let mut tasks = self
+            .task_store
+            .list_tasks(None, Some(filter), Some(1))
+            .await?;
+
+        Ok(tasks.pop())
+    }
+
+    /// Opens the file of the dump `uid` for reading.
+    pub async fn open_dump(&self, uid: &str) -> Result<tokio::fs::File> {
+        let path = dump::dump_file_path(&self.dump_path, uid)?;
+        Ok(tokio::fs::File::open(path).await?)
+    }
+
+    /// Keeps the work of the given kind enqueued until it is released with `release_kind`.
+    pub async fn hold_kind(&self, kind: HeldKind) {
+        self.scheduler.write().await.hold_kind(kind);
+    }
+
+    /// Lets the work of the given kind that was held be processed.
+    pub async fn release_kind(&self, kind: HeldKind) {
+        self.scheduler.write().await.release_kind(kind);
+    }
+
+    /// Registers a task canceling the given tasks. The batch currently processing is interrupted
+    /// right away if it contains any of them, the enqueued ones are removed from the queue before
+    /// the next batch is scheduled. The `reason` is recorded on every canceled task.
+    pub async fn register_cancelation_task(
+        &self,
+        tasks: Vec<TaskId>,
+        reason: Option<String>,
+    ) -> Result<Task> {
+        let content = TaskContent::TaskCancelation { tasks, reason };
+        let task = self.task_store.register(content).await?;
+        let scheduler = self.scheduler.read().await;
+        scheduler.cancel_processing(&task);
+        scheduler.publish(slice::from_ref(&task));
+        scheduler.notify();
         Ok(task)
     }
 
+    /// Registers a task canceling all the enqueued and processing tasks matching `filter`. The
+    /// tasks are resolved with `task_ids_from_query`, like the tasks of `list_tasks`.
+    pub async fn register_cancelation_task_matching(
+        &self,
+        filter: Option<TaskFilter>,
+        reason: Option<String>,
+    ) -> Result<Task> {
+        let mut unfinished = TaskFilter::default();
+        unfinished.filter_status(TaskStatus::Enqueued);
+        unfinished.filter_status(TaskStatus::Processing);
+
+        let ids = self.task_ids_from_query(filter).await?
+            & self.task_ids_from_query(Some(unfinished)).await?;
+
+        self.register_cancelation_task(ids.into_iter().collect(), reason)
+            .await
+    }
+
+    /// Imports a JSON array of tasks, see `TaskStore::import_tasks`, and returns the number of
+    /// imported tasks. The imported tasks that are not finished are then processed.
+    pub async fn import_tasks(&self, reader: impl Read + Send + 'static) -> Result<usize> {
+        let count = self.task_store.import_tasks(reader).await?;
+        self.scheduler.read().await.notify();
+        Ok(count)
+    }
+
+    /// Deletes the finished tasks among `to_delete`, along with the update files they may have
+    /// left behind, and returns the number of deleted tasks. Enqueued and processing tasks are
+    /// never deleted.
+    pub async fn delete_tasks(&self, to_delete: RoaringBitmap) -> Result<usize> {
+        let deleted = self.task_store.delete_tasks(to_delete).await?;
+        for task in &deleted {
+            if let Some(content_uuid) = task.get_content_uuid() {
+                // The update file of a processed task was already deleted once it was processed.
+                if let Err(e) = self.update_file_store.delete(content_uuid).await {
+                    log::debug!("no update file to delete for task {}: {}", task.id, e);
+                }
+            }
+        }
+
+        Ok(deleted.len())
+    }
+
+    /// Registers a retry of each failed task among `to_retry`, with the same content, and returns
+    /// the registered tasks. The retries are registered in the order of the tasks they retry, so
+    /// that a settings update is still processed before the document additions that followed it.
+    /// The update file of a document addition is deleted once it is processed, so the processed
+    /// document additions, failed ones included, can't be retried and are skipped.
+    pub async fn retry_tasks(&self, to_retry: RoaringBitmap) -> Result<Vec<Task>> {
+        let mut filter = TaskFilter::default();
+        filter.filter_uids(to_retry);
+        filter.filter_fn(Box::new(|task| {
+            matches!(task.events.last(), Some(TaskEvent::Failed { .. }))
+        }));
+        let mut failed = self.list_tasks(Some(filter), None, None).await?;
+        failed.sort_unstable_by_key(|task| task.id);
+
+        // The copies of the update files must not be seen as orphans before their task is
+        // registered.
+        let _registration_guard = self.registration_lock.read().await;
+        let mut retries = Vec::with_capacity(failed.len());
+        for task in failed {
+            let mut content = task.content;
+            if let TaskContent::DocumentAddition { content_uuid, .. } = &mut content {
+                let update_file_store = self.update_file_store.clone();
+                let original = *content_uuid;
+                let copy = spawn_blocking(move || match update_file_store.get_update(original) {
+                    Ok(_) => update_file_store.duplicate(original).map(Some),
+                    Err(_) => Ok(None),
+                })
+                .await??;
+                match copy {
+                    Some(copy) => *content_uuid = copy,
+                    None => {
+                        log::debug!("task {} can't be retried: its update file is gone", task.id);
+                        continue;
+                    }
+                }
+            }
+            retries.push((task.id, content));
+        }
+
+        let tasks = self.task_store.register_retries(retries).await?;
+        let scheduler = self.scheduler.read().await;
+        scheduler.publish(&tasks);
+        scheduler.notify();
+
+        Ok(tasks)
+    }
+
+    /// Subscribes to the tasks that are registered or change state, see `Scheduler::subscribe`,
+    /// and returns the tasks that are not finished yet along with the subscription. The
+    /// subscription is made first, so that no change is missed in between.
+    pub async fn subscribe_tasks(&self) -> Result<(Vec<Task>, broadcast::Receiver<Task>)> {
+        let events = self.scheduler.read().await.subscribe();
+        let unfinished = self.task_store.fetch_unfinished_tasks(None).await?;
+        Ok((unfinished, events))
+    }
+
+    /// Returns the number of tasks that are ahead of the task `id` in the queue. It's only a
+    /// snapshot of the queue, that may be outdated as soon as it is returned.
+    pub async fn enqueue_position(&self, id: TaskId) -> Result<u64> {
+        let position = self.task_store.count_unfinished_tasks_before(id).await?;
+        Ok(position)
+    }
+
     pub async fn get_task(&self, id: TaskId, filter: Option<TaskFilter>) -> Result<Task> {
         let task = self.scheduler.read().await.get_task(id, filter).await?;
         Ok(task)
@@ -488,6 +1086,80 @@ where
         Ok(tasks)
     }
 
+    /// Same as `list_tasks`, but also returns the number of tasks matching the filter. The total
+    /// and the tasks are computed from the same snapshot of the task store.
+    pub async fn list_tasks_with_total(
+        &self,
+        filter: Option<TaskFilter>,
+        limit: Option<usize>,
+        offset: Option<TaskId>,
+    ) -> Result<(u64, Vec<Task>)> {
+        let result = self
+            .scheduler
+            .read()
+            .await
+            .list_tasks_with_total(offset, filter, limit)
+            .await?;
+
+        Ok(result)
+    }
+
+    /// Returns the ids of the tasks matching `filter`. This is how the tasks of a query are
+    /// resolved, so that the operations working on a query match the same tasks as `list_tasks`.
+    pub async fn task_ids_from_query(&self, filter: Option<TaskFilter>) -> Result<RoaringBitmap> {
+        let ids = self.task_store.task_ids(filter).await?;
+        Ok(ids)
+    }
+
+    /// Counts the tasks matching the filter by status and kind, see `TaskContent::kind`, without
+    /// loading the tasks.
+    pub async fn count_tasks_by_status_and_kind(
+        &self,
+        filter: Option<TaskFilter>,
+    ) -> Result<BTreeMap<(TaskStatus, &'static str), u64>> {
+        let counts = self.task_store.count_by_status_and_kind(filter).await?;
+        Ok(counts)
+    }
+
+    /// Returns the number of tasks in each status for every day since `since`, see
+    /// `TaskStore::tasks_summary_by_day`.
+    pub async fn tasks_summary_by_day(&self, since: OffsetDateTime) -> Result<Vec<DaySummary>> {
+        let summaries = self.task_store.tasks_summary_by_day(since).await?;
+        Ok(summaries)
+    }
+
+    /// Returns a page of at most `limit` tasks, going in descending order from `offset`, along
+    /// with the total number of tasks matching the filter and the cursor of the next page. The
+    /// `limit` is lowered to the maximum allowed by the configuration, and the returned list holds
+    /// the limit that was actually used.
+    pub async fn list_tasks_paginated(
+        &self,
+        filter: Option<TaskFilter>,
+        limit: usize,
+        offset: Option<TaskId>,
+    ) -> Result<TaskList> {
+        let limit = limit.min(self.max_tasks_limit);
+        // We fetch one more task, to know if there is another page after this one.
+        let (total, mut results) = self
+            .list_tasks_with_total(filter, Some(limit.saturating_add(1)), offset)
+            .await?;
+
+        let next = if results.len() > limit {
+            results.pop().map(|task| task.id)
+        } else {
+            None
+        };
+        let from = results.first().map(|task| task.id);
+
+        Ok(TaskList {
+            results,
+            total,
+            limit,
+            from,
+            next,
+        })
+    }
+
     pub async fn list_index_task(
         &self,
         index_uid: String,
@@ -501,6 +1173,7 @@ where
 
         let mut filter = TaskFilter::default();
         filter.filter_index(index_uid);
+        let limit = limit.map(|limit| limit.min(self.max_tasks_limit));
 
         let tasks = self
             .scheduler
@@ -516,6 +1189,82 @@ where
         Ok(tasks)
     }
 
+    /// Lists all the files of the update file store, flagging the ones that no task references as
+    /// orphans.
+    pub async fn list_update_files(&self) -> Result<Vec<UpdateFileInfo>> {
+        let update_file_store = self.update_file_store.clone();
+        let files = spawn_blocking(move || update_file_store.list()).await??;
+
+        let mut filter = TaskFilter::default();
+        filter.filter_fn(Box::new(|task| {
+            matches!(task.content, TaskContent::DocumentAddition { .. })
+        }));
+        let referencing_tasks: HashMap<Uuid, TaskId> = self
+            .task_store
+            .list_tasks(None, Some(filter), None)
+            .await?
+            .into_iter()
+            .filter_map(|task| match task.content {
+                TaskContent::DocumentAddition { content_uuid, .. } => Some((content_uuid, task.id)),
+                _ => None,
+            })
+            .collect();
+
+        let mut update_files: Vec<_> = files
+            .into_iter()
+            .map(|(uuid, size)| UpdateFileInfo {
+                uuid,
+                size,
+                task_id: referencing_tasks.get(&uuid).copied(),
+            })
+            .collect();
+        update_files.sort_unstable_by_key(|file| file.uuid);
+
+        Ok(update_files)
+    }
+
+    /// Returns the total size in bytes of the update files of the enqueued document additions. An
+    /// update file that doesn't exist anymore counts as empty.
+    pub async fn pending_tasks_bytes(&self) -> Result<u64> {
+        let update_file_store = self.update_file_store.clone();
+        let files: HashMap<Uuid, u64> = spawn_blocking(move || update_file_store.list())
+            .await??
+            .into_iter()
+            .collect();
+
+        // Only the enqueued document additions are loaded, see `TaskStore::list_tasks`.
+        let mut filter = TaskFilter::default();
+        filter.filter_kind("DocumentAddition");
+        filter.filter_status(TaskStatus::Enqueued);
+        let bytes = self
+            .task_store
+            .list_tasks(None, Some(filter), None)
+            .await?
+            .iter()
+            .filter_map(Task::get_content_uuid)
+            .map(|uuid| files.get(&uuid).copied().unwrap_or(0))
+            .sum();
+
+        Ok(bytes)
+    }
+
+    /// Deletes the update files that are not referenced by any task, and returns the number of
+    /// deleted files. The document additions can't be registered meanwhile, so the update file of
+    /// a task being registered is never deleted.
+    pub async fn cleanup_orphaned_update_files(&self) -> Result<usize> {
+        let _registration_guard = self.registration_lock.write().await;
+
+        let mut deleted = 0;
+        for file in self.list_update_files().await? {
+            if file.is_orphan() {
+                self.update_file_store.delete(file.uuid).await?;
+                deleted += 1;
+            }
+        }
+
+        Ok(deleted)
+    }
+
     pub async fn list_indexes(&self) -> Result<Vec<IndexMetadata>> {
         let indexes = self.index_resolver.list().await?;
         let mut ret = Vec::new();
@@ -532,27 +1281,80 @@ where
         Ok(ret)
     }
 
+    /// Returns the number of indexes accepted by `filter`, along with the metadata of the `limit`
+    /// of them following the first `offset` ones. Only the indexes of the page are opened.
+    pub async fn list_indexes_paginated(
+        &self,
+        filter: impl Fn(&str) -> bool,
+        offset: usize,
+        limit: usize,
+    ) -> Result<(usize, Vec<IndexMetadata>)> {
+        let (total, indexes) = self
+            .index_resolver
+            .list_paginated(filter, offset, limit)
+            .await?;
+        let mut ret = Vec::new();
+        for (uid, index) in indexes {
+            let meta = index.meta()?;
+            let meta = IndexMetadata {
+                uuid: index.uuid(),
+                uid,
+                meta,
+            };
+            ret.push(meta);
+        }
+
+        Ok((total, ret))
+    }
+
+    /// Returns the current settings of the index `uid`. The index is retrieved through the index
+    /// resolver, so an index that is already open is not opened again.
     pub async fn settings(&self, uid: String) -> Result<Settings<Checked>> {
         let index = self.index_resolver.get_index(uid).await?;
         let settings = spawn_blocking(move || index.settings()).await??;
         Ok(settings)
     }
 
-    /// Return the total number of documents contained in the index + the selected documents.
+    /// Return the total number of documents matching `filter` + the selected documents + the
+    /// internal id to start the next page from, see `Index::retrieve_documents`.
     pub async fn documents(
         &self,
         uid: String,
         offset: usize,
         limit: usize,
+        from: Option<DocumentId>,
+        filter: Option<String>,
         attributes_to_retrieve: Option<Vec<String>>,
-    ) -> Result<(u64, Vec<Document>)> {
+    ) -> Result<(u64, Vec<Document>, Option<DocumentId>)> {
         let index = self.index_resolver.get_index(uid).await?;
-        let result =
-            spawn_blocking(move || index.retrieve_documents(offset, limit, attributes_to_retrieve))
-                .await??;
+        let result = spawn_blocking(move || {
+            index.retrieve_documents(
+                offset,
+                limit,
+                from,
+                filter.as_deref(),
+                attributes_to_retrieve,
+            )
+        })
+        .await??;
         Ok(result)
     }
 
+    /// Return the documents with the given ids, skipping the ids that don't match any document.
+    pub async fn documents_by_ids(
+        &self,
+        uid: String,
+        doc_ids: Vec<String>,
+        attributes_to_retrieve: Option<Vec<String>>,
+    ) -> Result<Vec<Document>> {
+        let index = self.index_resolver.get_index(uid).await?;
+        let documents = spawn_blocking(move || {
+            index.retrieve_documents_by_ids(doc_ids, attributes_to_retrieve)
+        })
+        .await??;
+        Ok(documents)
+    }
+
     pub async fn document(
         &self,
         uid: String,
@@ -572,6 +1374,12 @@ where
         Ok(result)
     }
 
+    /// Returns whether the index `uid` exists, without opening it.
+    pub async fn index_exists(&self, uid: &str) -> Result<bool> {
+        let exists = self.index_resolver.index_exists(uid.to_string()).await?;
+        Ok(exists)
+    }
+
     pub async fn get_index(&self, uid: String) -> Result<IndexMetadata> {
         let index = self.index_resolver.get_index(uid.clone()).await?;
         let uuid = index.uuid();
@@ -580,6 +1388,86 @@ where
         Ok(meta)
     }
 
+    /// Sets the `updated_at` of the index `uid` to now without changing anything else, e.g. to
+    /// mark it as refreshed. Fails with `IndexBusy` while a batch of the index is being processed.
+    pub async fn touch_index(&self, uid: String) -> Result<()> {
+        self.ensure_index_not_processing(&uid).await?;
+
+        let index = self.index_resolver.get_index(uid).await?;
+        spawn_blocking(move || index.touch()).await??;
+        Ok(())
+    }
+
+    /// Registers the deletion of the index `uid` only if it doesn't contain any document, and
+    /// returns `None` without registering anything otherwise. An index with pending document
+    /// additions is not considered empty. Fails with `IndexBusy` while a batch of the index is
+    /// being processed.
+    pub async fn delete_index_if_empty(&self, uid: String) -> Result<Option<Task>> {
+        self.ensure_index_not_processing(&uid).await?;
+
+        let index = self.index_resolver.get_index(uid.clone()).await?;
+        let stats = spawn_blocking(move || index.stats()).await??;
+        if stats.number_of_documents != 0 {
+            return Ok(None);
+        }
+
+        let mut filter = TaskFilter::default();
+        filter.filter_index(uid.clone());
+        filter.filter_fn(Box::new(|task| {
+            !task.is_finished() && matches!(task.content, TaskContent::DocumentAddition { .. })
+        }));
+        let pending_additions = self
+            .task_store
+            .list_tasks(None, Some(filter), Some(1))
+            .await?;
+        if !pending_additions.is_empty() {
+            return Ok(None);
+        }
+
+        let (task, _) = self.register_update(uid, Update::DeleteIndex).await?;
+        Ok(Some(task))
+    }
+
+    /// Fails right away, with the error the task would fail with, when the primary key of the
+    /// index `uid` can't be changed to `primary_key` because the index already has documents
+    /// with another primary key. Nothing is checked when the index doesn't exist yet, since it
+    /// may be created by an enqueued task.
+    async fn check_primary_key_update(&self, uid: &str, primary_key: &str) -> Result<()> {
+        let index = match self.index_resolver.get_index(uid.to_string()).await {
+            Ok(index) => index,
+            Err(IndexResolverError::UnexistingIndex(_)) => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        let (meta, stats) = spawn_blocking(move || -> std::result::Result<_, IndexError> {
+            Ok((index.meta()?, index.stats()?))
+        })
+        .await??;
+
+        match meta.primary_key {
+            Some(existing) if stats.number_of_documents != 0 && existing != primary_key => {
+                let error = milli::UserError::PrimaryKeyCannotBeChanged(existing);
+                Err(IndexResolverError::Milli(error.into()).into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns an `IndexBusy` error if a batch of the index `uid` is currently being processed.
+    /// Immediate admin operations must not race with the writes of an in-flight batch, while
+    /// registering new tasks is always allowed.
+    async fn ensure_index_not_processing(&self, uid: &str) -> Result<()> {
+        let processing_tasks = self.scheduler.read().await.get_processing_tasks().await?;
+        let is_processing = processing_tasks
+            .iter()
+            .any(|task| task.index_uid() == Some(uid));
+
+        if is_processing {
+            Err(IndexControllerError::IndexBusy(uid.to_owned()))
+        } else {
+            Ok(())
+        }
+    }
+
     pub async fn get_index_stats(&self, uid: String) -> Result<IndexStats> {
         let processing_tasks = self.scheduler.read().await.get_processing_tasks().await?;
         // Check if the currently indexing update is from our index.
@@ -594,6 +1482,27 @@ where
         Ok(stats)
     }
 
+    /// Drops the cached stats of the index `uid`, or of every index when `None`, and computes them
+    /// again from the indexes.
+    pub async fn recompute_stats(&self, uid: Option<String>) -> Result<()> {
+        let indexes = match uid {
+            Some(uid) => vec![self.index_resolver.get_index(uid).await?],
+            None => self
+                .index_resolver
+                .list()
+                .await?
+                .into_iter()
+                .map(|(_, index)| index)
+                .collect(),
+        };
+
+        for index in indexes {
+            spawn_blocking(move || index.recompute_stats()).await??;
+        }
+
+        Ok(())
+    }
+
     pub async fn get_all_stats(&self, search_rules: &SearchRules) -> Result<Stats> {
         let mut last_task: Option<OffsetDateTime> = None;
         let mut indexes = BTreeMap::new();
@@ -611,7 +1520,7 @@ where
                 })
                 .await??;
 
-            database_size += stats.size;
+            database_size += stats.database_size;
 
             last_task = last_task.map_or(Some(meta.updated_at), |last| {
                 Some(last.max(meta.updated_at))
@@ -647,6 +1556,85 @@ pub async fn get_arc_ownership_blocking<T>(mut item: Arc<T>) -> T {
     }
 }
 
+/// Writes the payload in a temporary file of the update files directory as it is received, so
+/// that large payloads are never held in memory as a whole. Returns the file, rewound, along with
+/// the size of the payload.
+async fn receive_payload(
+    mut payload: Payload,
+    update_file_store: &UpdateFileStore,
+) -> Result<(std::fs::File, usize)> {
+    let payload_file = tokio::fs::File::from_std(update_file_store.new_payload_file()?);
+    let mut payload_file = tokio::io::BufWriter::new(payload_file);
+    let mut payload_size = 0;
+    while let Some(bytes) = payload.next().await {
+        let bytes = bytes?;
+        payload_size += bytes.len();
+        payload_file.write_all(&bytes).await?;
+    }
+    payload_file.flush().await?;
+
+    let mut payload_file = payload_file.into_inner().into_std().await;
+    payload_file.seek(SeekFrom::Start(0))?;
+
+    Ok((payload_file, payload_size))
+}
+
+/// Returns whether both contents are document additions to the same index, with the same
+/// parameters and the same number of documents.
+fn same_document_addition_parameters(left: &TaskContent, right: &TaskContent) -> bool {
+    match (left, right) {
+        (
+            TaskContent::DocumentAddition {
+                merge_strategy: left_merge_strategy,
+                primary_key: left_primary_key,
+                documents_count: left_documents_count,
+                allow_index_creation: left_allow_index_creation,
+                index_uid: left_index_uid,
+                ..
+            },
+            TaskContent::DocumentAddition {
+                merge_strategy,
+                primary_key,
+                documents_count,
+                allow_index_creation,
+                index_uid,
+                ..
+            },
+        ) => {
+            left_merge_strategy == merge_strategy
+                && left_primary_key == primary_key
+                && left_documents_count == documents_count
+                && left_allow_index_creation == allow_index_creation
+                && left_index_uid == index_uid
+        }
+        _ => false,
+    }
+}
+
+/// Parses the documents of `payload` and writes them as an obkv batch in `writer`, returning the
+/// number of documents.
+fn read_documents(
+    format: DocumentAdditionFormat,
+    payload: impl Read,
+    writer: impl Write + Seek,
+    max_fields: usize,
+) -> std::result::Result<usize, DocumentFormatError> {
+    let max_fields = Some(max_fields);
+    // the readers buffer their input themselves.
+    match format {
+        DocumentAdditionFormat::Json => read_json(payload, writer, max_fields),
+        DocumentAdditionFormat::Csv {
+            delimiter,
+            quote,
+            has_header,
+        } => read_csv(payload, writer, delimiter, quote, has_header, max_fields),
+        DocumentAdditionFormat::Tsv { has_header } => {
+            read_tsv(payload, writer, has_header, max_fields)
+        }
+        DocumentAdditionFormat::Ndjson => read_ndjson(payload, writer, max_fields),
+    }
+}
+
 // Clamp the provided value to be a multiple of system page size.
 fn clamp_to_page_size(size: usize) -> usize {
     size / page_size::get() * page_size::get()
@@ -654,7 +1642,11 @@ fn clamp_to_page_size(size: usize) -> usize {
 
 #[cfg(test)]
 mod test {
+    use std::collections::BTreeSet;
+
     use futures::future::ok;
+    use meilisearch_types::error::{Code, ErrorCode};
+    use milli::update::Setting;
     use mockall::predicate::eq;
     use nelson::Mocker;
 
@@ -666,9 +1658,919 @@ mod test {
     use crate::index_resolver::index_store::MockIndexStore;
     use crate::index_resolver::meta_store::MockIndexMetaStore;
     use crate::index_resolver::IndexResolver;
+    use crate::tasks::error::Result as TaskResult;
+    use crate::tasks::MockBatchHandler;
 
     use super::*;
 
+    fn index_controller_with_documents(
+        index_uid: &str,
+        number_of_documents: u64,
+    ) -> IndexController<MockIndexMetaStore, MockIndexStore> {
+        let index_uuid = Uuid::new_v4();
+        let mut uuid_store = MockIndexMetaStore::new();
+        uuid_store
+            .expect_get()
+            .with(eq(index_uid.to_owned()))
+            .returning(move |s| {
+                Box::pin(ok((
+                    s,
+                    Some(crate::index_resolver::meta_store::IndexMeta {
+                        uuid: index_uuid,
+                        creation_task_id: 0,
+                    }),
+                )))
+            });
+
+        let mut index_store = MockIndexStore::new();
+        index_store
+            .expect_get()
+            .with(eq(index_uuid))
+            .returning(move |_uuid| {
+                let mocker = Mocker::default();
+                mocker
+                    .when::<(), IndexResult<IndexStats>>("stats")
+                    .once()
+                    .then(move |()| {
+                        Ok(IndexStats {
+                            database_size: 0,
+                            number_of_documents,
+                            is_indexing: None,
+                            field_distribution: Default::default(),
+                        })
+                    });
+                Box::pin(ok(Some(Index::mock(mocker))))
+            });
+
+        let task_store_mocker = Mocker::default();
+        task_store_mocker
+            .when::<(Option<TaskId>, Option<TaskFilter>, Option<usize>), TaskResult<Vec<Task>>>(
+                "list_tasks",
+            )
+            .then(|_| Ok(Vec::new()));
+        let processing_index_uid = IndexUid::new_unchecked(index_uid);
+        task_store_mocker
+            .when::<(TaskId, Option<TaskFilter>), TaskResult<Task>>("get_task")
+            .then(move |(id, _filter)| {
+                Ok(Task {
+                    id,
+                    content: TaskContent::DocumentAddition {
+                        index_uid: processing_index_uid.clone(),
+                        content_uuid: Uuid::new_v4(),
+                        merge_strategy: IndexDocumentsMethod::ReplaceDocuments,
+                        primary_key: None,
+                        documents_count: 12,
+                        allow_index_creation: true,
+                    },
+                    events: Vec::new(),
+                    retry_of: None,
+                    priority: 0,
+                })
+            });
+        task_store_mocker
+            .when::<(TaskContent, u8), TaskResult<Task>>("register_with_priority")
+            .then(|(content, priority)| {
+                Ok(Task {
+                    id: 0,
+                    content,
+                    events: Vec::new(),
+                    retry_of: None,
+                    priority,
+                })
+            });
+
+        gen_index_controller(
+            uuid_store,
+            index_store,
+            TaskStore::mock(task_store_mocker),
+            UpdateFileStore::mock(Mocker::default()),
+        )
+    }
+
+    /// Returns an index controller over the given stores, whose scheduler processes the tasks
+    /// with an index resolver over the same stores.
+    fn gen_index_controller(
+        meta_store: MockIndexMetaStore,
+        index_store: MockIndexStore,
+        task_store: TaskStore,
+        update_file_store: UpdateFileStore,
+    ) -> IndexController<MockIndexMetaStore, MockIndexStore> {
+        let index_resolver = Arc::new(IndexResolver::new(
+            meta_store,
+            index_store,
+            update_file_store.clone(),
+        ));
+        let scheduler = Scheduler::new(
+            task_store.clone(),
+            vec![index_resolver.clone()],
+            SchedulerConfig::default(),
+        )
+        .unwrap();
+
+        IndexController::mock(index_resolver, task_store, update_file_store, scheduler)
+    }
+
+    /// Returns an index controller over the given stores, whose scheduler marks every task as
+    /// succeeded without processing it.
+    fn gen_succeeding_index_controller(
+        task_store: TaskStore,
+        update_file_store: UpdateFileStore,
+        config: SchedulerConfig,
+    ) -> IndexController<MockIndexMetaStore, MockIndexStore> {
+        let mut handler = MockBatchHandler::new();
+        handler.expect_accept().returning(|_| true);
+        handler.expect_process_batch().returning(|mut batch| {
+            batch
+                .content
+                .push_event(TaskEvent::succeeded(crate::tasks::task::TaskResult::Other));
+            batch
+        });
+        handler.expect_finish().returning(|_| ());
+        let scheduler =
+            Scheduler::new(task_store.clone(), vec![Arc::new(handler)], config).unwrap();
+        let index_resolver = Arc::new(IndexResolver::new(
+            MockIndexMetaStore::new(),
+            MockIndexStore::new(),
+            update_file_store.clone(),
+        ));
+
+        IndexController::mock(index_resolver, task_store, update_file_store, scheduler)
+    }
+
+    #[actix_rt::test]
+    async fn delete_empty_index() {
+        let index_controller = index_controller_with_documents("test", 0);
+
+        let task = index_controller
+            .delete_index_if_empty("test".to_owned())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(task.content, TaskContent::IndexDeletion { .. }));
+        assert_eq!(task.index_uid(), Some("test"));
+    }
+
+    #[actix_rt::test]
+    async fn keep_populated_index() {
+        let index_controller = index_controller_with_documents("test", 12);
+
+        let task = index_controller
+            .delete_index_if_empty("test".to_owned())
+            .await
+            .unwrap();
+        assert!(task.is_none());
+    }
+
+    #[actix_rt::test]
+    async fn delete_index_while_processing() {
+        let index_controller = index_controller_with_documents("test", 0);
+
+        index_controller
+            .scheduler
+            .write()
+            .await
+            .set_processing_document_additions(vec![1]);
+
+        let error = index_controller
+            .delete_index_if_empty("test".to_owned())
+            .await
+            .unwrap_err();
+        assert!(matches!(error, IndexControllerError::IndexBusy(ref uid) if uid == "test"));
+        assert!(matches!(error.error_code(), Code::IndexBusy));
+
+        // registering a deletion is still allowed, it is processed after the in-flight batch.
+        let (task, _) = index_controller
+            .register_update("test".to_owned(), Update::DeleteIndex)
+            .await
+            .unwrap();
+        assert!(matches!(task.content, TaskContent::IndexDeletion { .. }));
+    }
+
+    #[actix_rt::test]
+    async fn list_update_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let update_file_store = UpdateFileStore::new(temp.path()).unwrap();
+
+        let (referenced, file) = update_file_store.new_update().unwrap();
+        file.persist().unwrap();
+        let (orphan, mut file) = update_file_store.new_update().unwrap();
+        file.write_all(b"orphan").unwrap();
+        file.persist().unwrap();
+
+        let task_store_mocker = Mocker::default();
+        task_store_mocker
+            .when::<(Option<TaskId>, Option<TaskFilter>, Option<usize>), TaskResult<Vec<Task>>>(
+                "list_tasks",
+            )
+            .then(move |_| {
+                Ok(vec![Task {
+                    id: 3,
+                    content: TaskContent::DocumentAddition {
+                        index_uid: IndexUid::new_unchecked("test"),
+                        content_uuid: referenced,
+                        merge_strategy: IndexDocumentsMethod::ReplaceDocuments,
+                        primary_key: None,
+                        documents_count: 0,
+                        allow_index_creation: true,
+                    },
+                    events: Vec::new(),
+                    retry_of: None,
+                    priority: 0,
+                }])
+            });
+        let task_store = TaskStore::mock(task_store_mocker);
+
+        let index_controller = gen_index_controller(
+            MockIndexMetaStore::new(),
+            MockIndexStore::new(),
+            task_store,
+            update_file_store,
+        );
+
+        let files = index_controller.list_update_files().await.unwrap();
+        assert_eq!(files.len(), 2);
+
+        let referenced = files.iter().find(|f| f.uuid == referenced).unwrap();
+        assert_eq!(referenced.task_id, Some(3));
+        assert!(!referenced.is_orphan());
+
+        let orphan = files.iter().find(|f| f.uuid == orphan).unwrap();
+        assert_eq!(orphan.task_id, None);
+        assert_eq!(orphan.size, 6);
+        assert!(orphan.is_orphan());
+    }
+
+    #[actix_rt::test]
+    async fn pending_tasks_bytes() {
+        let temp = tempfile::tempdir().unwrap();
+        let update_file_store = UpdateFileStore::new(temp.path()).unwrap();
+
+        let mut new_update = |content: &[u8]| {
+            let (uuid, mut file) = update_file_store.new_update().unwrap();
+            file.write_all(content).unwrap();
+            file.persist().unwrap();
+            uuid
+        };
+        let enqueued = new_update(b"kefir");
+        let batched = new_update(b"intel");
+        let succeeded = new_update(b"bobby");
+        let missing = Uuid::new_v4();
+
+        let task = |id, content_uuid, events| Task {
+            id,
+            content: TaskContent::DocumentAddition {
+                index_uid: IndexUid::new_unchecked("test"),
+                content_uuid,
+                merge_strategy: IndexDocumentsMethod::ReplaceDocuments,
+                primary_key: None,
+                documents_count: 1,
+                allow_index_creation: true,
+            },
+            events,
+            retry_of: None,
+            priority: 0,
+        };
+        let now = OffsetDateTime::now_utc();
+        let tasks = vec![
+            task(0, enqueued, vec![TaskEvent::Created(now)]),
+            task(
+                1,
+                batched,
+                vec![
+                    TaskEvent::Created(now),
+                    TaskEvent::Batched {
+                        timestamp: now,
+                        batch_id: 1,
+                    },
+                ],
+            ),
+            task(
+                2,
+                succeeded,
+                vec![
+                    TaskEvent::Created(now),
+                    TaskEvent::succeeded(crate::tasks::task::TaskResult::Other),
+                ],
+            ),
+            task(3, missing, vec![TaskEvent::Created(now)]),
+        ];
+
+        let env =
+            Arc::new(open_meta_env(temp.path(), 100 * 1024 * 1024, SyncMode::default()).unwrap());
+        let task_store = TaskStore::new(env.clone()).unwrap();
+        let mut txn = env.write_txn().unwrap();
+        for task in &tasks {
+            task_store.register_raw_update(&mut txn, task).unwrap();
+        }
+        txn.commit().unwrap();
+
+        // the scheduler works on another store, so that the tasks stay in their status.
+        let scheduler_dir = tempfile::tempdir().unwrap();
+        let scheduler_env =
+            open_meta_env(scheduler_dir.path(), 100 * 1024 * 1024, SyncMode::default()).unwrap();
+        let scheduler = Scheduler::new(
+            TaskStore::new(Arc::new(scheduler_env)).unwrap(),
+            vec![Arc::new(EmptyBatchHandler)],
+            SchedulerConfig::default(),
+        )
+        .unwrap();
+        let index_resolver = Arc::new(IndexResolver::new(
+            MockIndexMetaStore::new(),
+            MockIndexStore::new(),
+            update_file_store.clone(),
+        ));
+        let index_controller =
+            IndexController::mock(index_resolver, task_store, update_file_store, scheduler);
+
+        // only the files of the enqueued and batched tasks are counted.
+        assert_eq!(index_controller.pending_tasks_bytes().await.unwrap(), 10);
+    }
+
+    #[actix_rt::test]
+    async fn cleanup_orphaned_update_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let update_file_store = UpdateFileStore::new(temp.path()).unwrap();
+
+        let (referenced, file) = update_file_store.new_update().unwrap();
+        file.persist().unwrap();
+        let (orphan, file) = update_file_store.new_update().unwrap();
+        file.persist().unwrap();
+
+        let task_store_mocker = Mocker::default();
+        task_store_mocker
+            .when::<(Option<TaskId>, Option<TaskFilter>, Option<usize>), TaskResult<Vec<Task>>>(
+                "list_tasks",
+            )
+            .then(move |_| {
+                Ok(vec![Task {
+                    id: 3,
+                    content: TaskContent::DocumentAddition {
+                        index_uid: IndexUid::new_unchecked("test"),
+                        content_uuid: referenced,
+                        merge_strategy: IndexDocumentsMethod::ReplaceDocuments,
+                        primary_key: None,
+                        documents_count: 0,
+                        allow_index_creation: true,
+                    },
+                    events: Vec::new(),
+                    retry_of: None,
+                    priority: 0,
+                }])
+            });
+        let task_store = TaskStore::mock(task_store_mocker);
+
+        let index_controller = gen_index_controller(
+            MockIndexMetaStore::new(),
+            MockIndexStore::new(),
+            task_store,
+            update_file_store.clone(),
+        );
+
+        assert_eq!(
+            index_controller
+                .cleanup_orphaned_update_files()
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(update_file_store.get_update(referenced).is_ok());
+        assert!(update_file_store.get_update(orphan).is_err());
+    }
+
+    #[actix_rt::test]
+    async fn payload_overflowing_mid_stream_leaves_no_update_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let update_file_store = UpdateFileStore::new(temp.path()).unwrap();
+
+        // A chunked payload has no known length, so the limit is only reached once some chunks
+        // were already received.
+        let chunk = || Ok(Bytes::from("{\"id\": 1, \"name\": \"kefir\"}\n"));
+        let payload: Payload = Box::new(futures::stream::iter(vec![
+            chunk(),
+            chunk(),
+            Err(PayloadError::Overflow),
+            chunk(),
+        ]));
+        let addition = Update::DocumentAddition {
+            payload,
+            primary_key: None,
+            method: IndexDocumentsMethod::ReplaceDocuments,
+            format: DocumentAdditionFormat::Ndjson,
+            allow_index_creation: true,
+        };
+
+        // No task must be registered.
+        let task_store = TaskStore::mock(Mocker::default());
+        let index_controller = gen_index_controller(
+            MockIndexMetaStore::new(),
+            MockIndexStore::new(),
+            task_store,
+            update_file_store,
+        );
+
+        let error = index_controller
+            .register_update("doggos".to_string(), addition)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, IndexControllerError::PayloadTooLarge));
+        assert!(matches!(error.error_code(), Code::PayloadTooLarge));
+
+        let update_files = std::fs::read_dir(temp.path().join("updates/updates_files")).unwrap();
+        assert_eq!(update_files.count(), 0);
+    }
+
+    #[actix_rt::test]
+    async fn failed_registration_leaves_no_update_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let update_file_store = UpdateFileStore::new(temp.path()).unwrap();
+        let payload: Payload = Box::new(futures::stream::iter(vec![Ok(Bytes::from(
+            "{\"id\": 1, \"name\": \"kefir\"}\n",
+        ))]));
+        let addition = Update::DocumentAddition {
+            payload,
+            primary_key: None,
+            method: IndexDocumentsMethod::ReplaceDocuments,
+            format: DocumentAdditionFormat::Ndjson,
+            allow_index_creation: true,
+        };
+
+        // The commit of the registration fails.
+        let task_store_mocker = Mocker::default();
+        task_store_mocker
+            .when::<(TaskContent, u8), TaskResult<Task>>("register_with_priority")
+            .once()
+            .then(|_| Err(TaskError::TaskDatabaseFull));
+        let task_store = TaskStore::mock(task_store_mocker);
+        let index_controller = gen_index_controller(
+            MockIndexMetaStore::new(),
+            MockIndexStore::new(),
+            task_store,
+            update_file_store,
+        );
+
+        let error = index_controller
+            .register_update("doggos".to_string(), addition)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            IndexControllerError::TaskError(TaskError::TaskDatabaseFull)
+        ));
+
+        assert!(index_controller
+            .update_file_store
+            .list()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn process_tasks_in_every_sync_mode() {
+        for sync_mode in [SyncMode::Always, SyncMode::Periodic] {
+            let dir = tempfile::tempdir().unwrap();
+            let env = Arc::new(open_meta_env(dir.path(), 100 * 1024 * 1024, sync_mode).unwrap());
+            let config = SchedulerConfig {
+                tasks_sync_mode: sync_mode,
+                ..Default::default()
+            };
+            let index_controller = gen_succeeding_index_controller(
+                TaskStore::new(env).unwrap(),
+                UpdateFileStore::new(dir.path()).unwrap(),
+                config,
+            );
+
+            let task = index_controller
+                .task_store
+                .register(TaskContent::IndexCreation {
+                    index_uid: IndexUid::new_unchecked("doggos"),
+                    primary_key: None,
+                })
+                .await
+                .unwrap();
+            index_controller.scheduler.read().await.notify();
+
+            let task = index_controller
+                .wait_for_task(task.id, Duration::from_secs(10))
+                .await
+                .unwrap();
+            assert!(
+                matches!(task.events.last(), Some(TaskEvent::Succeeded { .. })),
+                "{}: {:?}",
+                sync_mode,
+                task.events
+            );
+        }
+    }
+
+    #[actix_rt::test]
+    async fn deleted_task_uids_are_never_given_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let env =
+            Arc::new(open_meta_env(dir.path(), 100 * 1024 * 1024, SyncMode::default()).unwrap());
+        let index_controller = gen_succeeding_index_controller(
+            TaskStore::new(env).unwrap(),
+            UpdateFileStore::new(dir.path()).unwrap(),
+            SchedulerConfig::default(),
+        );
+
+        let creation = TaskContent::IndexCreation {
+            index_uid: IndexUid::new_unchecked("doggos"),
+            primary_key: None,
+        };
+        for expected_uid in 0..2 {
+            let task = index_controller
+                .task_store
+                .register(creation.clone())
+                .await
+                .unwrap();
+            assert_eq!(task.id, expected_uid);
+            index_controller.scheduler.read().await.notify();
+
+            let task = index_controller
+                .wait_for_task(task.id, Duration::from_secs(10))
+                .await
+                .unwrap();
+            assert!(matches!(
+                task.events.last(),
+                Some(TaskEvent::Succeeded { .. })
+            ));
+
+            // the last task is deleted, its uid must not be given to the next task.
+            let deleted = index_controller
+                .delete_tasks(RoaringBitmap::from_iter([task.id]))
+                .await
+                .unwrap();
+            assert_eq!(deleted, 1);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn retry_failed_settings_then_import() {
+        let dir = tempfile::tempdir().unwrap();
+        let env =
+            Arc::new(open_meta_env(dir.path(), 100 * 1024 * 1024, SyncMode::default()).unwrap());
+        let task_store = TaskStore::new(env.clone()).unwrap();
+        let update_file_store = UpdateFileStore::new(dir.path()).unwrap();
+
+        let index_uid = IndexUid::new_unchecked("doggos");
+        let (import_uuid, mut update_file) = update_file_store.new_update().unwrap();
+        read_ndjson("{\"id\": 1}\n".as_bytes(), &mut *update_file, None).unwrap();
+        update_file.persist().unwrap();
+        let addition = |content_uuid| TaskContent::DocumentAddition {
+            index_uid: index_uid.clone(),
+            content_uuid,
+            merge_strategy: IndexDocumentsMethod::ReplaceDocuments,
+            primary_key: None,
+            documents_count: 1,
+            allow_index_creation: true,
+        };
+        let settings = TaskContent::SettingsUpdate {
+            index_uid: index_uid.clone(),
+            settings: Settings::default(),
+            is_deletion: false,
+            allow_index_creation: true,
+        };
+        let failed = TaskEvent::failed(IndexResolverError::UnexistingIndex("doggos".to_string()));
+        let gen_task = |id, content, last_event| Task {
+            id,
+            content,
+            events: vec![TaskEvent::Created(OffsetDateTime::now_utc()), last_event],
+            retry_of: None,
+            priority: 0,
+        };
+        let tasks = [
+            gen_task(0, settings.clone(), failed.clone()),
+            gen_task(1, addition(import_uuid), failed.clone()),
+            // the update file of this addition was already deleted.
+            gen_task(2, addition(Uuid::new_v4()), failed.clone()),
+            gen_task(
+                3,
+                TaskContent::IndexDeletion {
+                    index_uid: index_uid.clone(),
+                },
+                TaskEvent::succeeded(crate::tasks::task::TaskResult::Other),
+            ),
+        ];
+        let mut txn = env.write_txn().unwrap();
+        for task in &tasks {
+            task_store.register_raw_update(&mut txn, task).unwrap();
+        }
+        txn.commit().unwrap();
+
+        let index_controller = gen_index_controller(
+            MockIndexMetaStore::new(),
+            MockIndexStore::new(),
+            task_store.clone(),
+            update_file_store.clone(),
+        );
+
+        let retries = index_controller
+            .retry_tasks(RoaringBitmap::from_iter(0..4))
+            .await
+            .unwrap();
+
+        // the settings are still updated before the documents are imported.
+        let retried: Vec<_> = retries
+            .iter()
+            .map(|task| (task.id, task.retry_of))
+            .collect();
+        assert_eq!(retried, vec![(4, Some(0)), (5, Some(1))]);
+        assert_eq!(retries[0].content, settings);
+        // the retried import has its own copy of the update file.
+        let copy_uuid = retries[1].get_content_uuid().unwrap();
+        assert_ne!(copy_uuid, import_uuid);
+        assert_eq!(
+            update_file_store.hash(copy_uuid).unwrap(),
+            update_file_store.hash(import_uuid).unwrap()
+        );
+
+        // a retry of a retry is linked to the first task.
+        let mut import_retry = retries[1].clone();
+        import_retry.push_event(failed);
+        task_store.update_tasks(vec![import_retry]).await.unwrap();
+        let retries = index_controller
+            .retry_tasks(RoaringBitmap::from_iter([5]))
+            .await
+            .unwrap();
+        assert_eq!(retries[0].id, 6);
+        assert_eq!(retries[0].retry_of, Some(1));
+    }
+
+    #[actix_rt::test]
+    async fn cancel_unfinished_matching_tasks() {
+        let dir = tempfile::tempdir().unwrap();
+        let env =
+            Arc::new(open_meta_env(dir.path(), 100 * 1024 * 1024, SyncMode::default()).unwrap());
+        let task_store = TaskStore::new(env.clone()).unwrap();
+
+        let gen_task = |id, events| Task {
+            id,
+            content: TaskContent::Dump {
+                uid: format!("dump-{}", id),
+                indexes: None,
+            },
+            events,
+            retry_of: None,
+            priority: 0,
+        };
+        let now = OffsetDateTime::now_utc();
+        let tasks = [
+            gen_task(
+                0,
+                vec![
+                    TaskEvent::Created(now),
+                    TaskEvent::Canceled {
+                        canceled_by: 2,
+                        reason: None,
+                        timestamp: now,
+                    },
+                ],
+            ),
+            gen_task(
+                1,
+                vec![
+                    TaskEvent::Created(now),
+                    TaskEvent::Batched {
+                        timestamp: now,
+                        batch_id: 1,
+                    },
+                    TaskEvent::Processing(now),
+                ],
+            ),
+            gen_task(2, vec![TaskEvent::Created(now)]),
+            // doesn't match the filter.
+            gen_task(3, vec![TaskEvent::Created(now)]),
+        ];
+        let mut txn = env.write_txn().unwrap();
+        for task in &tasks {
+            task_store.register_raw_update(&mut txn, task).unwrap();
+        }
+        txn.commit().unwrap();
+
+        let index_controller = gen_index_controller(
+            MockIndexMetaStore::new(),
+            MockIndexStore::new(),
+            task_store,
+            UpdateFileStore::new(dir.path()).unwrap(),
+        );
+        // the dumps stay enqueued, so that nothing changes their status.
+        index_controller
+            .scheduler
+            .write()
+            .await
+            .hold_kind(HeldKind::Dump);
+
+        let mut filter = TaskFilter::default();
+        filter.filter_uids([0, 1, 2]);
+        let task = index_controller
+            .register_cancelation_task_matching(Some(filter), Some("wrong index".to_string()))
+            .await
+            .unwrap();
+
+        assert_eq!(task.id, 4);
+        assert_eq!(
+            task.content,
+            TaskContent::TaskCancelation {
+                tasks: vec![1, 2],
+                reason: Some("wrong index".to_string()),
+            }
+        );
+    }
+
+    #[actix_rt::test]
+    async fn deduplicate_identical_document_additions() {
+        let documents = "{\"id\": 1, \"name\": \"kefir\"}\n{\"id\": 2, \"name\": \"intel\"}\n";
+        let payload = |content: &'static str| -> Payload {
+            Box::new(futures::stream::iter(vec![Ok(Bytes::from(content))]))
+        };
+        let addition = |payload| Update::DocumentAddition {
+            payload,
+            primary_key: None,
+            method: IndexDocumentsMethod::ReplaceDocuments,
+            format: DocumentAdditionFormat::Ndjson,
+            allow_index_creation: true,
+        };
+
+        let dir = tempfile::tempdir().unwrap();
+        let update_file_store = UpdateFileStore::new(dir.path()).unwrap();
+        let (enqueued_uuid, mut update_file) = update_file_store.new_update().unwrap();
+        read_ndjson(documents.as_bytes(), &mut *update_file, None).unwrap();
+        update_file.persist().unwrap();
+
+        let enqueued = Task {
+            id: 0,
+            content: TaskContent::DocumentAddition {
+                index_uid: IndexUid::new_unchecked("doggos"),
+                content_uuid: enqueued_uuid,
+                merge_strategy: IndexDocumentsMethod::ReplaceDocuments,
+                primary_key: None,
+                documents_count: 2,
+                allow_index_creation: true,
+            },
+            events: vec![TaskEvent::Created(OffsetDateTime::now_utc())],
+            retry_of: None,
+            priority: 0,
+        };
+
+        let task_store_mocker = Mocker::default();
+        task_store_mocker
+            .when::<(Option<TaskId>, Option<TaskFilter>, Option<usize>), TaskResult<Vec<Task>>>(
+                "list_tasks",
+            )
+            .then(move |_| Ok(vec![enqueued.clone()]));
+        task_store_mocker
+            .when::<(TaskContent, u8), TaskResult<Task>>("register_with_priority")
+            .once()
+            .then(|(content, priority)| {
+                Ok(Task {
+                    id: 1,
+                    content,
+                    events: Vec::new(),
+                    retry_of: None,
+                    priority,
+                })
+            });
+        let task_store = TaskStore::mock(task_store_mocker);
+
+        let mut index_controller = gen_index_controller(
+            MockIndexMetaStore::new(),
+            MockIndexStore::new(),
+            task_store,
+            update_file_store,
+        );
+        index_controller.deduplicate_document_additions = true;
+
+        // The same documents are merged into the enqueued task, and their update file is removed.
+        let (task, _) = index_controller
+            .register_update("doggos".to_string(), addition(payload(documents)))
+            .await
+            .unwrap();
+        assert_eq!(task.id, 0);
+        assert_eq!(index_controller.update_file_store.list().unwrap().len(), 1);
+
+        // Different documents are registered as a new task.
+        let (task, _) = index_controller
+            .register_update(
+                "doggos".to_string(),
+                addition(payload(
+                    "{\"id\": 1, \"name\": \"kefir\"}\n{\"id\": 2, \"name\": \"bobby\"}\n",
+                )),
+            )
+            .await
+            .unwrap();
+        assert_eq!(task.id, 1);
+        assert_eq!(index_controller.update_file_store.list().unwrap().len(), 2);
+    }
+
+    #[actix_rt::test]
+    async fn settings_reflect_applied_settings_task() {
+        let dir = tempfile::tempdir().unwrap();
+        let index_uuid = Uuid::new_v4();
+        let index = Index::open(
+            dir.path().join(index_uuid.to_string()),
+            100 * 1024 * 1024,
+            index_uuid,
+            Arc::new(milli::update::IndexerConfig::default()),
+        )
+        .unwrap();
+
+        let mut uuid_store = MockIndexMetaStore::new();
+        uuid_store
+            .expect_get()
+            .with(eq("doggos".to_owned()))
+            .returning(move |s| {
+                Box::pin(ok((
+                    s,
+                    Some(crate::index_resolver::meta_store::IndexMeta {
+                        uuid: index_uuid,
+                        creation_task_id: 0,
+                    }),
+                )))
+            });
+        let mut index_store = MockIndexStore::new();
+        index_store
+            .expect_get()
+            .with(eq(index_uuid))
+            .returning(move |_| Box::pin(ok(Some(index.clone()))));
+
+        let index_controller = gen_index_controller(
+            uuid_store,
+            index_store,
+            TaskStore::mock(Mocker::default()),
+            UpdateFileStore::mock(Mocker::default()),
+        );
+
+        let mut task = Task {
+            id: 1,
+            content: TaskContent::SettingsUpdate {
+                settings: Settings {
+                    displayed_attributes: Setting::Set(vec![String::from("name")]),
+                    stop_words: Setting::Set(BTreeSet::from([String::from("the")])),
+                    ..Default::default()
+                },
+                is_deletion: false,
+                allow_index_creation: false,
+                index_uid: IndexUid::new_unchecked("doggos"),
+            },
+            events: Vec::new(),
+            retry_of: None,
+            priority: 0,
+        };
+        index_controller
+            .index_resolver
+            .process_task(&mut task)
+            .await;
+        assert!(matches!(
+            task.events.last(),
+            Some(TaskEvent::Succeeded { .. })
+        ));
+
+        let settings = index_controller
+            .settings("doggos".to_string())
+            .await
+            .unwrap();
+        assert_eq!(
+            settings.displayed_attributes,
+            Setting::Set(vec![String::from("name")])
+        );
+        assert_eq!(
+            settings.stop_words,
+            Setting::Set(BTreeSet::from([String::from("the")]))
+        );
+        assert_eq!(settings.distinct_attribute, Setting::Reset);
+    }
+
+    #[actix_rt::test]
+    async fn list_tasks_paginated_caps_the_limit() {
+        let task_store_mocker = Mocker::default();
+        task_store_mocker
+            .when::<(Option<TaskId>, Option<TaskFilter>, Option<usize>), TaskResult<(u64, Vec<Task>)>>(
+                "list_tasks_with_total",
+            )
+            .once()
+            .then(|(_, _, limit)| {
+                // One more task than the limit is fetched to know if there is a next page.
+                assert_eq!(limit, Some(3));
+                Ok((0, Vec::new()))
+            });
+        let task_store = TaskStore::mock(task_store_mocker);
+
+        let update_file_store = UpdateFileStore::mock(Mocker::default());
+        let mut index_controller = gen_index_controller(
+            MockIndexMetaStore::new(),
+            MockIndexStore::new(),
+            task_store,
+            update_file_store,
+        );
+        index_controller.max_tasks_limit = 2;
+
+        let list = index_controller
+            .list_tasks_paginated(None, 4_000_000_000, None)
+            .await
+            .unwrap();
+        assert_eq!(list.limit, 2);
+    }
+
     impl IndexController<MockIndexMetaStore, MockIndexStore> {
         pub fn mock(
             index_resolver: Arc<IndexResolver<MockIndexMetaStore, MockIndexStore>>,
@@ -681,6 +2583,12 @@ mod test {
                 task_store,
                 update_file_store,
                 scheduler,
+                deduplicate_document_additions: false,
+                max_fields_per_document: IndexerOpts::default().max_fields_per_document,
+                max_tasks_limit: SchedulerConfig::default().max_tasks_limit,
+                registration_lock: Arc::default(),
+                dump_path: PathBuf::new(),
+                dump_on_shutdown: false,
             }
         }
     }
@@ -755,20 +2663,9 @@ mod test {
         let task_store_mocker = nelson::Mocker::default();
         let mocker = Mocker::default();
         let update_file_store = UpdateFileStore::mock(mocker);
-        let index_resolver = Arc::new(IndexResolver::new(
-            uuid_store,
-            index_store,
-            update_file_store.clone(),
-        ));
         let task_store = TaskStore::mock(task_store_mocker);
-        let scheduler = Scheduler::new(
-            task_store.clone(),
-            vec![index_resolver.clone()],
-            SchedulerConfig::default(),
-        )
-        .unwrap();
         let index_controller =
-            IndexController::mock(index_resolver, task_store, update_file_store, scheduler);
+            gen_index_controller(uuid_store, index_store, task_store, update_file_store);
 
         let r = index_controller
             .search(index_uid.to_owned(), query.clone())
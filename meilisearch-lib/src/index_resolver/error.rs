@@ -3,6 +3,8 @@ use std::fmt;
 use meilisearch_types::error::{Code, ErrorCode};
 use meilisearch_types::index_uid::IndexUidFormatError;
 use meilisearch_types::internal_error;
+use milli::heed::{Error as HeedError, MdbError};
+use milli::InternalError;
 use tokio::sync::mpsc::error::SendError as MpscSendError;
 use tokio::sync::oneshot::error::RecvError as OneshotRecvError;
 use uuid::Uuid;
@@ -29,6 +31,10 @@ pub enum IndexResolverError {
     Milli(#[from] milli::Error),
     #[error("{0}")]
     BadlyFormatted(#[from] IndexUidFormatError),
+    #[error(
+        "Adding these documents would exceed the maximum of `{0}` documents across all indexes."
+    )]
+    TotalDocumentsQuotaExceeded(u64),
 }
 
 impl<T> From<MpscSendError<T>> for IndexResolverError
@@ -66,6 +72,33 @@ impl ErrorCode for IndexResolverError {
             IndexResolverError::UuidAlreadyExists(_) => Code::CreateIndex,
             IndexResolverError::Milli(e) => MilliError(e).error_code(),
             IndexResolverError::BadlyFormatted(_) => Code::InvalidIndexUid,
+            IndexResolverError::TotalDocumentsQuotaExceeded(_) => Code::TotalDocumentsQuotaExceeded,
+        }
+    }
+}
+
+impl IndexResolverError {
+    /// Returns whether the error is caused by a transient state of LMDB, in which case the
+    /// operation may succeed if it is attempted again, e.g. when the environment was resized by
+    /// another process while the index was being opened.
+    pub fn is_transient(&self) -> bool {
+        fn is_transient_mdb(error: &MdbError) -> bool {
+            matches!(error, MdbError::MapResized | MdbError::ReadersFull)
+        }
+
+        match self {
+            IndexResolverError::IndexError(IndexError::Milli(milli::Error::InternalError(
+                InternalError::Store(error),
+            )))
+            | IndexResolverError::Milli(milli::Error::InternalError(InternalError::Store(error))) => {
+                is_transient_mdb(error)
+            }
+            IndexResolverError::IndexError(IndexError::Internal(error))
+            | IndexResolverError::Internal(error) => matches!(
+                error.downcast_ref::<HeedError>(),
+                Some(HeedError::Mdb(error)) if is_transient_mdb(error)
+            ),
+            _ => false,
         }
     }
 }
@@ -32,7 +32,8 @@ pub trait IndexMetaStore: Sized {
     async fn insert(&self, name: String, meta: IndexMeta) -> Result<()>;
     async fn snapshot(&self, path: PathBuf) -> Result<HashSet<Uuid>>;
     async fn get_size(&self) -> Result<u64>;
-    async fn dump(&self, path: PathBuf) -> Result<()>;
+    /// Dumps the metadata of the indexes in `indexes`, or of every index when `None`.
+    async fn dump(&self, path: PathBuf, indexes: Option<Vec<String>>) -> Result<()>;
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -139,7 +140,7 @@ impl HeedMetaStore {
             .fold(0, |acc, m| acc + m.len()))
     }
 
-    pub fn dump(&self, path: PathBuf) -> Result<()> {
+    pub fn dump(&self, path: PathBuf, indexes: Option<Vec<String>>) -> Result<()> {
         let dump_path = path.join(UUIDS_DB_PATH);
         create_dir_all(&dump_path)?;
         let dump_file_path = dump_path.join("data.jsonl");
@@ -148,6 +149,12 @@ impl HeedMetaStore {
         let txn = self.env.read_txn()?;
         for entry in self.db.iter(&txn)? {
             let (uid, index_meta) = entry?;
+            if indexes
+                .as_ref()
+                .map_or(false, |indexes| !indexes.iter().any(|i| i == uid))
+            {
+                continue;
+            }
             let uid = uid.to_string();
 
             let entry = DumpEntry { uid, index_meta };
@@ -158,6 +165,16 @@ impl HeedMetaStore {
         Ok(())
     }
 
+    /// Reads the entries of the indexes dumped in `src`, in the order they were dumped.
+    pub fn read_dump(src: impl AsRef<Path>) -> Result<impl Iterator<Item = Result<DumpEntry>>> {
+        let src_indexes = src.as_ref().join(UUIDS_DB_PATH).join("data.jsonl");
+        let indexes = BufReader::new(File::open(&src_indexes)?);
+
+        Ok(serde_json::Deserializer::from_reader(indexes)
+            .into_iter::<DumpEntry>()
+            .map(|entry| entry.map_err(Into::into)))
+    }
+
     pub fn load_dump(src: impl AsRef<Path>, env: Arc<milli::heed::Env>) -> Result<()> {
         let src_indexes = src.as_ref().join(UUIDS_DB_PATH).join("data.jsonl");
         let indexes = File::open(&src_indexes)?;
@@ -216,8 +233,8 @@ impl IndexMetaStore for HeedMetaStore {
         self.get_size()
     }
 
-    async fn dump(&self, path: PathBuf) -> Result<()> {
+    async fn dump(&self, path: PathBuf, indexes: Option<Vec<String>>) -> Result<()> {
         let this = self.clone();
-        Ok(tokio::task::spawn_blocking(move || this.dump(path)).await??)
+        Ok(tokio::task::spawn_blocking(move || this.dump(path, indexes)).await??)
     }
 }
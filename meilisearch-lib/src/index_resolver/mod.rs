@@ -2,9 +2,11 @@ pub mod error;
 pub mod index_store;
 pub mod meta_store;
 
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use error::{IndexResolverError, Result};
 use index_store::{IndexStore, MapIndexStore};
@@ -17,8 +19,11 @@ use time::OffsetDateTime;
 use tokio::task::spawn_blocking;
 use uuid::Uuid;
 
-use crate::index::{error::Result as IndexResult, Index};
+use crate::dump::Checkpoint;
+use crate::index::error::IndexError;
+use crate::index::{error::Result as IndexResult, Index, Settings, Unchecked};
 use crate::options::IndexerOpts;
+use crate::tasks::batch::MustStopProcessing;
 use crate::tasks::task::{DocumentDeletion, Task, TaskContent, TaskEvent, TaskId, TaskResult};
 use crate::update_file_store::UpdateFileStore;
 
@@ -26,6 +31,12 @@ use self::meta_store::IndexMeta;
 
 pub type HardStateIndexResolver = IndexResolver<HeedMetaStore, MapIndexStore>;
 
+/// How many times opening an index is attempted while processing a task, when it fails because of
+/// a transient error.
+const INDEX_OPEN_ATTEMPTS: u32 = 3;
+/// The delay before the second attempt to open an index, doubled before each following attempt.
+const INDEX_OPEN_BACKOFF: Duration = Duration::from_millis(100);
+
 #[cfg(not(test))]
 pub use real::IndexResolver;
 
@@ -41,7 +52,9 @@ pub fn create_index_resolver(
 ) -> anyhow::Result<HardStateIndexResolver> {
     let uuid_store = HeedMetaStore::new(meta_env)?;
     let index_store = MapIndexStore::new(&path, index_size, indexer_opts)?;
-    Ok(IndexResolver::new(uuid_store, index_store, file_store))
+    let mut index_resolver = IndexResolver::new(uuid_store, index_store, file_store);
+    index_resolver.set_max_total_documents(indexer_opts.max_total_documents);
+    Ok(index_resolver)
 }
 
 mod real {
@@ -51,6 +64,11 @@ mod real {
         pub(super) index_uuid_store: U,
         pub(super) index_store: I,
         pub(super) file_store: UpdateFileStore,
+        /// The maximum number of documents across all indexes, if any.
+        pub(super) max_total_documents: Option<u64>,
+        /// The number of documents of the indexes by uid, as counted to check the quota. The count
+        /// of an index is forgotten as soon as one of its tasks is processed.
+        pub(super) documents_counts: Mutex<HashMap<String, u64>>,
     }
 
     impl IndexResolver<HeedMetaStore, MapIndexStore> {
@@ -60,13 +78,30 @@ mod real {
             index_db_size: usize,
             env: Arc<Env>,
             indexer_opts: &IndexerOpts,
+            checkpoint: &mut Checkpoint,
+            on_index_loaded: &mut dyn FnMut(u64),
         ) -> anyhow::Result<()> {
             HeedMetaStore::load_dump(&src, env)?;
             let indexes_path = src.as_ref().join("indexes");
-            let indexes = indexes_path.read_dir()?;
+            // the indexes are always imported in the same order, whether the import is resumed or not.
+            let mut indexes = indexes_path
+                .read_dir()?
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<std::io::Result<Vec<_>>>()?;
+            indexes.sort();
             let indexer_config = IndexerConfig::try_from(indexer_opts)?;
             for index in indexes {
-                Index::load_dump(&index?.path(), &dst, index_db_size, &indexer_config)?;
+                let name = index.file_name().unwrap_or_default().to_string_lossy();
+                let documents = match checkpoint.imported_index(&name) {
+                    Some(documents) => documents,
+                    None => {
+                        let documents =
+                            Index::load_dump(&index, &dst, index_db_size, &indexer_config)?;
+                        checkpoint.index_imported(name.into_owned(), documents)?;
+                        documents
+                    }
+                };
+                on_index_loaded(documents);
             }
 
             Ok(())
@@ -83,10 +118,74 @@ mod real {
                 index_uuid_store,
                 index_store,
                 file_store,
+                max_total_documents: None,
+                documents_counts: Mutex::default(),
+            }
+        }
+
+        pub fn set_max_total_documents(&mut self, max_total_documents: Option<u64>) {
+            self.max_total_documents = max_total_documents;
+        }
+
+        /// Forgets the number of documents of the index `uid`, so that it is counted again at the
+        /// next quota check. The tasks are processed one batch at a time, so forgetting it before
+        /// processing a task of the index is enough.
+        fn forget_documents_count(&self, uid: &str) {
+            self.documents_counts.lock().unwrap().remove(uid);
+        }
+
+        /// Returns how many of the document additions of `tasks`, taken in order, fit within the
+        /// maximum number of documents across all indexes. The documents of the tasks are counted
+        /// as new documents, even if they end up replacing existing ones.
+        async fn additions_within_quota(&self, tasks: &[Task]) -> Result<usize> {
+            let max_total_documents = match self.max_total_documents {
+                Some(max) => max,
+                None => return Ok(tasks.len()),
+            };
+
+            // only the indexes whose number of documents is unknown are opened.
+            let mut total = 0;
+            for (uid, meta) in self.index_uuid_store.list().await? {
+                let known = self.documents_counts.lock().unwrap().get(&uid).copied();
+                let count = match known {
+                    Some(count) => count,
+                    None => match self.index_store.get(meta.uuid).await? {
+                        Some(index) => {
+                            let count = index.documents_count()?;
+                            self.documents_counts.lock().unwrap().insert(uid, count);
+                            count
+                        }
+                        None => 0,
+                    },
+                };
+                total += count;
+            }
+
+            let mut accepted = 0;
+            for task in tasks {
+                if let TaskContent::DocumentAddition {
+                    documents_count, ..
+                } = task.content
+                {
+                    total += documents_count as u64;
+                }
+                if total > max_total_documents {
+                    break;
+                }
+                accepted += 1;
             }
+
+            Ok(accepted)
         }
 
-        pub async fn process_document_addition_batch(&self, tasks: &mut [Task]) {
+        /// Processes a batch of document additions. If `must_stop` is raised before the documents
+        /// are committed, nothing is indexed: the tasks targeted by a cancelation are canceled and
+        /// the others are enqueued again.
+        pub async fn process_document_addition_batch(
+            &self,
+            tasks: &mut [Task],
+            must_stop: &MustStopProcessing,
+        ) {
             fn get_content_uuid(task: &Task) -> Uuid {
                 match task {
                     Task {
@@ -97,6 +196,35 @@ mod real {
                 }
             }
 
+            // The additions that would exceed the quota fail, the others are still processed.
+            let tasks = match self.additions_within_quota(tasks).await {
+                Ok(accepted) => {
+                    let (accepted, rejected) = tasks.split_at_mut(accepted);
+                    if !rejected.is_empty() {
+                        let max = self.max_total_documents.unwrap_or_default();
+                        let event =
+                            TaskEvent::failed(IndexResolverError::TotalDocumentsQuotaExceeded(max));
+                        for task in rejected {
+                            task.push_event(event.clone());
+                        }
+                    }
+                    accepted
+                }
+                Err(e) => {
+                    let event = TaskEvent::failed(e);
+                    for task in tasks.iter_mut() {
+                        task.push_event(event.clone());
+                    }
+                    return;
+                }
+            };
+            if tasks.is_empty() {
+                return;
+            }
+            if let Some(uid) = tasks[0].index_uid() {
+                self.forget_documents_count(uid);
+            }
+
             let content_uuids = tasks.iter().map(get_content_uuid).collect::<Vec<_>>();
 
             match tasks.first() {
@@ -118,7 +246,8 @@ mod real {
                     let index = if *allow_index_creation {
                         self.get_or_create_index(index_uid.clone(), *id).await
                     } else {
-                        self.get_index(index_uid.as_str().to_string()).await
+                        self.get_index_for_processing(index_uid.as_str().to_string())
+                            .await
                     };
 
                     // If the index doesn't exist and we are not allowed to create it with the first
@@ -129,7 +258,7 @@ mod real {
                         Err(e) => {
                             let error = ResponseError::from(e);
                             for task in tasks.iter_mut() {
-                                task.events.push(TaskEvent::Failed {
+                                task.push_event(TaskEvent::Failed {
                                     error: error.clone(),
                                     timestamp: now,
                                 });
@@ -140,42 +269,72 @@ mod real {
                     };
 
                     let file_store = self.file_store.clone();
-                    let result = spawn_blocking(move || {
-                        index.update_documents(
+                    let flag = must_stop.clone();
+                    let result = spawn_blocking(move || -> IndexResult<_> {
+                        // The primary key is inferred from the documents when neither the index
+                        // nor the tasks define it.
+                        let infers_primary_key =
+                            primary_key.is_none() && index.meta()?.primary_key.is_none();
+                        let results = index.update_documents(
                             method,
                             primary_key,
                             file_store,
                             content_uuids.into_iter(),
-                        )
+                            flag.as_atomic(),
+                        )?;
+                        let inferred_primary_key = if infers_primary_key {
+                            index.meta()?.primary_key
+                        } else {
+                            None
+                        };
+
+                        Ok((results, inferred_primary_key))
                     })
                     .await;
 
                     match result {
-                        Ok(Ok(results)) => {
+                        Ok(Ok((results, inferred_primary_key))) => {
                             for (task, result) in tasks.iter_mut().zip(results) {
                                 let event = match result {
                                     Ok(addition) => {
                                         TaskEvent::succeeded(TaskResult::DocumentAddition {
                                             indexed_documents: addition.indexed_documents,
+                                            inferred_primary_key: inferred_primary_key.clone(),
                                         })
                                     }
                                     Err(error) => {
                                         TaskEvent::failed(IndexResolverError::from(error))
                                     }
                                 };
-                                task.events.push(event);
+                                task.push_event(event);
+                            }
+                        }
+                        Ok(Err(IndexError::AbortedIndexation)) => {
+                            // The reason is filled in when the cancelation is processed.
+                            let timestamp = OffsetDateTime::now_utc();
+                            for task in tasks.iter_mut() {
+                                match must_stop.canceled_by(task.id) {
+                                    Some(canceled_by) => task.push_event(TaskEvent::Canceled {
+                                        canceled_by,
+                                        reason: None,
+                                        timestamp,
+                                    }),
+                                    None => {
+                                        task.requeue();
+                                    }
+                                }
                             }
                         }
                         Ok(Err(e)) => {
                             let event = TaskEvent::failed(e);
                             for task in tasks.iter_mut() {
-                                task.events.push(event.clone());
+                                task.push_event(event.clone());
                             }
                         }
                         Err(e) => {
                             let event = TaskEvent::failed(IndexResolverError::from(e));
                             for task in tasks.iter_mut() {
-                                task.events.push(event.clone());
+                                task.push_event(event.clone());
                             }
                         }
                     }
@@ -184,6 +343,49 @@ mod real {
             }
         }
 
+        /// Processes a batch of settings updates of the same index. The settings of all the tasks
+        /// are merged so the index is only updated once. If the merged settings can't be applied,
+        /// the tasks are processed one by one so that only the faulty ones fail.
+        pub async fn process_settings_update_batch(&self, tasks: &mut [Task]) {
+            let mut settings = Settings::<Unchecked>::default();
+            for task in tasks.iter() {
+                match &task.content {
+                    TaskContent::SettingsUpdate {
+                        settings: update, ..
+                    } => settings = settings.merge(update.clone()),
+                    _ => panic!("unexpected task in the settings update batch"),
+                }
+            }
+
+            // The merged task creates the index the same way the first task would.
+            let mut merged = match tasks.first() {
+                Some(task) => task.clone(),
+                None => return,
+            };
+            if let TaskContent::SettingsUpdate {
+                settings: ref mut merged_settings,
+                ..
+            } = merged.content
+            {
+                *merged_settings = settings;
+            }
+
+            match self.process_task_inner(&merged).await {
+                Ok(result) => {
+                    let event = TaskEvent::succeeded(result);
+                    for task in tasks.iter_mut() {
+                        task.push_event(event.clone());
+                    }
+                }
+                Err(e) if tasks.len() == 1 => tasks[0].push_event(TaskEvent::failed(e)),
+                Err(_) => {
+                    for task in tasks.iter_mut() {
+                        self.process_task(task).await;
+                    }
+                }
+            }
+        }
+
         pub async fn delete_content_file(&self, content_uuid: Uuid) -> Result<()> {
             self.file_store.delete(content_uuid).await?;
             Ok(())
@@ -199,7 +401,9 @@ mod real {
                     index_uid,
                 } => {
                     let ids = ids.clone();
-                    let index = self.get_index(index_uid.clone().into_inner()).await?;
+                    let index = self
+                        .get_index_for_processing(index_uid.clone().into_inner())
+                        .await?;
 
                     let DocumentDeletionResult {
                         deleted_documents, ..
@@ -211,7 +415,9 @@ mod real {
                     deletion: DocumentDeletion::Clear,
                     index_uid,
                 } => {
-                    let index = self.get_index(index_uid.clone().into_inner()).await?;
+                    let index = self
+                        .get_index_for_processing(index_uid.clone().into_inner())
+                        .await?;
                     let deleted_documents = spawn_blocking(move || -> IndexResult<u64> {
                         let number_documents = index.stats()?.number_of_documents;
                         index.clear_documents()?;
@@ -221,6 +427,19 @@ mod real {
 
                     Ok(TaskResult::ClearAll { deleted_documents })
                 }
+                TaskContent::DocumentDeletion {
+                    deletion: DocumentDeletion::Filter(filter),
+                    index_uid,
+                } => {
+                    let filter = filter.clone();
+                    let index = self
+                        .get_index_for_processing(index_uid.clone().into_inner())
+                        .await?;
+                    let deleted_documents =
+                        spawn_blocking(move || index.clear_documents_matching(&filter)).await??;
+
+                    Ok(TaskResult::ClearAll { deleted_documents })
+                }
                 TaskContent::SettingsUpdate {
                     settings,
                     is_deletion,
@@ -228,7 +447,8 @@ mod real {
                     index_uid,
                 } => {
                     let index = if *is_deletion || !*allow_index_creation {
-                        self.get_index(index_uid.clone().into_inner()).await?
+                        self.get_index_for_processing(index_uid.clone().into_inner())
+                            .await?
                     } else {
                         self.get_or_create_index(index_uid.clone(), task.id).await?
                     };
@@ -265,7 +485,9 @@ mod real {
                     primary_key,
                     index_uid,
                 } => {
-                    let index = self.get_index(index_uid.clone().into_inner()).await?;
+                    let index = self
+                        .get_index_for_processing(index_uid.clone().into_inner())
+                        .await?;
 
                     if let Some(primary_key) = primary_key {
                         let primary_key = primary_key.clone();
@@ -274,23 +496,63 @@ mod real {
 
                     Ok(TaskResult::Other)
                 }
+                TaskContent::DocumentsExport {
+                    index_uid,
+                    path,
+                    filter,
+                } => {
+                    let index = self
+                        .get_index_for_processing(index_uid.clone().into_inner())
+                        .await?;
+                    let path = path.clone();
+                    let filter = filter.clone();
+
+                    let exported_documents =
+                        spawn_blocking(move || index.export_documents(path, filter.as_deref()))
+                            .await??;
+
+                    Ok(TaskResult::DocumentsExport { exported_documents })
+                }
                 _ => unreachable!("Invalid task for index resolver"),
             }
         }
 
         pub async fn process_task(&self, task: &mut Task) {
+            if let Some(uid) = task.index_uid() {
+                self.forget_documents_count(uid);
+            }
             match self.process_task_inner(task).await {
-                Ok(res) => task.events.push(TaskEvent::succeeded(res)),
-                Err(e) => task.events.push(TaskEvent::failed(e)),
+                Ok(res) => task.push_event(TaskEvent::succeeded(res)),
+                Err(e) => task.push_event(TaskEvent::failed(e)),
             }
         }
 
-        pub async fn dump(&self, path: impl AsRef<Path>) -> Result<()> {
-            for (_, index) in self.list().await? {
-                index.dump(&path)?;
+        /// Dumps the indexes in `indexes`, or every index when `None`, and returns the number of
+        /// dumped indexes and documents. Fails if one of the given indexes doesn't exist.
+        pub async fn dump(
+            &self,
+            path: impl AsRef<Path>,
+            indexes: Option<Vec<String>>,
+        ) -> Result<(usize, u64)> {
+            let mut dumped = self.list().await?;
+            if let Some(ref indexes) = indexes {
+                if let Some(missing) = indexes
+                    .iter()
+                    .find(|uid| !dumped.iter().any(|(name, _)| name == *uid))
+                {
+                    return Err(IndexResolverError::UnexistingIndex(missing.clone()));
+                }
+                dumped.retain(|(uid, _)| indexes.contains(uid));
             }
-            self.index_uuid_store.dump(path.as_ref().to_owned()).await?;
-            Ok(())
+
+            let mut documents_count = 0;
+            for (_, index) in &dumped {
+                documents_count += index.dump(&path)?;
+            }
+            self.index_uuid_store
+                .dump(path.as_ref().to_owned(), indexes)
+                .await?;
+            Ok((dumped.len(), documents_count))
         }
 
         async fn create_index(&self, uid: IndexUid, creation_task_id: TaskId) -> Result<Index> {
@@ -330,7 +592,9 @@ mod real {
         pub async fn get_or_create_index(&self, uid: IndexUid, task_id: TaskId) -> Result<Index> {
             match self.create_index(uid, task_id).await {
                 Ok(index) => Ok(index),
-                Err(IndexResolverError::IndexAlreadyExists(uid)) => self.get_index(uid).await,
+                Err(IndexResolverError::IndexAlreadyExists(uid)) => {
+                    self.get_index_for_processing(uid).await
+                }
                 Err(e) => Err(e),
             }
         }
@@ -351,6 +615,37 @@ mod real {
             Ok(indexes)
         }
 
+        /// Returns the number of indexes accepted by `filter`, and the `limit` of them that follow
+        /// the first `offset` ones, ordered by uid. Only the indexes of the page are opened.
+        pub async fn list_paginated(
+            &self,
+            filter: impl Fn(&str) -> bool,
+            offset: usize,
+            limit: usize,
+        ) -> Result<(usize, Vec<(String, Index)>)> {
+            let uuids: Vec<_> = self
+                .index_uuid_store
+                .list()
+                .await?
+                .into_iter()
+                .filter(|(name, _)| filter(name))
+                .collect();
+            let total = uuids.len();
+
+            let mut indexes = Vec::new();
+            for (name, IndexMeta { uuid, .. }) in uuids.into_iter().skip(offset).take(limit) {
+                match self.index_store.get(uuid).await? {
+                    Some(index) => indexes.push((name, index)),
+                    None => {
+                        // we found an unexisting index, we remove it from the uuid store
+                        let _ = self.index_uuid_store.delete(name).await;
+                    }
+                }
+            }
+
+            Ok((total, indexes))
+        }
+
         pub async fn delete_index(&self, uid: String) -> Result<Index> {
             match self.index_uuid_store.delete(uid.clone()).await? {
                 Some(IndexMeta { uuid, .. }) => match self.index_store.delete(uuid).await? {
@@ -381,6 +676,39 @@ mod real {
             }
         }
 
+        /// Gets the index `uid` to process a task on it. Unlike `get_index`, opening the index is
+        /// attempted again with an exponential backoff when it fails because of a transient
+        /// error, while a missing index still fails right away.
+        async fn get_index_for_processing(&self, uid: String) -> Result<Index> {
+            let mut backoff = INDEX_OPEN_BACKOFF;
+            let mut attempt = 1;
+            loop {
+                match self.get_index(uid.clone()).await {
+                    Err(e) if e.is_transient() && attempt < INDEX_OPEN_ATTEMPTS => {
+                        log::warn!(
+                            "Failed to open index `{}` (attempt {}/{}): {}. Retrying in {:?}.",
+                            uid,
+                            attempt,
+                            INDEX_OPEN_ATTEMPTS,
+                            e,
+                            backoff
+                        );
+                        tokio::time::sleep(backoff).await;
+                        backoff *= 2;
+                        attempt += 1;
+                    }
+                    result => return result,
+                }
+            }
+        }
+
+        /// Returns whether an index named `uid` exists, only looking at the uid to uuid mapping so
+        /// the environment of the index is never opened.
+        pub async fn index_exists(&self, uid: String) -> Result<bool> {
+            let (_, meta) = self.index_uuid_store.get(uid).await?;
+            Ok(meta.is_some())
+        }
+
         pub async fn get_index_creation_task_id(&self, index_uid: String) -> Result<TaskId> {
             let (uid, meta) = self.index_uuid_store.get(index_uid).await?;
             meta.map(
@@ -401,7 +729,10 @@ mod test {
     use super::meta_store::MockIndexMetaStore;
     use super::*;
 
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
     use futures::future::ok;
+    use milli::update::{DocumentAdditionResult, IndexDocumentsMethod};
     use milli::FieldDistribution;
     use nelson::Mocker;
 
@@ -417,8 +748,18 @@ mod test {
             index_db_size: usize,
             env: Arc<Env>,
             indexer_opts: &IndexerOpts,
+            checkpoint: &mut Checkpoint,
+            on_index_loaded: &mut dyn FnMut(u64),
         ) -> anyhow::Result<()> {
-            super::real::IndexResolver::load_dump(src, dst, index_db_size, env, indexer_opts)
+            super::real::IndexResolver::load_dump(
+                src,
+                dst,
+                index_db_size,
+                env,
+                indexer_opts,
+                checkpoint,
+                on_index_loaded,
+            )
         }
     }
 
@@ -432,22 +773,44 @@ mod test {
                 index_uuid_store,
                 index_store,
                 file_store,
+                max_total_documents: None,
+                documents_counts: Mutex::default(),
             })
         }
 
+        pub fn set_max_total_documents(&mut self, max_total_documents: Option<u64>) {
+            match self {
+                IndexResolver::Real(r) => r.set_max_total_documents(max_total_documents),
+                IndexResolver::Mock(_) => todo!(),
+            }
+        }
+
         pub fn mock(mocker: Mocker) -> Self {
             Self::Mock(mocker)
         }
 
-        pub async fn process_document_addition_batch(&self, tasks: &mut [Task]) {
+        pub async fn process_document_addition_batch(
+            &self,
+            tasks: &mut [Task],
+            must_stop: &MustStopProcessing,
+        ) {
             match self {
-                IndexResolver::Real(r) => r.process_document_addition_batch(tasks).await,
+                IndexResolver::Real(r) => r.process_document_addition_batch(tasks, must_stop).await,
                 IndexResolver::Mock(m) => unsafe {
                     m.get("process_document_addition_batch").call(tasks)
                 },
             }
         }
 
+        pub async fn process_settings_update_batch(&self, tasks: &mut [Task]) {
+            match self {
+                IndexResolver::Real(r) => r.process_settings_update_batch(tasks).await,
+                IndexResolver::Mock(m) => unsafe {
+                    m.get("process_settings_update_batch").call(tasks)
+                },
+            }
+        }
+
         pub async fn process_task(&self, task: &mut Task) {
             match self {
                 IndexResolver::Real(r) => r.process_task(task).await,
@@ -455,9 +818,13 @@ mod test {
             }
         }
 
-        pub async fn dump(&self, path: impl AsRef<Path>) -> Result<()> {
+        pub async fn dump(
+            &self,
+            path: impl AsRef<Path>,
+            indexes: Option<Vec<String>>,
+        ) -> Result<(usize, u64)> {
             match self {
-                IndexResolver::Real(r) => r.dump(path).await,
+                IndexResolver::Real(r) => r.dump(path, indexes).await,
                 IndexResolver::Mock(_) => todo!(),
             }
         }
@@ -477,6 +844,18 @@ mod test {
             }
         }
 
+        pub async fn list_paginated(
+            &self,
+            filter: impl Fn(&str) -> bool,
+            offset: usize,
+            limit: usize,
+        ) -> Result<(usize, Vec<(String, Index)>)> {
+            match self {
+                IndexResolver::Real(r) => r.list_paginated(filter, offset, limit).await,
+                IndexResolver::Mock(_) => todo!(),
+            }
+        }
+
         pub async fn delete_index(&self, uid: String) -> Result<Index> {
             match self {
                 IndexResolver::Real(r) => r.delete_index(uid).await,
@@ -491,6 +870,13 @@ mod test {
             }
         }
 
+        pub async fn index_exists(&self, uid: String) -> Result<bool> {
+            match self {
+                IndexResolver::Real(r) => r.index_exists(uid).await,
+                IndexResolver::Mock(_) => todo!(),
+            }
+        }
+
         pub async fn get_index_creation_task_id(&self, index_uid: String) -> Result<TaskId> {
             match self {
                 IndexResolver::Real(r) => r.get_index_creation_task_id(index_uid).await,
@@ -508,6 +894,160 @@ mod test {
         }
     }
 
+    #[actix_rt::test]
+    async fn test_index_exists() {
+        let mut meta_store = MockIndexMetaStore::new();
+        meta_store.expect_get().times(2).returning(|uid| {
+            let meta = (uid == "test").then(|| IndexMeta {
+                uuid: Uuid::new_v4(),
+                creation_task_id: 1,
+            });
+            Box::pin(ok((uid, meta)))
+        });
+
+        // the index store has no expectation: the indexes must never be opened.
+        let index_store = MockIndexStore::new();
+
+        let mocker = Mocker::default();
+        let file_store = UpdateFileStore::mock(mocker);
+
+        let index_resolver = IndexResolver::new(meta_store, index_store, file_store);
+
+        assert!(index_resolver
+            .index_exists("test".to_string())
+            .await
+            .unwrap());
+        assert!(!index_resolver
+            .index_exists("unexisting".to_string())
+            .await
+            .unwrap());
+    }
+
+    #[actix_rt::test]
+    async fn list_paginated_only_opens_the_page() {
+        let metas: Vec<_> = ["a", "b", "c", "d", "e"]
+            .iter()
+            .map(|uid| {
+                let meta = IndexMeta {
+                    uuid: Uuid::new_v4(),
+                    creation_task_id: 1,
+                };
+                (uid.to_string(), meta)
+            })
+            .collect();
+        let page_uuids = [metas[3].1.uuid, metas[4].1.uuid];
+
+        let mut meta_store = MockIndexMetaStore::new();
+        meta_store
+            .expect_list()
+            .once()
+            .returning(move || Box::pin(ok(metas.clone())));
+
+        let mut index_store = MockIndexStore::new();
+        index_store
+            .expect_get()
+            .times(2)
+            .withf(move |uuid| page_uuids.contains(uuid))
+            .returning(|_| Box::pin(ok(Some(Index::mock(Mocker::default())))));
+
+        let mocker = Mocker::default();
+        let file_store = UpdateFileStore::mock(mocker);
+
+        let index_resolver = IndexResolver::new(meta_store, index_store, file_store);
+
+        // "b" is filtered out, the page skips "a" and "c".
+        let (total, indexes) = index_resolver
+            .list_paginated(|uid| uid != "b", 2, 2)
+            .await
+            .unwrap();
+        assert_eq!(total, 4);
+        let uids: Vec<_> = indexes.iter().map(|(uid, _)| uid.as_str()).collect();
+        assert_eq!(uids, ["d", "e"]);
+    }
+
+    #[actix_rt::test]
+    async fn retry_opening_index_on_transient_error() {
+        let mut meta_store = MockIndexMetaStore::new();
+        meta_store.expect_get().times(2).returning(|uid| {
+            Box::pin(ok((
+                uid,
+                Some(IndexMeta {
+                    uuid: Uuid::new_v4(),
+                    creation_task_id: 1,
+                }),
+            )))
+        });
+
+        let attempts = AtomicUsize::new(0);
+        let mut index_store = MockIndexStore::new();
+        index_store.expect_get().times(2).returning(move |_| {
+            if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                let error = milli::InternalError::Store(milli::heed::MdbError::MapResized);
+                let error = IndexResolverError::Milli(error.into());
+                Box::pin(futures::future::err(error))
+            } else {
+                Box::pin(ok(Some(Index::mock(Mocker::default()))))
+            }
+        });
+
+        let mocker = Mocker::default();
+        let file_store = UpdateFileStore::mock(mocker);
+
+        let index_resolver = IndexResolver::new(meta_store, index_store, file_store);
+
+        let mut task = Task {
+            id: 1,
+            content: TaskContent::IndexUpdate {
+                primary_key: None,
+                index_uid: IndexUid::new_unchecked("test"),
+            },
+            events: Vec::new(),
+            retry_of: None,
+            priority: 0,
+        };
+
+        index_resolver.process_task(&mut task).await;
+
+        assert!(matches!(task.events[0], TaskEvent::Succeeded { .. }));
+    }
+
+    #[actix_rt::test]
+    async fn never_retry_opening_unexisting_index() {
+        let mut meta_store = MockIndexMetaStore::new();
+        meta_store
+            .expect_get()
+            .once()
+            .returning(|uid| Box::pin(ok((uid, None))));
+
+        let index_store = MockIndexStore::new();
+
+        let mocker = Mocker::default();
+        let file_store = UpdateFileStore::mock(mocker);
+
+        let index_resolver = IndexResolver::new(meta_store, index_store, file_store);
+
+        let mut task = Task {
+            id: 1,
+            content: TaskContent::IndexUpdate {
+                primary_key: None,
+                index_uid: IndexUid::new_unchecked("test"),
+            },
+            events: Vec::new(),
+            retry_of: None,
+            priority: 0,
+        };
+
+        index_resolver.process_task(&mut task).await;
+
+        match &task.events[0] {
+            TaskEvent::Failed { error, .. } => assert_eq!(
+                *error,
+                ResponseError::from(IndexResolverError::UnexistingIndex("test".to_string()))
+            ),
+            event => panic!("unexpected event: {:?}", event),
+        }
+    }
+
     #[actix_rt::test]
     async fn test_remove_unknown_index() {
         let mut meta_store = MockIndexMetaStore::new();
@@ -529,6 +1069,8 @@ mod test {
                 index_uid: IndexUid::new_unchecked("test"),
             },
             events: Vec::new(),
+            retry_of: None,
+            priority: 0,
         };
 
         index_resolver.process_task(&mut task).await;
@@ -554,7 +1096,7 @@ mod test {
                 .when::<(), IndexResult<IndexStats>>("stats")
                 .then(|_| {
                     Ok(IndexStats {
-                        size: 10,
+                        database_size: 10,
                         number_of_documents: 10,
                         is_indexing: None,
                         field_distribution: FieldDistribution::default(),
@@ -574,6 +1116,8 @@ mod test {
                 index_uid: IndexUid::new_unchecked("test"),
             },
             events: Vec::new(),
+            retry_of: None,
+            priority: 0,
         };
 
         index_resolver.process_task(&mut task).await;
@@ -606,7 +1150,7 @@ mod test {
                 .once()
                 .then(|_| {
                     Ok(IndexStats {
-                        size: 10,
+                        database_size: 10,
                         number_of_documents: 10,
                         is_indexing: None,
                         field_distribution: FieldDistribution::default(),
@@ -627,6 +1171,8 @@ mod test {
                 index_uid: IndexUid::new_unchecked("test"),
             },
             events: Vec::new(),
+            retry_of: None,
+            priority: 0,
         };
 
         index_resolver.process_task(&mut task).await;
@@ -676,10 +1222,184 @@ mod test {
                 index_uid: IndexUid::new_unchecked("test"),
             },
             events: Vec::new(),
+            retry_of: None,
+            priority: 0,
         };
 
         index_resolver.process_task(&mut task).await;
 
         assert!(matches!(task.events[0], TaskEvent::Succeeded { .. }));
     }
+
+    #[actix_rt::test]
+    async fn additions_over_the_documents_quota_fail() {
+        let meta = IndexMeta {
+            uuid: Uuid::new_v4(),
+            creation_task_id: 1,
+        };
+        let other_meta = IndexMeta {
+            uuid: Uuid::new_v4(),
+            creation_task_id: 2,
+        };
+        let mut meta_store = MockIndexMetaStore::new();
+        let listed = vec![
+            ("test".to_string(), meta.clone()),
+            ("other".to_string(), other_meta.clone()),
+        ];
+        meta_store
+            .expect_list()
+            .times(2)
+            .returning(move || Box::pin(ok(listed.clone())));
+        let test_uuid = meta.uuid;
+        meta_store
+            .expect_get()
+            .times(2)
+            .returning(move |uid| Box::pin(ok((uid, Some(meta.clone())))));
+
+        let gen_index = |documents_count| {
+            let mocker = Mocker::default();
+            mocker
+                .when::<(), IndexResult<u64>>("documents_count")
+                .then(move |_| Ok(documents_count));
+            mocker
+                .when::<(
+                    IndexDocumentsMethod,
+                    Option<String>,
+                    UpdateFileStore,
+                    std::vec::IntoIter<Uuid>,
+                ), IndexResult<Vec<IndexResult<DocumentAdditionResult>>>>(
+                    "update_documents"
+                )
+                .then(|_| Err(IndexError::AbortedIndexation));
+            Index::mock(mocker)
+        };
+        // the number of documents of an index is only read again once one of its tasks was
+        // processed.
+        let mut index_store = MockIndexStore::new();
+        index_store
+            .expect_get()
+            .with(mockall::predicate::eq(test_uuid))
+            .times(4)
+            .returning(move |_| Box::pin(ok(Some(gen_index(10)))));
+        index_store
+            .expect_get()
+            .with(mockall::predicate::eq(other_meta.uuid))
+            .once()
+            .returning(move |_| Box::pin(ok(Some(gen_index(5)))));
+
+        let mocker = Mocker::default();
+        let file_store = UpdateFileStore::mock(mocker);
+
+        let mut index_resolver = IndexResolver::new(meta_store, index_store, file_store);
+        index_resolver.set_max_total_documents(Some(25));
+
+        let now = OffsetDateTime::now_utc();
+        let gen_task = |id| Task {
+            id,
+            content: TaskContent::DocumentAddition {
+                content_uuid: Uuid::new_v4(),
+                merge_strategy: IndexDocumentsMethod::ReplaceDocuments,
+                primary_key: Some("id".to_string()),
+                documents_count: 10,
+                allow_index_creation: false,
+                index_uid: IndexUid::new_unchecked("test"),
+            },
+            events: vec![TaskEvent::Created(now)],
+            retry_of: None,
+            priority: 0,
+        };
+
+        for _ in 0..2 {
+            let mut tasks = vec![gen_task(0), gen_task(1)];
+            index_resolver
+                .process_document_addition_batch(&mut tasks, &MustStopProcessing::default())
+                .await;
+
+            // the first addition still fits, and reached the index, whose indexation was aborted.
+            assert_eq!(tasks[0].events, vec![TaskEvent::Created(now)]);
+            match tasks[1].events.last() {
+                Some(TaskEvent::Failed { error, .. }) => assert_eq!(
+                    *error,
+                    ResponseError::from(IndexResolverError::TotalDocumentsQuotaExceeded(25))
+                ),
+                event => panic!("unexpected event: {:?}", event),
+            }
+        }
+    }
+
+    #[actix_rt::test]
+    async fn aborted_addition_batch_only_cancels_the_targeted_tasks() {
+        let mut meta_store = MockIndexMetaStore::new();
+        meta_store.expect_get().once().returning(|_| {
+            Box::pin(ok((
+                "test".to_string(),
+                Some(IndexMeta {
+                    uuid: Uuid::new_v4(),
+                    creation_task_id: 1,
+                }),
+            )))
+        });
+
+        let mut index_store = MockIndexStore::new();
+        index_store.expect_get().once().returning(|_| {
+            let mocker = Mocker::default();
+            mocker
+                .when::<(
+                    IndexDocumentsMethod,
+                    Option<String>,
+                    UpdateFileStore,
+                    std::vec::IntoIter<Uuid>,
+                ), IndexResult<Vec<IndexResult<DocumentAdditionResult>>>>(
+                    "update_documents"
+                )
+                .once()
+                .then(|_| Err(IndexError::AbortedIndexation));
+            Box::pin(ok(Some(Index::mock(mocker))))
+        });
+
+        let mocker = Mocker::default();
+        let file_store = UpdateFileStore::mock(mocker);
+
+        let index_resolver = IndexResolver::new(meta_store, index_store, file_store);
+
+        let now = OffsetDateTime::now_utc();
+        let gen_task = |id| Task {
+            id,
+            content: TaskContent::DocumentAddition {
+                content_uuid: Uuid::new_v4(),
+                merge_strategy: IndexDocumentsMethod::ReplaceDocuments,
+                primary_key: Some("id".to_string()),
+                documents_count: 10,
+                allow_index_creation: false,
+                index_uid: IndexUid::new_unchecked("test"),
+            },
+            events: vec![
+                TaskEvent::Created(now),
+                TaskEvent::Batched {
+                    batch_id: 0,
+                    timestamp: now,
+                },
+                TaskEvent::Processing(now),
+            ],
+            retry_of: None,
+            priority: 0,
+        };
+        let mut tasks = vec![gen_task(0), gen_task(1), gen_task(2)];
+
+        let must_stop = MustStopProcessing::default();
+        must_stop.must_stop(3, [1]);
+
+        index_resolver
+            .process_document_addition_batch(&mut tasks, &must_stop)
+            .await;
+
+        assert!(matches!(
+            tasks[1].events.last(),
+            Some(TaskEvent::Canceled { canceled_by: 3, .. })
+        ));
+        // the tasks that weren't targeted are enqueued again.
+        for task in [&tasks[0], &tasks[2]] {
+            assert_eq!(task.events, vec![TaskEvent::Created(now)]);
+        }
+    }
 }
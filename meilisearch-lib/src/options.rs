@@ -5,14 +5,22 @@ use std::{convert::TryFrom, num::ParseIntError, ops::Deref, str::FromStr};
 
 use byte_unit::{Byte, ByteError};
 use clap::Parser;
+use milli::heed::{flags::Flags, EnvOpenOptions};
 use milli::update::IndexerConfig;
 use serde::{Deserialize, Serialize};
 use sysinfo::{RefreshKind, System, SystemExt};
 
 const MEILI_MAX_INDEXING_MEMORY: &str = "MEILI_MAX_INDEXING_MEMORY";
 const MEILI_MAX_INDEXING_THREADS: &str = "MEILI_MAX_INDEXING_THREADS";
+const MEILI_MAX_TOTAL_DOCUMENTS: &str = "MEILI_MAX_TOTAL_DOCUMENTS";
+const MEILI_MAX_FIELDS_PER_DOCUMENT: &str = "MEILI_MAX_FIELDS_PER_DOCUMENT";
 const DISABLE_AUTO_BATCHING: &str = "DISABLE_AUTO_BATCHING";
+const MEILI_DEDUPLICATE_DOCUMENT_ADDITIONS: &str = "MEILI_DEDUPLICATE_DOCUMENT_ADDITIONS";
+const MEILI_MAX_TASKS_LIMIT: &str = "MEILI_MAX_TASKS_LIMIT";
+const MEILI_TASKS_SYNC_MODE: &str = "MEILI_TASKS_SYNC_MODE";
 const DEFAULT_LOG_EVERY_N: usize = 100000;
+const DEFAULT_MAX_FIELDS_PER_DOCUMENT: usize = 10_000;
+const DEFAULT_MAX_TASKS_LIMIT: usize = 1000;
 
 #[derive(Debug, Clone, Parser, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case", deny_unknown_fields)]
@@ -40,15 +48,47 @@ pub struct IndexerOpts {
     #[clap(long, env = MEILI_MAX_INDEXING_THREADS, default_value_t)]
     #[serde(default)]
     pub max_indexing_threads: MaxThreads,
+
+    /// Sets the maximum number of documents across all indexes. A document addition that would
+    /// exceed it fails before being processed. Deletions are always allowed.
+    #[clap(long, env = MEILI_MAX_TOTAL_DOCUMENTS)]
+    #[serde(default)]
+    pub max_total_documents: Option<u64>,
+
+    /// Sets the maximum number of fields a document can have. A payload containing a document with
+    /// more fields is rejected.
+    #[clap(long, env = MEILI_MAX_FIELDS_PER_DOCUMENT, default_value_t = default_max_fields_per_document())]
+    #[serde(default = "default_max_fields_per_document")]
+    pub max_fields_per_document: usize,
 }
 
-#[derive(Debug, Clone, Parser, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Parser, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case", deny_unknown_fields)]
 pub struct SchedulerConfig {
     /// Deactivates auto-batching when provided.
     #[clap(long, env = DISABLE_AUTO_BATCHING)]
     #[serde(default)]
     pub disable_auto_batching: bool,
+
+    /// Returns the already enqueued task instead of registering a new one when a document addition
+    /// has the exact same content and parameters.
+    #[clap(long, env = MEILI_DEDUPLICATE_DOCUMENT_ADDITIONS)]
+    #[serde(default)]
+    pub deduplicate_document_additions: bool,
+
+    /// Sets the maximum number of tasks that can be returned at once. A larger `limit` is lowered
+    /// to this value.
+    #[clap(long, env = MEILI_MAX_TASKS_LIMIT, default_value_t = default_max_tasks_limit())]
+    #[serde(default = "default_max_tasks_limit")]
+    pub max_tasks_limit: usize,
+
+    /// Sets whether every commit of the tasks database waits for the disk (`always`), or lets the
+    /// operating system flush it periodically (`periodic`), which is faster but can lose the
+    /// last registered or processed tasks on a power failure. The commits of the indexes always
+    /// wait for the disk.
+    #[clap(long, env = MEILI_TASKS_SYNC_MODE, default_value_t)]
+    #[serde(default)]
+    pub tasks_sync_mode: SyncMode,
 }
 
 impl IndexerOpts {
@@ -57,6 +97,8 @@ impl IndexerOpts {
         let IndexerOpts {
             max_indexing_memory,
             max_indexing_threads,
+            max_total_documents,
+            max_fields_per_document,
             log_every_n: _,
             max_nb_chunks: _,
         } = self;
@@ -70,6 +112,16 @@ impl IndexerOpts {
             MEILI_MAX_INDEXING_THREADS,
             max_indexing_threads.0.to_string(),
         );
+        if let Some(max_total_documents) = max_total_documents {
+            export_to_env_if_not_present(
+                MEILI_MAX_TOTAL_DOCUMENTS,
+                max_total_documents.to_string(),
+            );
+        }
+        export_to_env_if_not_present(
+            MEILI_MAX_FIELDS_PER_DOCUMENT,
+            max_fields_per_document.to_string(),
+        );
     }
 }
 
@@ -99,6 +151,19 @@ impl Default for IndexerOpts {
             max_nb_chunks: None,
             max_indexing_memory: MaxMemory::default(),
             max_indexing_threads: MaxThreads::default(),
+            max_total_documents: None,
+            max_fields_per_document: DEFAULT_MAX_FIELDS_PER_DOCUMENT,
+        }
+    }
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            disable_auto_batching: false,
+            deduplicate_document_additions: false,
+            max_tasks_limit: DEFAULT_MAX_TASKS_LIMIT,
+            tasks_sync_mode: SyncMode::default(),
         }
     }
 }
@@ -107,8 +172,17 @@ impl SchedulerConfig {
     pub fn export_to_env(self) {
         let SchedulerConfig {
             disable_auto_batching,
+            deduplicate_document_additions,
+            max_tasks_limit,
+            tasks_sync_mode,
         } = self;
         export_to_env_if_not_present(DISABLE_AUTO_BATCHING, disable_auto_batching.to_string());
+        export_to_env_if_not_present(
+            MEILI_DEDUPLICATE_DOCUMENT_ADDITIONS,
+            deduplicate_document_additions.to_string(),
+        );
+        export_to_env_if_not_present(MEILI_MAX_TASKS_LIMIT, max_tasks_limit.to_string());
+        export_to_env_if_not_present(MEILI_TASKS_SYNC_MODE, tasks_sync_mode.to_string());
     }
 }
 
@@ -157,6 +231,152 @@ impl MaxMemory {
     }
 }
 
+/// How the archives of the dumps are compressed. It's written as `gzip`, `zstd`, or `zstd:<level>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum CompressionLevel {
+    /// gzip at its default level, the only compression of the archives before zstd.
+    Gzip,
+    /// zstd at the given level, from 1, the fastest, to 22, the smallest.
+    Zstd(i32),
+}
+
+impl CompressionLevel {
+    pub const DEFAULT_ZSTD_LEVEL: i32 = 3;
+}
+
+impl Default for CompressionLevel {
+    fn default() -> Self {
+        Self::Gzip
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid compression `{0}`, expected `gzip`, `zstd`, or `zstd:<level>` with a level between 1 and 22.")]
+pub struct CompressionLevelError(String);
+
+impl FromStr for CompressionLevel {
+    type Err = CompressionLevelError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || CompressionLevelError(s.to_string());
+        match s.trim() {
+            "gzip" => Ok(Self::Gzip),
+            "zstd" => Ok(Self::Zstd(Self::DEFAULT_ZSTD_LEVEL)),
+            other => {
+                let level = other
+                    .strip_prefix("zstd:")
+                    .ok_or_else(invalid)?
+                    .parse()
+                    .map_err(|_| invalid())?;
+                if (1..=22).contains(&level) {
+                    Ok(Self::Zstd(level))
+                } else {
+                    Err(invalid())
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for CompressionLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Gzip => f.write_str("gzip"),
+            Self::Zstd(level) => write!(f, "zstd:{}", level),
+        }
+    }
+}
+
+impl TryFrom<String> for CompressionLevel {
+    type Error = CompressionLevelError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<CompressionLevel> for String {
+    fn from(level: CompressionLevel) -> Self {
+        level.to_string()
+    }
+}
+
+/// When the commits of the tasks database, the environment opened by `open_meta_env`, are
+/// flushed to disk. It's written as `always` or `periodic`. The environments of the indexes are
+/// not affected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum SyncMode {
+    /// Every commit waits for an fsync, so a task is never lost once its registration or its
+    /// processing returned.
+    Always,
+    /// Commits return as soon as their data is handed to the operating system, which flushes it
+    /// periodically. It's faster, but a crash of the operating system or a power failure can undo
+    /// the last commits. The database stays consistent, and a crash of Meilisearch alone never
+    /// loses anything.
+    Periodic,
+}
+
+impl SyncMode {
+    /// Sets the LMDB flags of this mode on the options of an environment. An environment must
+    /// always be opened with the same mode, since heed refuses to open it again with other flags.
+    pub fn apply(self, options: &mut EnvOpenOptions) {
+        if let Self::Periodic = self {
+            // Safety: `MDB_NOSYNC` only changes when the commits reach the disk, the environment
+            // is never shared with another process that would expect them to be synced.
+            unsafe {
+                options.flag(Flags::MdbNoSync);
+            }
+        }
+    }
+}
+
+impl Default for SyncMode {
+    fn default() -> Self {
+        Self::Always
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid sync mode `{0}`, expected `always` or `periodic`.")]
+pub struct SyncModeError(String);
+
+impl FromStr for SyncMode {
+    type Err = SyncModeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "always" => Ok(Self::Always),
+            "periodic" => Ok(Self::Periodic),
+            other => Err(SyncModeError(other.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for SyncMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Always => f.write_str("always"),
+            Self::Periodic => f.write_str("periodic"),
+        }
+    }
+}
+
+impl TryFrom<String> for SyncMode {
+    type Error = SyncModeError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<SyncMode> for String {
+    fn from(mode: SyncMode) -> Self {
+        mode.to_string()
+    }
+}
+
 /// Returns the total amount of bytes available or `None` if this system isn't supported.
 fn total_memory_bytes() -> Option<u64> {
     if System::IS_SUPPORTED {
@@ -203,3 +423,11 @@ impl Deref for MaxThreads {
 fn default_log_every_n() -> usize {
     DEFAULT_LOG_EVERY_N
 }
+
+fn default_max_tasks_limit() -> usize {
+    DEFAULT_MAX_TASKS_LIMIT
+}
+
+fn default_max_fields_per_document() -> usize {
+    DEFAULT_MAX_FIELDS_PER_DOCUMENT
+}
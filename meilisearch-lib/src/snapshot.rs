@@ -15,6 +15,7 @@ use walkdir::WalkDir;
 use crate::compression::from_tar_gz;
 use crate::index_controller::open_meta_env;
 use crate::index_controller::versioning::VERSION_FILE_NAME;
+use crate::options::SyncMode;
 use crate::tasks::Scheduler;
 
 pub struct SnapshotService {
@@ -23,6 +24,7 @@ pub struct SnapshotService {
     pub(crate) snapshot_path: PathBuf,
     pub(crate) index_size: usize,
     pub(crate) meta_env_size: usize,
+    pub(crate) sync_mode: SyncMode,
     pub(crate) scheduler: Arc<RwLock<Scheduler>>,
 }
 
@@ -37,6 +39,7 @@ impl SnapshotService {
                 dest_path: self.snapshot_path.clone(),
                 src_path: self.db_path.clone(),
                 meta_env_size: self.meta_env_size,
+                sync_mode: self.sync_mode,
                 index_size: self.index_size,
             };
             self.scheduler.write().await.schedule_snapshot(snapshot_job);
@@ -80,11 +83,12 @@ pub fn load_snapshot(
 
 #[derive(Debug)]
 pub struct SnapshotJob {
-    dest_path: PathBuf,
-    src_path: PathBuf,
+    pub(crate) dest_path: PathBuf,
+    pub(crate) src_path: PathBuf,
 
-    meta_env_size: usize,
-    index_size: usize,
+    pub(crate) meta_env_size: usize,
+    pub(crate) sync_mode: SyncMode,
+    pub(crate) index_size: usize,
 }
 
 impl SnapshotJob {
@@ -145,7 +149,7 @@ impl SnapshotJob {
     }
 
     fn snapshot_meta_env(&self, path: &Path) -> anyhow::Result<()> {
-        let env = open_meta_env(&self.src_path, self.meta_env_size)?;
+        let env = open_meta_env(&self.src_path, self.meta_env_size, self.sync_mode)?;
 
         let dst = path.join("data.mdb");
         env.copy_to_path(dst, milli::heed::CompactionOption::Enabled)?;
@@ -1,14 +1,20 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
 use time::OffsetDateTime;
 
 use crate::snapshot::SnapshotJob;
 
-use super::task::{Task, TaskEvent};
+use super::task::{Task, TaskEvent, TaskId};
 
 pub type BatchId = u32;
 
 #[derive(Debug)]
 pub enum BatchContent {
     DocumentsAdditionBatch(Vec<Task>),
+    /// Settings updates of the same index, applied at once.
+    SettingsUpdates(Vec<Task>),
     IndexUpdate(Task),
     Dump(Task),
     Snapshot(SnapshotJob),
@@ -19,23 +25,73 @@ pub enum BatchContent {
 impl BatchContent {
     pub fn first(&self) -> Option<&Task> {
         match self {
-            BatchContent::DocumentsAdditionBatch(ts) => ts.first(),
+            BatchContent::DocumentsAdditionBatch(ts) | BatchContent::SettingsUpdates(ts) => {
+                ts.first()
+            }
             BatchContent::Dump(t) | BatchContent::IndexUpdate(t) => Some(t),
             BatchContent::Snapshot(_) | BatchContent::Empty => None,
         }
     }
 
+    /// Returns the ids of the tasks contained in the batch.
+    pub fn task_ids(&self) -> Vec<TaskId> {
+        match self {
+            BatchContent::DocumentsAdditionBatch(ts) | BatchContent::SettingsUpdates(ts) => {
+                ts.iter().map(|t| t.id).collect()
+            }
+            BatchContent::Dump(t) | BatchContent::IndexUpdate(t) => vec![t.id],
+            BatchContent::Snapshot(_) | BatchContent::Empty => Vec::new(),
+        }
+    }
+
     pub fn push_event(&mut self, event: TaskEvent) {
         match self {
-            BatchContent::DocumentsAdditionBatch(ts) => {
-                ts.iter_mut().for_each(|t| t.events.push(event.clone()))
+            BatchContent::DocumentsAdditionBatch(ts) | BatchContent::SettingsUpdates(ts) => {
+                ts.iter_mut().for_each(|t| t.push_event(event.clone()))
             }
-            BatchContent::IndexUpdate(t) | BatchContent::Dump(t) => t.events.push(event),
+            BatchContent::IndexUpdate(t) | BatchContent::Dump(t) => t.push_event(event),
             BatchContent::Snapshot(_) | BatchContent::Empty => (),
         }
     }
 }
 
+/// Flag shared between the scheduler and the handler processing a batch, raised when the tasks
+/// of the batch are canceled. Handlers are expected to check it regularly and to stop as soon as
+/// possible once it is raised.
+#[derive(Debug, Clone, Default)]
+pub struct MustStopProcessing {
+    must_stop: Arc<AtomicBool>,
+    /// The tasks of the batch that were canceled, with the id of the task that canceled them.
+    canceled: Arc<Mutex<HashMap<TaskId, TaskId>>>,
+}
+
+impl MustStopProcessing {
+    pub fn get(&self) -> bool {
+        self.must_stop.load(Ordering::Acquire)
+    }
+
+    pub fn as_atomic(&self) -> &AtomicBool {
+        &self.must_stop
+    }
+
+    /// Returns the id of the task that canceled the task `id`, or `None` if `id` wasn't targeted
+    /// by any cancelation. The other tasks of an interrupted batch must be processed again.
+    pub fn canceled_by(&self, id: TaskId) -> Option<TaskId> {
+        self.canceled.lock().unwrap().get(&id).copied()
+    }
+
+    /// Records that the `tasks` of the batch were canceled by the task `canceled_by`, and raises
+    /// the flag.
+    pub fn must_stop(&self, canceled_by: TaskId, tasks: impl IntoIterator<Item = TaskId>) {
+        let mut canceled = self.canceled.lock().unwrap();
+        for id in tasks {
+            canceled.entry(id).or_insert(canceled_by);
+        }
+        drop(canceled);
+        self.must_stop.store(true, Ordering::Release);
+    }
+}
+
 #[derive(Debug)]
 pub struct Batch {
     // Only batches that contains a persistent tasks are given an id. Snapshot batches don't have
@@ -43,6 +99,7 @@ pub struct Batch {
     pub id: Option<BatchId>,
     pub created_at: OffsetDateTime,
     pub content: BatchContent,
+    pub must_stop: MustStopProcessing,
 }
 
 impl Batch {
@@ -51,11 +108,13 @@ impl Batch {
             id,
             created_at: OffsetDateTime::now_utc(),
             content,
+            must_stop: MustStopProcessing::default(),
         }
     }
     pub fn len(&self) -> usize {
         match self.content {
-            BatchContent::DocumentsAdditionBatch(ref ts) => ts.len(),
+            BatchContent::DocumentsAdditionBatch(ref ts)
+            | BatchContent::SettingsUpdates(ref ts) => ts.len(),
             BatchContent::IndexUpdate(_) | BatchContent::Dump(_) | BatchContent::Snapshot(_) => 1,
             BatchContent::Empty => 0,
         }
@@ -70,6 +129,7 @@ impl Batch {
             id: None,
             created_at: OffsetDateTime::now_utc(),
             content: BatchContent::Empty,
+            must_stop: MustStopProcessing::default(),
         }
     }
 }
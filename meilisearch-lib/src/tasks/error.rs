@@ -12,13 +12,18 @@ pub type Result<T> = std::result::Result<T, TaskError>;
 pub enum TaskError {
     #[error("Task `{0}` not found.")]
     UnexistingTask(TaskId),
+    #[error("The task database reached its maximum size. Increase it with the `--max-task-db-size` option.")]
+    TaskDatabaseFull,
+    #[error("The task list is malformed: {0}.")]
+    MalformedTaskList(serde_json::Error),
+    #[error("Task `{0}` can't be imported: its uid must be unique and greater than the uid of every existing task.")]
+    TaskUidAlreadyUsed(TaskId),
     #[error("Internal error: {0}")]
     Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
 }
 
 internal_error!(
-    TaskError: milli::heed::Error,
-    JoinError,
+    TaskError: JoinError,
     std::io::Error,
     serde_json::Error,
     UpdateFileStoreError
@@ -28,7 +33,19 @@ impl ErrorCode for TaskError {
     fn error_code(&self) -> Code {
         match self {
             TaskError::UnexistingTask(_) => Code::TaskNotFound,
+            TaskError::TaskDatabaseFull => Code::DatabaseSizeLimitReached,
+            TaskError::MalformedTaskList(_) => Code::MalformedPayload,
+            TaskError::TaskUidAlreadyUsed(_) => Code::BadRequest,
             TaskError::Internal(_) => Code::Internal,
         }
     }
 }
+
+impl From<milli::heed::Error> for TaskError {
+    fn from(error: milli::heed::Error) -> Self {
+        match error {
+            milli::heed::Error::Mdb(milli::heed::MdbError::MapFull) => Self::TaskDatabaseFull,
+            error => Self::Internal(Box::new(error)),
+        }
+    }
+}
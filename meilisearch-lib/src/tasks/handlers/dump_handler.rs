@@ -18,14 +18,17 @@ where
     async fn process_batch(&self, mut batch: Batch) -> Batch {
         match &batch.content {
             BatchContent::Dump(Task {
-                content: TaskContent::Dump { uid },
+                content: TaskContent::Dump { uid, indexes },
                 ..
             }) => {
-                match self.run(uid.clone()).await {
-                    Ok(_) => {
+                match self.run(uid.clone(), indexes.clone()).await {
+                    Ok((index_count, total_documents)) => {
                         batch
                             .content
-                            .push_event(TaskEvent::succeeded(TaskResult::Other));
+                            .push_event(TaskEvent::succeeded(TaskResult::Dump {
+                                index_count: index_count as u64,
+                                total_documents,
+                            }));
                     }
                     Err(e) => batch.content.push_event(TaskEvent::failed(e)),
                 }
@@ -78,9 +81,9 @@ mod test {
 
                 let mocker = Mocker::default();
                 if should_accept {
-                    mocker.when::<String, DumpResult<()>>("run")
+                    mocker.when::<(String, Option<Vec<String>>), DumpResult<(usize, u64)>>("run")
                     .once()
-                    .then(|_| Ok(()));
+                    .then(|_| Ok((1, 0)));
                 }
 
                 let dump_handler = DumpHandler::<MockIndexMetaStore, MockIndexStore>::mock(mocker);
@@ -109,7 +112,7 @@ mod test {
 
                 let mocker = Mocker::default();
                 if should_accept {
-                    mocker.when::<String, DumpResult<()>>("run")
+                    mocker.when::<(String, Option<Vec<String>>), DumpResult<(usize, u64)>>("run")
                     .once()
                     .then(|_| Err(DumpError::Internal("error".into())));
                 }
@@ -12,14 +12,20 @@ where
     fn accept(&self, batch: &Batch) -> bool {
         matches!(
             batch.content,
-            BatchContent::DocumentsAdditionBatch(_) | BatchContent::IndexUpdate(_)
+            BatchContent::DocumentsAdditionBatch(_)
+                | BatchContent::SettingsUpdates(_)
+                | BatchContent::IndexUpdate(_)
         )
     }
 
     async fn process_batch(&self, mut batch: Batch) -> Batch {
         match batch.content {
             BatchContent::DocumentsAdditionBatch(ref mut tasks) => {
-                self.process_document_addition_batch(tasks).await;
+                self.process_document_addition_batch(tasks, &batch.must_stop)
+                    .await;
+            }
+            BatchContent::SettingsUpdates(ref mut tasks) => {
+                self.process_settings_update_batch(tasks).await;
             }
             BatchContent::IndexUpdate(ref mut task) => {
                 self.process_task(task).await;
@@ -32,7 +38,8 @@ where
 
     async fn finish(&self, batch: &Batch) {
         if let BatchContent::DocumentsAdditionBatch(ref tasks) = batch.content {
-            for task in tasks {
+            // The tasks that are not finished were enqueued again, and still need their file.
+            for task in tasks.iter().filter(|task| task.is_finished()) {
                 if let Some(content_uuid) = task.get_content_uuid() {
                     if let Err(e) = self.delete_content_file(content_uuid).await {
                         log::error!("error deleting update file: {}", e);
@@ -52,7 +59,7 @@ mod test {
     };
     use crate::tasks::{
         handlers::test::task_to_batch,
-        task::{Task, TaskContent},
+        task::{Task, TaskContent, TaskEvent, TaskResult},
     };
     use crate::update_file_store::{Result as FileStoreResult, UpdateFileStore};
 
@@ -61,6 +68,7 @@ mod test {
     use milli::update::IndexDocumentsMethod;
     use nelson::Mocker;
     use proptest::prelude::*;
+    use time::OffsetDateTime;
     use uuid::Uuid;
 
     proptest! {
@@ -78,6 +86,7 @@ mod test {
 
             match batch.content {
                 BatchContent::DocumentsAdditionBatch(_)
+                    | BatchContent::SettingsUpdates(_)
                     | BatchContent::IndexUpdate(_) => assert!(index_resolver.accept(&batch)),
                 BatchContent::Dump(_)
                     | BatchContent::Snapshot(_)
@@ -112,7 +121,9 @@ mod test {
                 allow_index_creation: true,
                 index_uid: IndexUid::new_unchecked("test"),
             },
-            events: Vec::new(),
+            events: vec![TaskEvent::succeeded(TaskResult::Other)],
+            retry_of: None,
+            priority: 0,
         };
 
         let batch = task_to_batch(task);
@@ -120,6 +131,52 @@ mod test {
         index_resolver.finish(&batch).await;
     }
 
+    #[actix_rt::test]
+    async fn finisher_keeps_the_files_of_requeued_additions() {
+        let index_store = MockIndexStore::new();
+        let meta_store = MockIndexMetaStore::new();
+        let mocker = Mocker::default();
+        let canceled_uuid = Uuid::new_v4();
+        mocker
+            .when::<Uuid, FileStoreResult<()>>("delete")
+            .once()
+            .then(move |uuid| {
+                assert_eq!(uuid, canceled_uuid);
+                Ok(())
+            });
+        let update_file_store = UpdateFileStore::mock(mocker);
+        let index_resolver = IndexResolver::new(meta_store, index_store, update_file_store);
+
+        let now = OffsetDateTime::now_utc();
+        let gen_task = |id, content_uuid, events| Task {
+            id,
+            content: TaskContent::DocumentAddition {
+                content_uuid,
+                merge_strategy: IndexDocumentsMethod::ReplaceDocuments,
+                primary_key: None,
+                documents_count: 100,
+                allow_index_creation: true,
+                index_uid: IndexUid::new_unchecked("test"),
+            },
+            events,
+            retry_of: None,
+            priority: 0,
+        };
+        let canceled = TaskEvent::Canceled {
+            canceled_by: 3,
+            reason: None,
+            timestamp: now,
+        };
+        let tasks = vec![
+            gen_task(0, Uuid::new_v4(), vec![TaskEvent::Created(now)]),
+            gen_task(1, canceled_uuid, vec![TaskEvent::Created(now), canceled]),
+        ];
+
+        let batch = Batch::new(Some(0), BatchContent::DocumentsAdditionBatch(tasks));
+
+        index_resolver.finish(&batch).await;
+    }
+
     #[actix_rt::test]
     #[should_panic]
     async fn panic_when_passed_unsupported_batch() {
@@ -133,8 +190,11 @@ mod test {
             id: 1,
             content: TaskContent::Dump {
                 uid: String::from("hello"),
+                indexes: None,
             },
             events: Vec::new(),
+            retry_of: None,
+            priority: 0,
         };
 
         let batch = task_to_batch(task);
@@ -17,18 +17,21 @@ mod test {
             TaskContent::DocumentAddition { .. } => {
                 BatchContent::DocumentsAdditionBatch(vec![task])
             }
+            TaskContent::SettingsUpdate { .. } => BatchContent::SettingsUpdates(vec![task]),
             TaskContent::DocumentDeletion { .. }
-            | TaskContent::SettingsUpdate { .. }
             | TaskContent::IndexDeletion { .. }
             | TaskContent::IndexCreation { .. }
-            | TaskContent::IndexUpdate { .. } => BatchContent::IndexUpdate(task),
+            | TaskContent::IndexUpdate { .. }
+            | TaskContent::DocumentsExport { .. } => BatchContent::IndexUpdate(task),
             TaskContent::Dump { .. } => BatchContent::Dump(task),
+            TaskContent::TaskCancelation { .. } => unreachable!("cancelations are never batched"),
         };
 
         Batch {
             id: Some(1),
             created_at: OffsetDateTime::now_utc(),
             content,
+            must_stop: Default::default(),
         }
     }
 }
@@ -2,8 +2,8 @@ use async_trait::async_trait;
 
 pub use handlers::empty_handler::EmptyBatchHandler;
 pub use handlers::snapshot_handler::SnapshotHandler;
-pub use scheduler::Scheduler;
-pub use task_store::TaskFilter;
+pub use scheduler::{HeldKind, Scheduler};
+pub use task_store::{DaySummary, LoadedTasks, TaskFilter};
 
 #[cfg(test)]
 pub use task_store::test::MockTaskStore as TaskStore;
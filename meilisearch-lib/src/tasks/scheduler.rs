@@ -1,5 +1,5 @@
 use std::cmp::Ordering;
-use std::collections::{hash_map::Entry, BinaryHeap, HashMap, VecDeque};
+use std::collections::{hash_map::Entry, BinaryHeap, HashMap, HashSet, VecDeque};
 use std::ops::{Deref, DerefMut};
 use std::slice;
 use std::sync::Arc;
@@ -7,33 +7,45 @@ use std::sync::Arc;
 use atomic_refcell::AtomicRefCell;
 use milli::update::IndexDocumentsMethod;
 use time::OffsetDateTime;
-use tokio::sync::{watch, RwLock};
+use tokio::sync::{broadcast, watch, RwLock};
 
 use crate::options::SchedulerConfig;
 use crate::snapshot::SnapshotJob;
 
-use super::batch::{Batch, BatchContent};
-use super::error::Result;
-use super::task::{Task, TaskContent, TaskEvent, TaskId};
+use super::batch::{Batch, BatchContent, MustStopProcessing};
+use super::error::{Result, TaskError};
+use super::task::{Task, TaskContent, TaskEvent, TaskId, TaskResult};
 use super::update_loop::UpdateLoop;
 use super::{BatchHandler, TaskFilter, TaskStore};
 
+/// The number of task changes kept for the subscribers that are late, see `Scheduler::subscribe`.
+const TASK_EVENTS_CAPACITY: usize = 1024;
+
 #[derive(Eq, Debug, Clone, Copy)]
 enum TaskType {
     DocumentAddition { number: usize },
     DocumentUpdate { number: usize },
+    SettingsUpdate { may_create_index: bool },
     IndexUpdate,
     Dump,
 }
 
-/// Two tasks are equal if they have the same type.
+/// Two tasks are equal if they have the same type, and can be batched together.
 impl PartialEq for TaskType {
     fn eq(&self, other: &Self) -> bool {
-        matches!(
-            (self, other),
+        match (self, other) {
             (Self::DocumentAddition { .. }, Self::DocumentAddition { .. })
-                | (Self::DocumentUpdate { .. }, Self::DocumentUpdate { .. })
-        )
+            | (Self::DocumentUpdate { .. }, Self::DocumentUpdate { .. }) => true,
+            (
+                Self::SettingsUpdate {
+                    may_create_index: left,
+                },
+                Self::SettingsUpdate {
+                    may_create_index: right,
+                },
+            ) => left == right,
+            _ => false,
+        }
     }
 }
 
@@ -41,6 +53,7 @@ impl PartialEq for TaskType {
 struct PendingTask {
     kind: TaskType,
     id: TaskId,
+    priority: u8,
 }
 
 impl PartialEq for PendingTask {
@@ -61,10 +74,55 @@ impl Ord for PendingTask {
     }
 }
 
+impl From<Task> for PendingTask {
+    fn from(task: Task) -> Self {
+        let id = task.id;
+        let priority = task.priority;
+
+        let kind = match task.content {
+            TaskContent::DocumentAddition {
+                documents_count,
+                merge_strategy: IndexDocumentsMethod::ReplaceDocuments,
+                ..
+            } => TaskType::DocumentAddition {
+                number: documents_count,
+            },
+            TaskContent::DocumentAddition {
+                documents_count,
+                merge_strategy: IndexDocumentsMethod::UpdateDocuments,
+                ..
+            } => TaskType::DocumentUpdate {
+                number: documents_count,
+            },
+            TaskContent::SettingsUpdate {
+                is_deletion,
+                allow_index_creation,
+                ..
+            } => TaskType::SettingsUpdate {
+                // Only the settings updates that would create a missing index the same way can be
+                // batched together.
+                may_create_index: allow_index_creation && !is_deletion,
+            },
+            TaskContent::Dump { .. } => TaskType::Dump,
+            TaskContent::DocumentDeletion { .. }
+            | TaskContent::IndexDeletion { .. }
+            | TaskContent::IndexCreation { .. }
+            | TaskContent::IndexUpdate { .. }
+            | TaskContent::DocumentsExport { .. } => TaskType::IndexUpdate,
+            _ => unreachable!("unhandled task type"),
+        };
+        PendingTask { kind, id, priority }
+    }
+}
+
 #[derive(Debug)]
 struct TaskList {
     id: TaskListIdentifier,
     tasks: BinaryHeap<PendingTask>,
+    /// The highest priority of the tasks of the list. The tasks of a list are always processed in
+    /// order, so a high priority task makes the tasks enqueued before it on the same index jump
+    /// ahead of the other lists too.
+    priority: u8,
 }
 
 impl Deref for TaskList {
@@ -86,8 +144,24 @@ impl TaskList {
         Self {
             id,
             tasks: Default::default(),
+            priority: 0,
         }
     }
+
+    fn push(&mut self, task: PendingTask) {
+        self.priority = self.priority.max(task.priority);
+        self.tasks.push(task);
+    }
+
+    /// Computes the priority of the list again, after tasks were removed from it.
+    fn update_priority(&mut self) {
+        self.priority = self
+            .tasks
+            .iter()
+            .map(|task| task.priority)
+            .max()
+            .unwrap_or(0);
+    }
 }
 
 impl PartialEq for TaskList {
@@ -106,7 +180,10 @@ impl Ord for TaskList {
                     (None, None) => Ordering::Equal,
                     (None, Some(_)) => Ordering::Less,
                     (Some(_), None) => Ordering::Greater,
-                    (Some(lhs), Some(rhs)) => lhs.cmp(rhs),
+                    (Some(lhs), Some(rhs)) => self
+                        .priority
+                        .cmp(&other.priority)
+                        .then_with(|| lhs.cmp(rhs)),
                 }
             }
             (TaskListIdentifier::Index(_), TaskListIdentifier::Dump) => Ordering::Less,
@@ -138,51 +215,41 @@ impl From<&Task> for TaskListIdentifier {
             | TaskContent::SettingsUpdate { index_uid, .. }
             | TaskContent::IndexDeletion { index_uid }
             | TaskContent::IndexCreation { index_uid, .. }
-            | TaskContent::IndexUpdate { index_uid, .. } => {
+            | TaskContent::IndexUpdate { index_uid, .. }
+            | TaskContent::DocumentsExport { index_uid, .. } => {
                 TaskListIdentifier::Index(index_uid.as_str().to_string())
             }
             TaskContent::Dump { .. } => TaskListIdentifier::Dump,
+            TaskContent::TaskCancelation { .. } => {
+                unreachable!("cancelations are never queued")
+            }
         }
     }
 }
 
+/// The kinds of work that can be held enqueued until they are explicitly released, see
+/// `Scheduler::hold_kind`.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum HeldKind {
+    Dump,
+    Snapshot,
+}
+
 #[derive(Default)]
 struct TaskQueue {
     /// Maps index uids to their TaskList, for quick access
     index_tasks: HashMap<TaskListIdentifier, Arc<AtomicRefCell<TaskList>>>,
     /// A queue that orders TaskList by the priority of their fist update
     queue: BinaryHeap<Arc<AtomicRefCell<TaskList>>>,
+    /// The task lists that are kept out of the queue until they are resumed.
+    suspended: HashSet<TaskListIdentifier>,
 }
 
 impl TaskQueue {
     fn insert(&mut self, task: Task) {
         let id = task.id;
         let uid = TaskListIdentifier::from(&task);
-
-        let kind = match task.content {
-            TaskContent::DocumentAddition {
-                documents_count,
-                merge_strategy: IndexDocumentsMethod::ReplaceDocuments,
-                ..
-            } => TaskType::DocumentAddition {
-                number: documents_count,
-            },
-            TaskContent::DocumentAddition {
-                documents_count,
-                merge_strategy: IndexDocumentsMethod::UpdateDocuments,
-                ..
-            } => TaskType::DocumentUpdate {
-                number: documents_count,
-            },
-            TaskContent::Dump { .. } => TaskType::Dump,
-            TaskContent::DocumentDeletion { .. }
-            | TaskContent::SettingsUpdate { .. }
-            | TaskContent::IndexDeletion { .. }
-            | TaskContent::IndexCreation { .. }
-            | TaskContent::IndexUpdate { .. } => TaskType::IndexUpdate,
-            _ => unreachable!("unhandled task type"),
-        };
-        let task = PendingTask { kind, id };
+        let task = PendingTask::from(task);
 
         match self.index_tasks.entry(uid) {
             Entry::Occupied(entry) => {
@@ -195,14 +262,24 @@ impl TaskQueue {
                 // insert to preserve the order in the queue.
                 assert!(list.peek().map(|old_id| id >= old_id.id).unwrap_or(true));
 
+                let previous_priority = list.priority;
                 list.push(task);
+
+                // The list must be moved up in the queue if the new task raised its priority.
+                if list.priority > previous_priority && !self.suspended.contains(&list.id) {
+                    drop(list);
+                    self.queue = std::mem::take(&mut self.queue).into_iter().collect();
+                }
             }
             Entry::Vacant(entry) => {
+                let suspended = self.suspended.contains(entry.key());
                 let mut task_list = TaskList::new(entry.key().clone());
                 task_list.push(task);
                 let task_list = Arc::new(AtomicRefCell::new(task_list));
                 entry.insert(task_list.clone());
-                self.queue.push(task_list);
+                if !suspended {
+                    self.queue.push(task_list);
+                }
             }
         }
     }
@@ -213,7 +290,9 @@ impl TaskQueue {
         let head = self.queue.pop()?;
         let result = {
             let mut ref_head = head.borrow_mut();
-            f(&mut *ref_head)
+            let result = f(&mut *ref_head);
+            ref_head.update_priority();
+            result
         };
         if !head.borrow().tasks.is_empty() {
             // After being mutated, the head is reinserted to the correct position.
@@ -225,6 +304,74 @@ impl TaskQueue {
         Some(result)
     }
 
+    /// Removes the given tasks from the queue, so they are never scheduled.
+    fn remove(&mut self, ids: &[TaskId]) {
+        for list in self.index_tasks.values() {
+            let mut list = list.borrow_mut();
+            let tasks = std::mem::take(&mut list.tasks);
+            list.tasks = tasks
+                .into_iter()
+                .filter(|task| !ids.contains(&task.id))
+                .collect();
+            list.update_priority();
+        }
+
+        // The first task of the lists may have changed, so the queue must be rebuilt.
+        self.index_tasks.retain(|_, list| !list.borrow().is_empty());
+        self.rebuild_queue();
+    }
+
+    /// Enqueues again a task that was already scheduled, like the unfinished tasks of an
+    /// interrupted batch. Tasks of the same index may have been enqueued since, so its id can be
+    /// lower than the first id of its task list, and the queue must be rebuilt.
+    fn requeue(&mut self, task: Task) {
+        let uid = TaskListIdentifier::from(&task);
+        match self.index_tasks.get(&uid) {
+            Some(list) => {
+                list.borrow_mut().push(PendingTask::from(task));
+                self.rebuild_queue();
+            }
+            None => self.insert(task),
+        }
+    }
+
+    /// Builds the queue again from the task lists that are not suspended.
+    fn rebuild_queue(&mut self) {
+        self.queue = self
+            .index_tasks
+            .iter()
+            .filter(|(id, _)| !self.suspended.contains(id))
+            .map(|(_, list)| list.clone())
+            .collect();
+    }
+
+    /// Keeps the task list `id` out of the queue, so its tasks are not scheduled until it is
+    /// resumed. The tasks inserted in the meantime are kept in the list.
+    fn suspend(&mut self, id: TaskListIdentifier) {
+        if self.suspended.insert(id.clone()) {
+            let queue = std::mem::take(&mut self.queue);
+            self.queue = queue
+                .into_iter()
+                .filter(|list| list.borrow().id != id)
+                .collect();
+        }
+    }
+
+    /// Puts the task list `id` back in the queue.
+    fn resume(&mut self, id: TaskListIdentifier) {
+        if self.suspended.remove(&id) {
+            if let Some(list) = self.index_tasks.get(&id) {
+                self.queue.push(list.clone());
+            }
+        }
+    }
+
+    /// Returns whether there are tasks that can be scheduled, the tasks of the suspended task
+    /// lists excluded.
+    fn has_schedulable_tasks(&self) -> bool {
+        !self.queue.is_empty()
+    }
+
     pub fn is_empty(&self) -> bool {
         self.queue.is_empty() && self.index_tasks.is_empty()
     }
@@ -237,10 +384,16 @@ pub struct Scheduler {
 
     store: TaskStore,
     processing: Processing,
+    /// Raised when the tasks being processed are canceled.
+    must_stop: MustStopProcessing,
     next_fetched_task_id: TaskId,
     config: SchedulerConfig,
+    /// The kinds of work that are kept enqueued until they are released.
+    held_kinds: HashSet<HeldKind>,
     /// Notifies the update loop that a new task was received
     notifier: watch::Sender<()>,
+    /// Sends the tasks that changed to the subscribers.
+    events: broadcast::Sender<Task>,
 }
 
 impl Scheduler {
@@ -250,6 +403,7 @@ impl Scheduler {
         config: SchedulerConfig,
     ) -> Result<Arc<RwLock<Self>>> {
         let (notifier, rcv) = watch::channel(());
+        let (events, _) = broadcast::channel(TASK_EVENTS_CAPACITY);
 
         let this = Self {
             snapshots: VecDeque::new(),
@@ -257,9 +411,12 @@ impl Scheduler {
 
             store,
             processing: Processing::Nothing,
+            must_stop: MustStopProcessing::default(),
             next_fetched_task_id: 0,
             config,
+            held_kinds: HashSet::new(),
             notifier,
+            events,
         };
 
         // Notify update loop to start processing pending updates immediately after startup.
@@ -280,16 +437,69 @@ impl Scheduler {
     }
 
     /// Clears the processing list, this method should be called when the processing of a batch is finished.
-    pub fn finish(&mut self) {
+    /// The tasks of the batch that are left unfinished, because the batch was interrupted by the
+    /// cancelation of other tasks, are enqueued again.
+    pub fn finish(&mut self, content: &BatchContent) {
         self.processing = Processing::Nothing;
+
+        if let BatchContent::DocumentsAdditionBatch(tasks) = content {
+            for task in tasks.iter().filter(|task| !task.is_finished()) {
+                self.tasks.requeue(task.clone());
+            }
+        }
+
+        self.notify_if_not_empty();
+    }
+
+    /// Marks the given document additions as being processed, bypassing the task queue.
+    #[cfg(test)]
+    pub fn set_processing_document_additions(&mut self, ids: Vec<TaskId>) {
+        self.processing = Processing::DocumentAdditions(ids);
     }
 
     pub fn notify(&self) {
         let _ = self.notifier.send(());
     }
 
+    /// Returns a receiver of the tasks that are registered or change state from now on, with their
+    /// new state. A receiver that falls more than `TASK_EVENTS_CAPACITY` tasks behind misses the
+    /// oldest ones.
+    pub fn subscribe(&self) -> broadcast::Receiver<Task> {
+        self.events.subscribe()
+    }
+
+    /// Sends the `tasks` to the subscribers.
+    pub fn publish(&self, tasks: &[Task]) {
+        // there is no need to clone the tasks when nobody listens.
+        if self.events.receiver_count() > 0 {
+            for task in tasks {
+                let _ = self.events.send(task.clone());
+            }
+        }
+    }
+
     fn notify_if_not_empty(&self) {
-        if !self.snapshots.is_empty() || !self.tasks.is_empty() {
+        let has_snapshots =
+            !self.snapshots.is_empty() && !self.held_kinds.contains(&HeldKind::Snapshot);
+        if has_snapshots || self.tasks.has_schedulable_tasks() {
+            self.notify();
+        }
+    }
+
+    /// Keeps the work of the given kind enqueued, even when there is nothing else to process,
+    /// until it is released with `release_kind`. The work of this kind can still be registered.
+    pub fn hold_kind(&mut self, kind: HeldKind) {
+        if self.held_kinds.insert(kind) && kind == HeldKind::Dump {
+            self.tasks.suspend(TaskListIdentifier::Dump);
+        }
+    }
+
+    /// Lets the work of the given kind be processed again.
+    pub fn release_kind(&mut self, kind: HeldKind) {
+        if self.held_kinds.remove(&kind) {
+            if kind == HeldKind::Dump {
+                self.tasks.resume(TaskListIdentifier::Dump);
+            }
             self.notify();
         }
     }
@@ -298,22 +508,42 @@ impl Scheduler {
         match content {
             BatchContent::DocumentsAdditionBatch(tasks) => {
                 let tasks = self.store.update_tasks(tasks).await?;
+                self.publish(&tasks);
                 Ok(BatchContent::DocumentsAdditionBatch(tasks))
             }
+            BatchContent::SettingsUpdates(tasks) => {
+                let tasks = self.store.update_tasks(tasks).await?;
+                self.publish(&tasks);
+                Ok(BatchContent::SettingsUpdates(tasks))
+            }
             BatchContent::IndexUpdate(t) => {
                 let mut tasks = self.store.update_tasks(vec![t]).await?;
+                self.publish(&tasks);
                 Ok(BatchContent::IndexUpdate(tasks.remove(0)))
             }
             BatchContent::Dump(t) => {
                 let mut tasks = self.store.update_tasks(vec![t]).await?;
+                self.publish(&tasks);
                 Ok(BatchContent::Dump(tasks.remove(0)))
             }
             other => Ok(other),
         }
     }
 
+    /// Returns the task `id`, or a `TaskError::UnexistingTask` if there is no such task. The tasks
+    /// of the batch that was just prepared are only marked as processing in the store once the
+    /// update loop starts processing it, until then they are returned as processing anyway.
     pub async fn get_task(&self, id: TaskId, filter: Option<TaskFilter>) -> Result<Task> {
-        self.store.get_task(id, filter).await
+        let mut task = self.store.get_task(id, filter).await?;
+        let is_processing = self.processing.ids().any(|processing| processing == id);
+        if is_processing
+            && !task.is_finished()
+            && !matches!(task.events.last(), Some(TaskEvent::Processing(_)))
+        {
+            task.push_event(TaskEvent::Processing(OffsetDateTime::now_utc()));
+        }
+
+        Ok(task)
     }
 
     pub async fn list_tasks(
@@ -325,6 +555,17 @@ impl Scheduler {
         self.store.list_tasks(offset, filter, limit).await
     }
 
+    pub async fn list_tasks_with_total(
+        &self,
+        offset: Option<TaskId>,
+        filter: Option<TaskFilter>,
+        limit: Option<usize>,
+    ) -> Result<(u64, Vec<Task>)> {
+        self.store
+            .list_tasks_with_total(offset, filter, limit)
+            .await
+    }
+
     pub async fn get_processing_tasks(&self) -> Result<Vec<Task>> {
         let mut tasks = Vec::new();
 
@@ -336,32 +577,133 @@ impl Scheduler {
         Ok(tasks)
     }
 
+    /// Enqueues the snapshot job, unless a snapshot is already enqueued. Since that snapshot is
+    /// yet to be made, it will contain everything the new one would, and a slow snapshot can't
+    /// cause a backlog of snapshots.
     pub fn schedule_snapshot(&mut self, job: SnapshotJob) {
+        if !self.snapshots.is_empty() {
+            log::debug!("a snapshot is already enqueued, skipping the scheduled snapshot");
+            return;
+        }
+
         self.snapshots.push_back(job);
         self.notify();
     }
 
+    /// Asks the handler processing the current batch to stop if the batch contains any of the
+    /// tasks targeted by the `cancelation`. The targeted tasks that are still enqueued are
+    /// canceled the next time the pending tasks are fetched.
+    pub fn cancel_processing(&self, cancelation: &Task) {
+        if let TaskContent::TaskCancelation { tasks, .. } = &cancelation.content {
+            let targeted: Vec<_> = self
+                .processing
+                .ids()
+                .filter(|id| tasks.contains(id))
+                .collect();
+            if !targeted.is_empty() {
+                self.must_stop.must_stop(cancelation.id, targeted);
+            }
+        }
+    }
+
     async fn fetch_pending_tasks(&mut self) -> Result<()> {
-        self.store
+        let tasks = self
+            .store
             .fetch_unfinished_tasks(Some(self.next_fetched_task_id))
-            .await?
-            .into_iter()
-            .for_each(|t| {
-                self.next_fetched_task_id = t.id + 1;
-                self.register_task(t);
-            });
+            .await?;
+
+        let mut cancelations = Vec::new();
+        for task in tasks {
+            self.next_fetched_task_id = task.id + 1;
+            match task.content {
+                TaskContent::TaskCancelation { .. } => cancelations.push(task),
+                _ => self.register_task(task),
+            }
+        }
+
+        for cancelation in cancelations {
+            self.process_cancelation(cancelation).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes the tasks targeted by the `cancelation` from the queue and marks them as canceled.
+    /// The tasks that were interrupted while processing were already marked as canceled by their
+    /// handler, and only get the reason of the cancelation.
+    async fn process_cancelation(&mut self, mut cancelation: Task) -> Result<()> {
+        let (targets, reason) = match &cancelation.content {
+            TaskContent::TaskCancelation { tasks, reason } => (tasks.clone(), reason.clone()),
+            _ => unreachable!("not a cancelation task"),
+        };
+
+        self.tasks.remove(&targets);
+
+        let timestamp = OffsetDateTime::now_utc();
+        let mut canceled_tasks = 0;
+        let mut updated = Vec::new();
+        for id in targets {
+            let mut task = match self.store.get_task(id, None).await {
+                Ok(task) => task,
+                Err(TaskError::UnexistingTask(_)) => continue,
+                Err(e) => return Err(e),
+            };
+
+            match task.events.last() {
+                Some(TaskEvent::Canceled { canceled_by, .. }) if *canceled_by == cancelation.id => {
+                    canceled_tasks += 1;
+                    if let Some(TaskEvent::Canceled {
+                        reason: task_reason @ None,
+                        ..
+                    }) = task.events.last_mut()
+                    {
+                        if reason.is_some() {
+                            *task_reason = reason.clone();
+                            updated.push(task);
+                        }
+                    }
+                }
+                _ if task.is_finished() => (),
+                _ if matches!(task.content, TaskContent::TaskCancelation { .. }) => (),
+                _ => {
+                    task.push_event(TaskEvent::Canceled {
+                        canceled_by: cancelation.id,
+                        reason: reason.clone(),
+                        timestamp,
+                    });
+                    updated.push(task);
+                    canceled_tasks += 1;
+                }
+            }
+        }
+
+        cancelation.push_event(TaskEvent::succeeded(TaskResult::TaskCancelation {
+            canceled_tasks,
+        }));
+        updated.push(cancelation);
+
+        let updated = self.store.update_tasks(updated).await?;
+        self.publish(&updated);
 
         Ok(())
     }
 
     /// Prepare the next batch, and set `processing` to the ids in that batch.
+    #[tracing::instrument(
+        name = "create_next_batch",
+        level = "debug",
+        skip_all,
+        fields(batch_id, task_ids)
+    )]
     pub async fn prepare(&mut self) -> Result<Batch> {
         // If there is a job to process, do it first.
-        if let Some(job) = self.snapshots.pop_front() {
-            // There is more work to do, notify the update loop
-            self.notify_if_not_empty();
-            let batch = Batch::new(None, BatchContent::Snapshot(job));
-            return Ok(batch);
+        if !self.held_kinds.contains(&HeldKind::Snapshot) {
+            if let Some(job) = self.snapshots.pop_front() {
+                // There is more work to do, notify the update loop
+                self.notify_if_not_empty();
+                let batch = Batch::new(None, BatchContent::Snapshot(job));
+                return Ok(batch);
+            }
         }
 
         // Try to fill the queue with pending tasks.
@@ -389,9 +731,18 @@ impl Scheduler {
                 timestamp: OffsetDateTime::now_utc(),
             });
 
+            let span = tracing::Span::current();
+            span.record("batch_id", &id);
+            span.record(
+                "task_ids",
+                &tracing::field::debug(processing.ids().collect::<Vec<_>>()),
+            );
+
             self.processing = processing;
+            self.must_stop = MustStopProcessing::default();
 
-            let batch = Batch::new(Some(id), content);
+            let mut batch = Batch::new(Some(id), content);
+            batch.must_stop = self.must_stop.clone();
 
             // There is more work to do, notify the update loop
             self.notify_if_not_empty();
@@ -406,6 +757,7 @@ impl Scheduler {
 #[derive(Debug, PartialEq, Eq)]
 pub enum Processing {
     DocumentAdditions(Vec<TaskId>),
+    SettingsUpdates(Vec<TaskId>),
     IndexUpdate(TaskId),
     Dump(TaskId),
     /// Variant used when there is nothing to process.
@@ -441,7 +793,9 @@ impl Processing {
 
     pub fn ids(&self) -> impl Iterator<Item = TaskId> + '_ {
         match self {
-            Processing::DocumentAdditions(v) => ProcessingIter::Many(v.iter()),
+            Processing::DocumentAdditions(v) | Processing::SettingsUpdates(v) => {
+                ProcessingIter::Many(v.iter())
+            }
             Processing::IndexUpdate(id) | Processing::Dump(id) => ProcessingIter::Single(Some(*id)),
             Processing::Nothing => ProcessingIter::Single(None),
         }
@@ -449,7 +803,7 @@ impl Processing {
 
     pub fn len(&self) -> usize {
         match self {
-            Processing::DocumentAdditions(v) => v.len(),
+            Processing::DocumentAdditions(v) | Processing::SettingsUpdates(v) => v.len(),
             Processing::IndexUpdate(_) | Processing::Dump(_) => 1,
             Processing::Nothing => 0,
         }
@@ -467,6 +821,7 @@ fn make_batch(tasks: &mut TaskQueue, config: &SchedulerConfig) -> Processing {
             Some(PendingTask {
                 kind: TaskType::IndexUpdate,
                 id,
+                ..
             }) => {
                 list.pop();
                 Processing::IndexUpdate(id)
@@ -474,6 +829,7 @@ fn make_batch(tasks: &mut TaskQueue, config: &SchedulerConfig) -> Processing {
             Some(PendingTask {
                 kind: TaskType::Dump,
                 id,
+                ..
             }) => {
                 list.pop();
                 Processing::Dump(id)
@@ -502,7 +858,10 @@ fn make_batch(tasks: &mut TaskQueue, config: &SchedulerConfig) -> Processing {
                         _ => break,
                     }
                 }
-                Processing::DocumentAdditions(task_list)
+                match kind {
+                    TaskType::SettingsUpdate { .. } => Processing::SettingsUpdates(task_list),
+                    _ => Processing::DocumentAdditions(task_list),
+                }
             }
             None => Processing::Nothing,
         })
@@ -511,10 +870,14 @@ fn make_batch(tasks: &mut TaskQueue, config: &SchedulerConfig) -> Processing {
 
 #[cfg(test)]
 mod test {
+    use std::path::PathBuf;
+
     use meilisearch_types::index_uid::IndexUid;
     use milli::update::IndexDocumentsMethod;
+    use nelson::Mocker;
     use uuid::Uuid;
 
+    use crate::options::SyncMode;
     use crate::tasks::task::TaskContent;
 
     use super::*;
@@ -524,6 +887,8 @@ mod test {
             id,
             content,
             events: vec![],
+            retry_of: None,
+            priority: 0,
         }
     }
 
@@ -555,6 +920,25 @@ mod test {
         assert!(queue.queue.is_empty());
     }
 
+    #[test]
+    #[rustfmt::skip]
+    fn remove_canceled_tasks() {
+        let mut queue = TaskQueue::default();
+        queue.insert(gen_task(0, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test1") }));
+        queue.insert(gen_task(1, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test2") }));
+        queue.insert(gen_task(2, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test1") }));
+        queue.insert(gen_task(3, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test2") }));
+
+        queue.remove(&[0, 1, 3]);
+
+        let config = SchedulerConfig::default();
+
+        // test2 has no more tasks, and task 2 is now the head of test1.
+        assert_eq!(make_batch(&mut queue, &config), Processing::IndexUpdate(2));
+        assert_eq!(make_batch(&mut queue, &config), Processing::Nothing);
+        assert!(queue.is_empty());
+    }
+
     fn gen_doc_addition_task_content(index_uid: &str) -> TaskContent {
         TaskContent::DocumentAddition {
             content_uuid: Uuid::new_v4(),
@@ -578,7 +962,10 @@ mod test {
         queue.insert(gen_task(5, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test1")}));
         queue.insert(gen_task(6, gen_doc_addition_task_content("test2")));
         queue.insert(gen_task(7, gen_doc_addition_task_content("test1")));
-        queue.insert(gen_task(8, TaskContent::Dump { uid: "adump".to_owned() }));
+        queue.insert(gen_task(8, TaskContent::Dump {
+            uid: "adump".to_owned(),
+            indexes: None,
+        }));
 
         let config = SchedulerConfig::default();
 
@@ -606,4 +993,368 @@ mod test {
 
         assert!(queue.is_empty());
     }
+
+    fn gen_settings_task_content(index_uid: &str, allow_index_creation: bool) -> TaskContent {
+        TaskContent::SettingsUpdate {
+            settings: Default::default(),
+            is_deletion: false,
+            allow_index_creation,
+            index_uid: IndexUid::new_unchecked(index_uid),
+        }
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn batch_settings_updates() {
+        let mut queue = TaskQueue::default();
+        queue.insert(gen_task(0, gen_settings_task_content("test1", true)));
+        queue.insert(gen_task(1, gen_settings_task_content("test1", true)));
+        queue.insert(gen_task(2, gen_settings_task_content("test1", true)));
+        queue.insert(gen_task(3, gen_settings_task_content("test1", false)));
+        queue.insert(gen_task(4, gen_doc_addition_task_content("test1")));
+        queue.insert(gen_task(5, gen_settings_task_content("test1", true)));
+
+        let config = SchedulerConfig::default();
+
+        assert_eq!(make_batch(&mut queue, &config), Processing::SettingsUpdates(vec![0, 1, 2]));
+        assert_eq!(make_batch(&mut queue, &config), Processing::SettingsUpdates(vec![3]));
+        assert_eq!(make_batch(&mut queue, &config), Processing::DocumentAdditions(vec![4]));
+        assert_eq!(make_batch(&mut queue, &config), Processing::SettingsUpdates(vec![5]));
+        assert!(queue.is_empty());
+
+        let config = SchedulerConfig { disable_auto_batching: true, ..Default::default() };
+        queue.insert(gen_task(6, gen_settings_task_content("test1", true)));
+        queue.insert(gen_task(7, gen_settings_task_content("test1", true)));
+
+        assert_eq!(make_batch(&mut queue, &config), Processing::SettingsUpdates(vec![6]));
+        assert_eq!(make_batch(&mut queue, &config), Processing::SettingsUpdates(vec![7]));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn never_batch_tasks_of_different_indexes() {
+        let mut queue = TaskQueue::default();
+        queue.insert(gen_task(0, gen_doc_addition_task_content("catto")));
+        queue.insert(gen_task(1, gen_doc_addition_task_content("doggo")));
+        queue.insert(gen_task(2, gen_settings_task_content("catto", true)));
+        queue.insert(gen_task(3, gen_settings_task_content("doggo", true)));
+
+        let config = SchedulerConfig::default();
+
+        // Each index has its own list of tasks, so a batch never mixes the tasks of two indexes.
+        assert_eq!(make_batch(&mut queue, &config), Processing::DocumentAdditions(vec![0]));
+        assert_eq!(make_batch(&mut queue, &config), Processing::DocumentAdditions(vec![1]));
+        assert_eq!(make_batch(&mut queue, &config), Processing::SettingsUpdates(vec![2]));
+        assert_eq!(make_batch(&mut queue, &config), Processing::SettingsUpdates(vec![3]));
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn high_priority_tasks_are_batched_first() {
+        let mut queue = TaskQueue::default();
+        queue.insert(gen_task(0, gen_doc_addition_task_content("movies")));
+        queue.insert(gen_task(1, gen_doc_addition_task_content("movies")));
+        queue.insert(Task { priority: 1, ..gen_task(2, gen_settings_task_content("products", true)) });
+
+        let config = SchedulerConfig::default();
+
+        assert_eq!(make_batch(&mut queue, &config), Processing::SettingsUpdates(vec![2]));
+        assert_eq!(make_batch(&mut queue, &config), Processing::DocumentAdditions(vec![0, 1]));
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn high_priority_tasks_never_overtake_their_index() {
+        let mut queue = TaskQueue::default();
+        queue.insert(gen_task(0, gen_doc_addition_task_content("movies")));
+        queue.insert(gen_task(1, gen_doc_addition_task_content("products")));
+        queue.insert(Task { priority: 1, ..gen_task(2, gen_settings_task_content("movies", true)) });
+
+        let config = SchedulerConfig::default();
+
+        // the addition enqueued before the settings on the same index is processed first, and the
+        // whole index jumps ahead of the others.
+        assert_eq!(make_batch(&mut queue, &config), Processing::DocumentAdditions(vec![0]));
+        assert_eq!(make_batch(&mut queue, &config), Processing::SettingsUpdates(vec![2]));
+        assert_eq!(make_batch(&mut queue, &config), Processing::DocumentAdditions(vec![1]));
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn suspended_dumps_are_not_scheduled() {
+        let mut queue = TaskQueue::default();
+        queue.insert(gen_task(0, TaskContent::Dump {
+            uid: "dump".to_string(),
+            indexes: None,
+        }));
+        queue.insert(gen_task(1, gen_doc_addition_task_content("test1")));
+
+        queue.suspend(TaskListIdentifier::Dump);
+        queue.insert(gen_task(2, TaskContent::Dump {
+            uid: "other_dump".to_string(),
+            indexes: None,
+        }));
+
+        let config = SchedulerConfig::default();
+
+        assert_eq!(make_batch(&mut queue, &config), Processing::DocumentAdditions(vec![1]));
+        assert_eq!(make_batch(&mut queue, &config), Processing::Nothing);
+        assert!(!queue.has_schedulable_tasks());
+
+        queue.resume(TaskListIdentifier::Dump);
+
+        assert_eq!(make_batch(&mut queue, &config), Processing::Dump(0));
+        assert_eq!(make_batch(&mut queue, &config), Processing::Dump(2));
+        assert!(queue.is_empty());
+    }
+
+    /// Returns a scheduler without any pending task, and without an update loop.
+    fn gen_scheduler() -> Scheduler {
+        let mocker = Mocker::default();
+        mocker
+            .when::<Option<TaskId>, Result<Vec<Task>>>("fetch_unfinished_tasks")
+            .then(|_| Ok(Vec::new()));
+        let (notifier, _receiver) = watch::channel(());
+        let (events, _) = broadcast::channel(TASK_EVENTS_CAPACITY);
+        Scheduler {
+            snapshots: VecDeque::new(),
+            tasks: TaskQueue::default(),
+            store: TaskStore::mock(mocker),
+            processing: Processing::Nothing,
+            must_stop: MustStopProcessing::default(),
+            next_fetched_task_id: 0,
+            config: SchedulerConfig::default(),
+            held_kinds: HashSet::new(),
+            notifier,
+            events,
+        }
+    }
+
+    fn gen_snapshot_job() -> SnapshotJob {
+        SnapshotJob {
+            dest_path: PathBuf::from("snapshots"),
+            src_path: PathBuf::from("data.ms"),
+            meta_env_size: 0,
+            sync_mode: SyncMode::default(),
+            index_size: 0,
+        }
+    }
+
+    #[actix_rt::test]
+    async fn held_snapshots_stay_enqueued() {
+        let mut scheduler = gen_scheduler();
+
+        scheduler.hold_kind(HeldKind::Snapshot);
+        scheduler.schedule_snapshot(gen_snapshot_job());
+
+        let batch = scheduler.prepare().await.unwrap();
+        assert!(matches!(batch.content, BatchContent::Empty));
+        assert_eq!(scheduler.snapshots.len(), 1);
+
+        scheduler.release_kind(HeldKind::Snapshot);
+
+        let batch = scheduler.prepare().await.unwrap();
+        assert!(matches!(batch.content, BatchContent::Snapshot(_)));
+        assert!(scheduler.snapshots.is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn publish_updated_tasks() {
+        let mut scheduler = gen_scheduler();
+        let mocker = Mocker::default();
+        mocker
+            .when::<Vec<Task>, Result<Vec<Task>>>("update_tasks")
+            .then(Ok);
+        scheduler.store = TaskStore::mock(mocker);
+        let mut events = scheduler.subscribe();
+
+        let mut task = gen_task(
+            0,
+            TaskContent::IndexDeletion {
+                index_uid: IndexUid::new_unchecked("test"),
+            },
+        );
+        task.push_event(TaskEvent::succeeded(TaskResult::Other));
+        scheduler
+            .update_tasks(BatchContent::IndexUpdate(task.clone()))
+            .await
+            .unwrap();
+
+        assert_eq!(events.try_recv().unwrap(), task);
+        assert!(events.try_recv().is_err());
+    }
+
+    fn gen_index_deletion(id: TaskId) -> Task {
+        let mut task = gen_task(
+            id,
+            TaskContent::IndexDeletion {
+                index_uid: IndexUid::new_unchecked("test"),
+            },
+        );
+        task.events
+            .push(TaskEvent::Created(OffsetDateTime::now_utc()));
+        task
+    }
+
+    fn gen_scheduler_with_tasks(tasks: Vec<Task>) -> Scheduler {
+        let mut scheduler = gen_scheduler();
+        let mocker = Mocker::default();
+        mocker
+            .when::<(TaskId, Option<TaskFilter>), Result<Task>>("get_task")
+            .then(move |(id, _)| {
+                tasks
+                    .iter()
+                    .find(|task| task.id == id)
+                    .cloned()
+                    .ok_or(TaskError::UnexistingTask(id))
+            });
+        scheduler.store = TaskStore::mock(mocker);
+        scheduler
+    }
+
+    #[actix_rt::test]
+    async fn get_enqueued_task() {
+        let task = gen_index_deletion(0);
+        let scheduler = gen_scheduler_with_tasks(vec![task.clone()]);
+
+        assert_eq!(scheduler.get_task(0, None).await.unwrap(), task);
+    }
+
+    #[actix_rt::test]
+    async fn get_task_of_prepared_batch() {
+        let mut scheduler = gen_scheduler_with_tasks(vec![gen_index_deletion(0)]);
+        scheduler.processing = Processing::IndexUpdate(0);
+
+        // the processing event is not stored yet, but the task is returned as processing.
+        let task = scheduler.get_task(0, None).await.unwrap();
+        assert!(matches!(
+            task.events.as_slice(),
+            [TaskEvent::Created(_), TaskEvent::Processing(_)]
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn get_missing_task() {
+        let scheduler = gen_scheduler_with_tasks(vec![gen_index_deletion(0)]);
+
+        let error = scheduler.get_task(1, None).await.unwrap_err();
+        assert!(matches!(error, TaskError::UnexistingTask(1)));
+    }
+
+    #[actix_rt::test]
+    async fn cancelation_reason_is_recorded_on_canceled_tasks() {
+        let enqueued = gen_index_deletion(0);
+        // interrupted while processing, its handler already marked it as canceled.
+        let mut interrupted = gen_index_deletion(1);
+        interrupted.push_event(TaskEvent::Canceled {
+            canceled_by: 2,
+            reason: None,
+            timestamp: OffsetDateTime::now_utc(),
+        });
+        let mut cancelation = gen_task(
+            2,
+            TaskContent::TaskCancelation {
+                tasks: vec![0, 1],
+                reason: Some("wrong index".to_string()),
+            },
+        );
+        cancelation.push_event(TaskEvent::Created(OffsetDateTime::now_utc()));
+
+        let mut scheduler = gen_scheduler();
+        let tasks = vec![enqueued, interrupted];
+        let mocker = Mocker::default();
+        mocker
+            .when::<(TaskId, Option<TaskFilter>), Result<Task>>("get_task")
+            .then(move |(id, _)| Ok(tasks[id as usize].clone()));
+        mocker
+            .when::<Vec<Task>, Result<Vec<Task>>>("update_tasks")
+            .then(Ok);
+        scheduler.store = TaskStore::mock(mocker);
+        let mut events = scheduler.subscribe();
+
+        scheduler.process_cancelation(cancelation).await.unwrap();
+
+        for id in [0, 1] {
+            let task = events.try_recv().unwrap();
+            assert_eq!(task.id, id);
+            assert!(matches!(
+                task.events.last(),
+                Some(TaskEvent::Canceled { canceled_by: 2, reason: Some(reason), .. })
+                    if reason == "wrong index"
+            ));
+        }
+        let cancelation = events.try_recv().unwrap();
+        assert!(matches!(
+            cancelation.events.last(),
+            Some(TaskEvent::Succeeded {
+                result: TaskResult::TaskCancelation { canceled_tasks: 2 },
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn interrupted_additions_are_enqueued_again() {
+        let mut scheduler = gen_scheduler();
+        scheduler.set_processing_document_additions(vec![0, 1]);
+
+        let cancelation = gen_task(
+            2,
+            TaskContent::TaskCancelation {
+                tasks: vec![1],
+                reason: None,
+            },
+        );
+        scheduler.cancel_processing(&cancelation);
+
+        assert!(scheduler.must_stop.get());
+        assert_eq!(scheduler.must_stop.canceled_by(0), None);
+        assert_eq!(scheduler.must_stop.canceled_by(1), Some(2));
+
+        // a task of the same index is enqueued while the batch is being processed.
+        scheduler.register_task(gen_index_deletion(3));
+
+        // the handler only canceled the targeted task, the other one is enqueued again.
+        let mut requeued = gen_task(0, gen_doc_addition_task_content("test"));
+        requeued.push_event(TaskEvent::Created(OffsetDateTime::now_utc()));
+        let mut canceled = gen_task(1, gen_doc_addition_task_content("test"));
+        canceled.push_event(TaskEvent::Canceled {
+            canceled_by: 2,
+            reason: None,
+            timestamp: OffsetDateTime::now_utc(),
+        });
+        scheduler.finish(&BatchContent::DocumentsAdditionBatch(vec![
+            requeued, canceled,
+        ]));
+
+        assert!(scheduler.processing.is_nothing());
+        assert_eq!(
+            make_batch(&mut scheduler.tasks, &scheduler.config),
+            Processing::DocumentAdditions(vec![0])
+        );
+        assert_eq!(
+            make_batch(&mut scheduler.tasks, &scheduler.config),
+            Processing::IndexUpdate(3)
+        );
+        assert!(scheduler.tasks.is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn never_enqueue_two_snapshots() {
+        let mut scheduler = gen_scheduler();
+
+        scheduler.schedule_snapshot(gen_snapshot_job());
+        scheduler.schedule_snapshot(gen_snapshot_job());
+        assert_eq!(scheduler.snapshots.len(), 1);
+
+        // a snapshot can be enqueued while the previous one is being made.
+        let batch = scheduler.prepare().await.unwrap();
+        assert!(matches!(batch.content, BatchContent::Snapshot(_)));
+        scheduler.schedule_snapshot(gen_snapshot_job());
+        scheduler.schedule_snapshot(gen_snapshot_job());
+        assert_eq!(scheduler.snapshots.len(), 1);
+    }
 }
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use meilisearch_types::error::ResponseError;
 use meilisearch_types::index_uid::IndexUid;
 use milli::update::{DocumentAdditionResult, IndexDocumentsMethod};
@@ -13,9 +15,29 @@ pub type TaskId = u32;
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 #[cfg_attr(test, derive(proptest_derive::Arbitrary))]
 pub enum TaskResult {
-    DocumentAddition { indexed_documents: u64 },
-    DocumentDeletion { deleted_documents: u64 },
-    ClearAll { deleted_documents: u64 },
+    DocumentAddition {
+        indexed_documents: u64,
+        /// The primary key inferred from the documents, when neither the index nor the task
+        /// defined one.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        inferred_primary_key: Option<String>,
+    },
+    DocumentDeletion {
+        deleted_documents: u64,
+    },
+    ClearAll {
+        deleted_documents: u64,
+    },
+    DocumentsExport {
+        exported_documents: u64,
+    },
+    TaskCancelation {
+        canceled_tasks: u64,
+    },
+    Dump {
+        index_count: u64,
+        total_documents: u64,
+    },
     Other,
 }
 
@@ -23,6 +45,7 @@ impl From<DocumentAdditionResult> for TaskResult {
     fn from(other: DocumentAdditionResult) -> Self {
         Self::DocumentAddition {
             indexed_documents: other.indexed_documents,
+            inferred_primary_key: None,
         }
     }
 }
@@ -58,6 +81,16 @@ pub enum TaskEvent {
         #[serde(with = "time::serde::rfc3339")]
         timestamp: OffsetDateTime,
     },
+    Canceled {
+        /// The id of the `TaskCancelation` task that canceled this task.
+        canceled_by: TaskId,
+        /// Why the task was canceled, as given to the `TaskCancelation` task.
+        #[serde(default)]
+        reason: Option<String>,
+        #[cfg_attr(test, proptest(strategy = "test::datetime_strategy()"))]
+        #[serde(with = "time::serde::rfc3339")]
+        timestamp: OffsetDateTime,
+    },
 }
 
 impl TaskEvent {
@@ -74,6 +107,26 @@ impl TaskEvent {
             timestamp: OffsetDateTime::now_utc(),
         }
     }
+
+    pub fn timestamp(&self) -> OffsetDateTime {
+        match self {
+            TaskEvent::Created(ts) | TaskEvent::Processing(ts) => *ts,
+            TaskEvent::Batched { timestamp, .. }
+            | TaskEvent::Succeeded { timestamp, .. }
+            | TaskEvent::Failed { timestamp, .. }
+            | TaskEvent::Canceled { timestamp, .. } => *timestamp,
+        }
+    }
+
+    fn timestamp_mut(&mut self) -> &mut OffsetDateTime {
+        match self {
+            TaskEvent::Created(ts) | TaskEvent::Processing(ts) => ts,
+            TaskEvent::Batched { timestamp, .. }
+            | TaskEvent::Succeeded { timestamp, .. }
+            | TaskEvent::Failed { timestamp, .. }
+            | TaskEvent::Canceled { timestamp, .. } => timestamp,
+        }
+    }
 }
 
 /// A task represents an operation that Meilisearch must do.
@@ -90,20 +143,65 @@ pub struct Task {
     // the TaskContent.
     pub content: TaskContent,
     pub events: Vec<TaskEvent>,
+    /// The failed task this task retries, see `TaskStore::register_retries`. The retries of a
+    /// retry point to the first task, so that a task and all its retries share the same link.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_of: Option<TaskId>,
+    /// The enqueued tasks of a higher priority are scheduled first, see `TaskStore::register`.
+    #[serde(default, skip_serializing_if = "is_default_priority")]
+    pub priority: u8,
+}
+
+fn is_default_priority(priority: &u8) -> bool {
+    *priority == 0
 }
 
 impl Task {
     /// Return true when a task is finished.
-    /// A task is finished when its last state is either `Succeeded` or `Failed`.
+    /// A task is finished when its last state is either `Succeeded`, `Failed` or `Canceled`.
     pub fn is_finished(&self) -> bool {
         self.events.last().map_or(false, |event| {
             matches!(
                 event,
-                TaskEvent::Succeeded { .. } | TaskEvent::Failed { .. }
+                TaskEvent::Succeeded { .. } | TaskEvent::Failed { .. } | TaskEvent::Canceled { .. }
             )
         })
     }
 
+    /// Pushes a new event to the task.
+    ///
+    /// The timestamps of the events are taken from the system clock, which can go backward. In
+    /// that case, the timestamp of the new event is clamped to the one of the previous event, so
+    /// that the events of a task are always ordered and the task duration is never negative.
+    pub fn push_event(&mut self, mut event: TaskEvent) {
+        if let Some(last) = self.events.last().map(TaskEvent::timestamp) {
+            if event.timestamp() < last {
+                log::warn!(
+                    "The system clock went backward by {} while processing task {}.",
+                    last - event.timestamp(),
+                    self.id,
+                );
+                *event.timestamp_mut() = last;
+            }
+        }
+
+        self.events.push(event);
+    }
+
+    /// Removes the events pushed when the task was batched and processed, so that the task is
+    /// enqueued again. Returns whether any event was removed.
+    pub fn requeue(&mut self) -> bool {
+        let events = self.events.len();
+        while matches!(
+            self.events.last(),
+            Some(TaskEvent::Batched { .. } | TaskEvent::Processing(_))
+        ) {
+            self.events.pop();
+        }
+
+        self.events.len() < events
+    }
+
     /// Return the content_uuid of the `Task` if there is one.
     pub fn get_content_uuid(&self) -> Option<Uuid> {
         match self {
@@ -115,6 +213,18 @@ impl Task {
         }
     }
 
+    /// Returns the status of the task, as given by its last event. The batched tasks are still
+    /// enqueued.
+    pub fn status(&self) -> TaskStatus {
+        match self.events.last() {
+            Some(TaskEvent::Created(_) | TaskEvent::Batched { .. }) | None => TaskStatus::Enqueued,
+            Some(TaskEvent::Processing(_)) => TaskStatus::Processing,
+            Some(TaskEvent::Succeeded { .. }) => TaskStatus::Succeeded,
+            Some(TaskEvent::Failed { .. }) => TaskStatus::Failed,
+            Some(TaskEvent::Canceled { .. }) => TaskStatus::Canceled,
+        }
+    }
+
     pub fn index_uid(&self) -> Option<&str> {
         match &self.content {
             TaskContent::DocumentAddition { index_uid, .. }
@@ -122,17 +232,62 @@ impl Task {
             | TaskContent::SettingsUpdate { index_uid, .. }
             | TaskContent::IndexDeletion { index_uid }
             | TaskContent::IndexCreation { index_uid, .. }
-            | TaskContent::IndexUpdate { index_uid, .. } => Some(index_uid.as_str()),
-            TaskContent::Dump { .. } => None,
+            | TaskContent::IndexUpdate { index_uid, .. }
+            | TaskContent::DocumentsExport { index_uid, .. } => Some(index_uid.as_str()),
+            TaskContent::Dump { .. } | TaskContent::TaskCancelation { .. } => None,
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum TaskStatus {
+    Enqueued,
+    Processing,
+    Succeeded,
+    Failed,
+    Canceled,
+}
+
+impl TaskStatus {
+    pub const ALL: [Self; 5] = [
+        Self::Enqueued,
+        Self::Processing,
+        Self::Succeeded,
+        Self::Failed,
+        Self::Canceled,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Enqueued => "enqueued",
+            Self::Processing => "processing",
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+            Self::Canceled => "canceled",
+        }
+    }
+}
+
+/// A page of tasks, along with its pagination metadata.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TaskList {
+    pub results: Vec<Task>,
+    /// The number of tasks matching the query, not only the ones in `results`.
+    pub total: u64,
+    pub limit: usize,
+    /// The id of the first task of the page.
+    pub from: Option<TaskId>,
+    /// The id to use as `from` to fetch the next page, if there is one.
+    pub next: Option<TaskId>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 #[cfg_attr(test, derive(proptest_derive::Arbitrary))]
 pub enum DocumentDeletion {
     Clear,
     Ids(Vec<String>),
+    /// Clears the documents matching the filter.
+    Filter(String),
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -173,9 +328,58 @@ pub enum TaskContent {
     },
     Dump {
         uid: String,
+        /// The indexes to dump, along with their tasks. Every index is dumped when `None`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        indexes: Option<Vec<String>>,
+    },
+    /// Writes the documents of an index matching `filter` as NDJSON in the file at `path`.
+    DocumentsExport {
+        index_uid: IndexUid,
+        #[cfg_attr(test, proptest(value = "PathBuf::from(\"export.ndjson\")"))]
+        path: PathBuf,
+        filter: Option<String>,
+    },
+    /// Cancels the given tasks. Cancelations are never queued, they are handled by the scheduler
+    /// itself, see `Scheduler::cancel_processing`.
+    #[cfg_attr(test, proptest(skip))]
+    TaskCancelation {
+        tasks: Vec<TaskId>,
+        /// Why the tasks are canceled, recorded on each canceled task.
+        #[serde(default)]
+        reason: Option<String>,
     },
 }
 
+impl TaskContent {
+    /// The name of every variant, as it is serialized.
+    pub const KINDS: &'static [&'static str] = &[
+        "DocumentAddition",
+        "DocumentDeletion",
+        "SettingsUpdate",
+        "IndexDeletion",
+        "IndexCreation",
+        "IndexUpdate",
+        "Dump",
+        "DocumentsExport",
+        "TaskCancelation",
+    ];
+
+    /// Returns the name of the variant, one of `KINDS`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            TaskContent::DocumentAddition { .. } => "DocumentAddition",
+            TaskContent::DocumentDeletion { .. } => "DocumentDeletion",
+            TaskContent::SettingsUpdate { .. } => "SettingsUpdate",
+            TaskContent::IndexDeletion { .. } => "IndexDeletion",
+            TaskContent::IndexCreation { .. } => "IndexCreation",
+            TaskContent::IndexUpdate { .. } => "IndexUpdate",
+            TaskContent::Dump { .. } => "Dump",
+            TaskContent::DocumentsExport { .. } => "DocumentsExport",
+            TaskContent::TaskCancelation { .. } => "TaskCancelation",
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use proptest::prelude::*;
@@ -192,4 +396,30 @@ mod test {
     pub(super) fn datetime_strategy() -> impl Strategy<Value = OffsetDateTime> {
         Just(OffsetDateTime::now_utc())
     }
+
+    #[test]
+    fn push_event_clamps_backward_clock() {
+        let created_at = OffsetDateTime::now_utc();
+        let mut task = Task {
+            id: 0,
+            content: TaskContent::IndexDeletion {
+                index_uid: IndexUid::new_unchecked("test"),
+            },
+            events: vec![TaskEvent::Created(created_at)],
+            retry_of: None,
+            priority: 0,
+        };
+
+        task.push_event(TaskEvent::Processing(created_at + time::Duration::SECOND));
+        // the clock jumps one hour backward before the task finishes.
+        task.push_event(TaskEvent::Succeeded {
+            result: TaskResult::Other,
+            timestamp: created_at - time::Duration::HOUR,
+        });
+
+        let started_at = task.events[1].timestamp();
+        let finished_at = task.events[2].timestamp();
+        assert!(!(finished_at - started_at).is_negative());
+        assert_eq!(finished_at, started_at);
+    }
 }
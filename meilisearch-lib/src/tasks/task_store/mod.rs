@@ -1,18 +1,20 @@
 mod store;
 
-use std::collections::HashSet;
-use std::io::{BufWriter, Write};
+use std::collections::{BTreeMap, HashSet};
+use std::io::{BufReader, BufWriter, Read, Write};
 use std::path::Path;
 use std::sync::Arc;
 
-use log::debug;
+use log::{debug, warn};
 use milli::heed::{Env, RwTxn};
-use time::OffsetDateTime;
+use roaring::RoaringBitmap;
+use serde::Deserialize;
+use time::{Date, OffsetDateTime};
 
 use super::batch::BatchContent;
 use super::error::TaskError;
 use super::scheduler::Processing;
-use super::task::{Task, TaskContent, TaskId};
+use super::task::{Task, TaskContent, TaskId, TaskStatus};
 use super::Result;
 use crate::tasks::task::TaskEvent;
 use crate::update_file_store::UpdateFileStore;
@@ -24,20 +26,48 @@ pub use store::Store;
 
 type FilterFn = Box<dyn Fn(&Task) -> bool + Sync + Send + 'static>;
 
+/// A change in the lifecycle of an index, that a task must have made to match a `TaskFilter`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IndexLifecycle {
+    Created,
+    Deleted,
+}
+
 /// Defines constraints to be applied when querying for Tasks from the store.
 #[derive(Default)]
 pub struct TaskFilter {
     indexes: Option<HashSet<String>>,
+    statuses: Option<HashSet<TaskStatus>>,
+    kinds: Option<HashSet<&'static str>>,
+    uids: Option<RoaringBitmap>,
+    index_lifecycle: Option<IndexLifecycle>,
     filter_fn: Option<FilterFn>,
 }
 
 impl TaskFilter {
     fn pass(&self, task: &Task) -> bool {
+        let matches_uid = self
+            .uids
+            .as_ref()
+            .map_or(true, |uids| uids.contains(task.id));
+        let matches_status = self
+            .statuses
+            .as_ref()
+            .map_or(true, |statuses| statuses.contains(&task.status()));
+        let matches_kind = self
+            .kinds
+            .as_ref()
+            .map_or(true, |kinds| kinds.contains(task.content.kind()));
         match task.index_uid() {
-            Some(index_uid) => self
-                .indexes
-                .as_ref()
-                .map_or(true, |indexes| indexes.contains(index_uid)),
+            Some(index_uid) => {
+                matches_uid
+                    && matches_status
+                    && matches_kind
+                    && self
+                        .indexes
+                        .as_ref()
+                        .map_or(true, |indexes| indexes.contains(index_uid))
+            }
             None => false,
         }
     }
@@ -46,6 +76,42 @@ impl TaskFilter {
         self.indexes.as_ref()
     }
 
+    fn filtered_statuses(&self) -> Option<&HashSet<TaskStatus>> {
+        self.statuses.as_ref()
+    }
+
+    fn filtered_kinds(&self) -> Option<&HashSet<&'static str>> {
+        self.kinds.as_ref()
+    }
+
+    fn filtered_uids(&self) -> Option<&RoaringBitmap> {
+        self.uids.as_ref()
+    }
+
+    /// Returns whether the filter restricts the tasks with one of the mappings of the store, so
+    /// that its candidates are only ids of existing tasks.
+    fn restricts_by_mapping(&self) -> bool {
+        self.indexes.is_some() || self.statuses.is_some() || self.kinds.is_some()
+    }
+
+    /// Adds task uids to the filter, so the filter must match one of these uids.
+    pub fn filter_uids(&mut self, uids: impl IntoIterator<Item = TaskId>) {
+        self.uids.get_or_insert_with(Default::default).extend(uids);
+    }
+
+    /// Adds a status to the filter, so the filter must match a task in one of these statuses.
+    pub fn filter_status(&mut self, status: TaskStatus) {
+        self.statuses
+            .get_or_insert_with(Default::default)
+            .insert(status);
+    }
+
+    /// Adds a kind, see `TaskContent::kind`, to the filter, so the filter must match a task of
+    /// one of these kinds.
+    pub fn filter_kind(&mut self, kind: &'static str) {
+        self.kinds.get_or_insert_with(Default::default).insert(kind);
+    }
+
     /// Adds an index to the filter, so the filter must match this index.
     pub fn filter_index(&mut self, index: String) {
         self.indexes
@@ -53,9 +119,116 @@ impl TaskFilter {
             .insert(index);
     }
 
+    /// Only matches the index creations that succeeded. Replaces `only_index_deleted`.
+    pub fn only_index_created(&mut self) {
+        self.index_lifecycle = Some(IndexLifecycle::Created);
+    }
+
+    /// Only matches the index deletions that succeeded. Replaces `only_index_created`.
+    pub fn only_index_deleted(&mut self) {
+        self.index_lifecycle = Some(IndexLifecycle::Deleted);
+    }
+
     pub fn filter_fn(&mut self, f: FilterFn) {
         self.filter_fn.replace(f);
     }
+
+    /// Returns whether the filter has constraints that can only be checked once the tasks are
+    /// loaded, see `post_filter`.
+    fn has_post_filter(&self) -> bool {
+        self.index_lifecycle.is_some() || self.filter_fn.is_some()
+    }
+
+    /// Returns whether the task matches the constraints that can only be checked once the task is
+    /// loaded, i.e. all of them but the indexes, the statuses, the kinds and the uids.
+    fn post_filter(&self, task: &Task) -> bool {
+        let matches_lifecycle = match self.index_lifecycle {
+            Some(lifecycle) => {
+                let matches_kind = match lifecycle {
+                    IndexLifecycle::Created => {
+                        matches!(task.content, TaskContent::IndexCreation { .. })
+                    }
+                    IndexLifecycle::Deleted => {
+                        matches!(task.content, TaskContent::IndexDeletion { .. })
+                    }
+                };
+                matches_kind && matches!(task.events.last(), Some(TaskEvent::Succeeded { .. }))
+            }
+            None => true,
+        };
+
+        matches_lifecycle && self.filter_fn.as_ref().map_or(true, |f| f(task))
+    }
+}
+
+/// The number of tasks in each status for a single day, see `TaskStore::tasks_summary_by_day`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DaySummary {
+    pub date: Date,
+    pub enqueued: u64,
+    pub processing: u64,
+    pub succeeded: u64,
+    pub failed: u64,
+    pub canceled: u64,
+}
+
+impl DaySummary {
+    fn new(date: Date) -> Self {
+        Self {
+            date,
+            enqueued: 0,
+            processing: 0,
+            succeeded: 0,
+            failed: 0,
+            canceled: 0,
+        }
+    }
+
+    fn count_mut(&mut self, status: SummaryStatus) -> &mut u64 {
+        match status {
+            SummaryStatus::Enqueued => &mut self.enqueued,
+            SummaryStatus::Processing => &mut self.processing,
+            SummaryStatus::Succeeded => &mut self.succeeded,
+            SummaryStatus::Failed => &mut self.failed,
+            SummaryStatus::Canceled => &mut self.canceled,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum SummaryStatus {
+    Enqueued,
+    Processing,
+    Succeeded,
+    Failed,
+    Canceled,
+}
+
+impl SummaryStatus {
+    /// Returns the day a task is counted on along with its status: finished tasks are counted on
+    /// the day they finished, the other ones on the day they were enqueued.
+    fn of(task: &Task) -> Option<(OffsetDateTime, Self)> {
+        let (status, event) = match task.events.last()? {
+            TaskEvent::Created(_) | TaskEvent::Batched { .. } => {
+                (Self::Enqueued, task.events.first()?)
+            }
+            TaskEvent::Processing(_) => (Self::Processing, task.events.first()?),
+            event @ TaskEvent::Succeeded { .. } => (Self::Succeeded, event),
+            event @ TaskEvent::Failed { .. } => (Self::Failed, event),
+            event @ TaskEvent::Canceled { .. } => (Self::Canceled, event),
+        };
+        Some((event.timestamp(), status))
+    }
+}
+
+/// The outcome of `TaskStore::load_dump`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoadedTasks {
+    /// The number of tasks replayed into the store.
+    pub replayed: usize,
+    /// The number of tasks that were skipped because their kind is unknown to this version,
+    /// which happens with dumps produced by a newer version.
+    pub skipped: usize,
 }
 
 pub struct TaskStore {
@@ -77,6 +250,19 @@ impl TaskStore {
     }
 
     pub async fn register(&self, content: TaskContent) -> Result<Task> {
+        self.register_with_priority(content, 0).await
+    }
+
+    /// Registers a task of the given `priority`. The enqueued tasks of a higher priority are
+    /// scheduled before the others, but never before the tasks enqueued before them on the same
+    /// index, so that the writes to an index are never reordered.
+    #[tracing::instrument(
+        name = "register",
+        level = "info",
+        skip_all,
+        fields(task_id, index_uid)
+    )]
+    pub async fn register_with_priority(&self, content: TaskContent, priority: u8) -> Result<Task> {
         debug!("registering update: {:?}", content);
         let store = self.store.clone();
         let task = tokio::task::spawn_blocking(move || -> Result<Task> {
@@ -87,6 +273,8 @@ impl TaskStore {
                 id: next_task_id,
                 content,
                 events: vec![created_at],
+                retry_of: None,
+                priority,
             };
 
             store.put(&mut txn, &task)?;
@@ -96,14 +284,100 @@ impl TaskStore {
         })
         .await??;
 
+        let span = tracing::Span::current();
+        span.record("task_id", &task.id);
+        if let Some(index_uid) = task.index_uid() {
+            span.record("index_uid", &index_uid);
+        }
+
         Ok(task)
     }
 
+    /// Registers a retry for each of the `(task id, content)` pairs of `retries`, in this order,
+    /// and returns the registered tasks. The retries are registered at once so that their uids
+    /// are contiguous. The retry of a task that is itself a retry is linked to the first task.
+    pub async fn register_retries(&self, retries: Vec<(TaskId, TaskContent)>) -> Result<Vec<Task>> {
+        let store = self.store.clone();
+        tokio::task::spawn_blocking(move || -> Result<Vec<Task>> {
+            let mut txn = store.wtxn()?;
+            let mut tasks = Vec::with_capacity(retries.len());
+            for (retried, content) in retries {
+                let (retry_of, priority) = match store.get(&txn, retried)? {
+                    Some(Task {
+                        retry_of: Some(first),
+                        priority,
+                        ..
+                    }) => (first, priority),
+                    Some(Task { priority, .. }) => (retried, priority),
+                    None => (retried, 0),
+                };
+                let task = Task {
+                    id: store.next_task_id(&mut txn)?,
+                    content,
+                    events: vec![TaskEvent::Created(OffsetDateTime::now_utc())],
+                    retry_of: Some(retry_of),
+                    priority,
+                };
+                store.put(&mut txn, &task)?;
+                tasks.push(task);
+            }
+            txn.commit()?;
+
+            Ok(tasks)
+        })
+        .await?
+    }
+
     pub fn register_raw_update(&self, wtxn: &mut RwTxn, task: &Task) -> Result<()> {
         self.store.put(wtxn, task)?;
         Ok(())
     }
 
+    /// Enqueues again the tasks that were batched or processing when Meilisearch stopped, by
+    /// removing their `Batched` and `Processing` events, and returns how many tasks were enqueued.
+    /// It must be called before the scheduler starts.
+    pub fn requeue_interrupted_tasks(&self) -> Result<usize> {
+        let mut txn = self.store.wtxn()?;
+        let mut count = 0;
+        for mut task in self.store.fetch_unfinished_tasks(&txn, None)? {
+            if task.requeue() {
+                self.store.put(&mut txn, &task)?;
+                count += 1;
+            }
+        }
+        txn.commit()?;
+
+        Ok(count)
+    }
+
+    /// Imports the JSON array of tasks read from `reader`, as written in the tasks of a dump, and
+    /// returns the number of imported tasks. The tasks must have distinct uids, all greater than
+    /// the uid of every existing task, otherwise nothing is imported.
+    pub async fn import_tasks(&self, reader: impl Read + Send + 'static) -> Result<usize> {
+        let store = self.store.clone();
+        let count = tokio::task::spawn_blocking(move || -> Result<usize> {
+            let mut tasks: Vec<Task> = serde_json::from_reader(BufReader::new(reader))
+                .map_err(TaskError::MalformedTaskList)?;
+            tasks.sort_by_key(|task| task.id);
+
+            let mut txn = store.wtxn()?;
+            let mut next_task_id = store.next_task_id(&mut txn)?;
+            for task in &tasks {
+                if task.id < next_task_id {
+                    return Err(TaskError::TaskUidAlreadyUsed(task.id));
+                }
+                store.put(&mut txn, task)?;
+                next_task_id = task.id + 1;
+            }
+            txn.commit()?;
+
+            Ok(tasks.len())
+        })
+        .await??;
+
+        Ok(count)
+    }
+
     pub async fn get_task(&self, id: TaskId, filter: Option<TaskFilter>) -> Result<Task> {
         let store = self.store.clone();
         let task = tokio::task::spawn_blocking(move || -> Result<_> {
@@ -123,6 +397,32 @@ impl TaskStore {
         }
     }
 
+    /// Deletes the finished tasks among `to_delete`, and returns the deleted tasks. The tasks that
+    /// are enqueued or processing can't be deleted and are left untouched, as well as the ids that
+    /// don't match any task.
+    pub async fn delete_tasks(&self, to_delete: RoaringBitmap) -> Result<Vec<Task>> {
+        let store = self.store.clone();
+        let deleted = tokio::task::spawn_blocking(move || -> Result<_> {
+            let mut txn = store.wtxn()?;
+            let mut deleted = Vec::new();
+            for id in &to_delete {
+                match store.get(&txn, id)? {
+                    Some(task) if task.is_finished() => {
+                        store.delete(&mut txn, &task)?;
+                        deleted.push(task);
+                    }
+                    _ => (),
+                }
+            }
+            txn.commit()?;
+
+            Ok(deleted)
+        })
+        .await??;
+
+        Ok(deleted)
+    }
+
     /// This methods takes a `Processing` which contains the next task ids to process, and returns
     /// the corresponding tasks along with the ownership to the passed processing.
     ///
@@ -137,7 +437,7 @@ impl TaskStore {
             let txn = store.rtxn()?;
 
             let content = match processing {
-                Processing::DocumentAdditions(ref ids) => {
+                Processing::DocumentAdditions(ref ids) | Processing::SettingsUpdates(ref ids) => {
                     let mut tasks = Vec::new();
 
                     for id in ids.iter() {
@@ -146,7 +446,11 @@ impl TaskStore {
                             .ok_or(TaskError::UnexistingTask(*id))?;
                         tasks.push(task);
                     }
-                    BatchContent::DocumentsAdditionBatch(tasks)
+
+                    match processing {
+                        Processing::SettingsUpdates(_) => BatchContent::SettingsUpdates(tasks),
+                        _ => BatchContent::DocumentsAdditionBatch(tasks),
+                    }
                 }
                 Processing::IndexUpdate(id) => {
                     let task = store.get(&txn, id)?.ok_or(TaskError::UnexistingTask(id))?;
@@ -197,6 +501,49 @@ impl TaskStore {
         .await?
     }
 
+    /// Returns the number of tasks registered before the task `id` that are not finished yet.
+    /// They are counted from the status to task ids mapping, without loading any task.
+    pub async fn count_unfinished_tasks_before(&self, id: TaskId) -> Result<u64> {
+        let store = self.store.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let mut filter = TaskFilter::default();
+            filter.filter_status(TaskStatus::Enqueued);
+            filter.filter_status(TaskStatus::Processing);
+
+            let txn = store.rtxn()?;
+            let mut unfinished = store.task_ids(&txn, Some(&filter))?;
+            unfinished.remove_range(id..);
+            Ok(unfinished.len())
+        })
+        .await?
+    }
+
+    /// Returns whether the tasks of each index are correctly indexed.
+    pub async fn verify_integrity(&self) -> Result<bool> {
+        let store = self.store.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let txn = store.rtxn()?;
+            store.verify_index_uid_task_ids(&txn)
+        })
+        .await?
+    }
+
+    /// Regenerates the tasks of each index from the content of the tasks, in a single write
+    /// transaction.
+    pub async fn rebuild_index_tasks(&self) -> Result<()> {
+        let store = self.store.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let mut txn = store.wtxn()?;
+            store.rebuild_index_uid_task_ids(&mut txn)?;
+            txn.commit()?;
+            Ok(())
+        })
+        .await?
+    }
+
     pub async fn list_tasks(
         &self,
         offset: Option<TaskId>,
@@ -213,15 +560,113 @@ impl TaskStore {
         .await?
     }
 
+    /// Returns the number of tasks matching the filter along with the requested page of tasks,
+    /// both computed from the same snapshot of the store.
+    pub async fn list_tasks_with_total(
+        &self,
+        offset: Option<TaskId>,
+        filter: Option<TaskFilter>,
+        limit: Option<usize>,
+    ) -> Result<(u64, Vec<Task>)> {
+        let store = self.store.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let txn = store.rtxn()?;
+            store.list_tasks_with_total(&txn, offset, filter, limit)
+        })
+        .await?
+    }
+
+    /// Returns the ids of the tasks matching the filter.
+    pub async fn task_ids(&self, filter: Option<TaskFilter>) -> Result<RoaringBitmap> {
+        let store = self.store.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let txn = store.rtxn()?;
+            store.task_ids(&txn, filter.as_ref())
+        })
+        .await?
+    }
+
+    /// Counts the tasks matching the filter by the key `key` returns for each of them, without
+    /// keeping the tasks in memory.
+    pub async fn count_tasks<K>(
+        &self,
+        filter: Option<TaskFilter>,
+        key: impl Fn(&Task) -> K + Send + 'static,
+    ) -> Result<BTreeMap<K, u64>>
+    where
+        K: Ord + Send + 'static,
+    {
+        let store = self.store.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let txn = store.rtxn()?;
+            store.count_tasks(&txn, filter, key)
+        })
+        .await?
+    }
+
+    /// Counts the tasks matching the filter by status and kind, without loading them when the
+    /// filter has no constraint on their content.
+    pub async fn count_by_status_and_kind(
+        &self,
+        filter: Option<TaskFilter>,
+    ) -> Result<BTreeMap<(TaskStatus, &'static str), u64>> {
+        let store = self.store.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let txn = store.rtxn()?;
+            store.count_by_status_and_kind(&txn, filter.as_ref())
+        })
+        .await?
+    }
+
+    /// Returns the number of tasks in each status for every day since `since`, in ascending
+    /// order of date. Finished tasks are counted on the day they finished, the other ones on the
+    /// day they were enqueued. Days without any task are omitted.
+    pub async fn tasks_summary_by_day(&self, since: OffsetDateTime) -> Result<Vec<DaySummary>> {
+        let counts = self
+            .count_tasks(None, move |task| {
+                SummaryStatus::of(task)
+                    .filter(|(instant, _)| *instant >= since)
+                    .map(|(instant, status)| (instant.date(), status))
+            })
+            .await?;
+
+        let mut summaries: Vec<DaySummary> = Vec::new();
+        for ((date, status), count) in counts.into_iter().filter_map(|(k, c)| Some((k?, c))) {
+            match summaries.last_mut() {
+                Some(summary) if summary.date == date => *summary.count_mut(status) += count,
+                _ => {
+                    let mut summary = DaySummary::new(date);
+                    *summary.count_mut(status) += count;
+                    summaries.push(summary);
+                }
+            }
+        }
+
+        Ok(summaries)
+    }
+
+    /// Dumps the tasks, and the content of the unfinished ones. When `indexes` is set, only the
+    /// tasks targeting these indexes are dumped.
     pub async fn dump(
         env: Arc<Env>,
         dir_path: impl AsRef<Path>,
         update_file_store: UpdateFileStore,
+        indexes: Option<Vec<String>>,
     ) -> Result<()> {
         let store = Self::new(env)?;
         let update_dir = dir_path.as_ref().join("updates");
         let updates_file = update_dir.join("data.jsonl");
-        let tasks = store.list_tasks(None, None, None).await?;
+        let mut tasks = store.list_tasks(None, None, None).await?;
+        if let Some(indexes) = indexes {
+            tasks.retain(|task| {
+                task.index_uid()
+                    .map_or(false, |uid| indexes.iter().any(|i| i == uid))
+            });
+        }
 
         let dir_path = dir_path.as_ref().to_path_buf();
         tokio::task::spawn_blocking(move || -> Result<()> {
@@ -247,7 +692,9 @@ impl TaskStore {
         Ok(())
     }
 
-    pub fn load_dump(src: impl AsRef<Path>, env: Arc<Env>) -> anyhow::Result<()> {
+    /// Replays the tasks of the dump into the task store. The tasks of an unknown kind are skipped
+    /// instead of failing the whole import.
+    pub fn load_dump(src: impl AsRef<Path>, env: Arc<Env>) -> anyhow::Result<LoadedTasks> {
         // create a dummy update field store, since it is not needed right now.
         let store = Self::new(env.clone())?;
 
@@ -255,30 +702,77 @@ impl TaskStore {
         let update_data = std::fs::File::open(&src_update_path.join("data.jsonl"))?;
         let update_data = std::io::BufReader::new(update_data);
 
-        let stream = serde_json::Deserializer::from_reader(update_data).into_iter::<Task>();
+        let stream =
+            serde_json::Deserializer::from_reader(update_data).into_iter::<serde_json::Value>();
 
+        let mut loaded = LoadedTasks {
+            replayed: 0,
+            skipped: 0,
+        };
         let mut wtxn = env.write_txn()?;
         for entry in stream {
-            store.register_raw_update(&mut wtxn, &entry?)?;
+            let entry = entry?;
+            let task = match Task::deserialize(&entry) {
+                Ok(task) => task,
+                Err(e) => match unknown_task_kind(&entry) {
+                    Some(kind) => {
+                        warn!(
+                            "Skipping task {} of the dump, its kind `{}` is unknown.",
+                            entry["id"], kind
+                        );
+                        loaded.skipped += 1;
+                        continue;
+                    }
+                    None => return Err(e.into()),
+                },
+            };
+            store.register_raw_update(&mut wtxn, &task)?;
+            loaded.replayed += 1;
         }
         wtxn.commit()?;
 
-        Ok(())
+        Ok(loaded)
+    }
+}
+
+/// Returns the kind of the content of a dumped task when it is not one of `TaskContent::KINDS`.
+fn unknown_task_kind(task: &serde_json::Value) -> Option<&str> {
+    let kind = match &task["content"] {
+        serde_json::Value::String(kind) => kind,
+        serde_json::Value::Object(content) if content.len() == 1 => content.keys().next()?,
+        _ => return None,
+    };
+
+    if TaskContent::KINDS.contains(&kind.as_str()) {
+        None
+    } else {
+        Some(kind)
     }
 }
 
 #[cfg(test)]
 pub mod test {
-    use crate::tasks::{scheduler::Processing, task_store::store::test::tmp_env};
+    use crate::index::Settings;
+    use crate::tasks::task::{DocumentDeletion, TaskResult};
+    use crate::tasks::{
+        scheduler::Processing,
+        task_store::store::test::{tmp_env, tmp_env_with_size},
+    };
 
     use super::*;
 
+    use std::path::PathBuf;
+    use std::sync::Mutex;
+
+    use meilisearch_types::error::{Code, ErrorCode};
     use meilisearch_types::index_uid::IndexUid;
+    use milli::update::IndexDocumentsMethod;
     use nelson::Mocker;
     use proptest::{
         strategy::Strategy,
         test_runner::{Config, TestRunner},
     };
+    use uuid::Uuid;
 
     pub enum MockTaskStore {
         Real(TaskStore),
@@ -303,8 +797,9 @@ pub mod test {
             env: Arc<milli::heed::Env>,
             path: impl AsRef<Path>,
             update_file_store: UpdateFileStore,
+            indexes: Option<Vec<String>>,
         ) -> Result<()> {
-            TaskStore::dump(env, path, update_file_store).await
+            TaskStore::dump(env, path, update_file_store, indexes).await
         }
 
         pub fn mock(mocker: Mocker) -> Self {
@@ -327,6 +822,13 @@ pub mod test {
             }
         }
 
+        pub async fn delete_tasks(&self, to_delete: RoaringBitmap) -> Result<Vec<Task>> {
+            match self {
+                Self::Real(s) => s.delete_tasks(to_delete).await,
+                Self::Mock(m) => unsafe { m.get("delete_tasks").call(to_delete) },
+            }
+        }
+
         pub async fn get_processing_tasks(
             &self,
             tasks: Processing,
@@ -344,6 +846,27 @@ pub mod test {
             }
         }
 
+        pub async fn count_unfinished_tasks_before(&self, id: TaskId) -> Result<u64> {
+            match self {
+                Self::Real(s) => s.count_unfinished_tasks_before(id).await,
+                Self::Mock(m) => unsafe { m.get("count_unfinished_tasks_before").call(id) },
+            }
+        }
+
+        pub async fn verify_integrity(&self) -> Result<bool> {
+            match self {
+                Self::Real(s) => s.verify_integrity().await,
+                Self::Mock(m) => unsafe { m.get("verify_integrity").call(()) },
+            }
+        }
+
+        pub async fn rebuild_index_tasks(&self) -> Result<()> {
+            match self {
+                Self::Real(s) => s.rebuild_index_tasks().await,
+                Self::Mock(m) => unsafe { m.get("rebuild_index_tasks").call(()) },
+            }
+        }
+
         pub async fn list_tasks(
             &self,
             from: Option<TaskId>,
@@ -356,9 +879,91 @@ pub mod test {
             }
         }
 
+        pub async fn list_tasks_with_total(
+            &self,
+            from: Option<TaskId>,
+            filter: Option<TaskFilter>,
+            limit: Option<usize>,
+        ) -> Result<(u64, Vec<Task>)> {
+            match self {
+                Self::Real(s) => s.list_tasks_with_total(from, filter, limit).await,
+                Self::Mock(m) => unsafe {
+                    m.get("list_tasks_with_total").call((from, filter, limit))
+                },
+            }
+        }
+
+        pub async fn task_ids(&self, filter: Option<TaskFilter>) -> Result<RoaringBitmap> {
+            match self {
+                Self::Real(s) => s.task_ids(filter).await,
+                Self::Mock(m) => unsafe { m.get("task_ids").call(filter) },
+            }
+        }
+
+        pub async fn count_tasks<K>(
+            &self,
+            filter: Option<TaskFilter>,
+            key: impl Fn(&Task) -> K + Send + 'static,
+        ) -> Result<BTreeMap<K, u64>>
+        where
+            K: Ord + Send + 'static,
+        {
+            match self {
+                Self::Real(s) => s.count_tasks(filter, key).await,
+                Self::Mock(_m) => todo!(),
+            }
+        }
+
+        pub async fn count_by_status_and_kind(
+            &self,
+            filter: Option<TaskFilter>,
+        ) -> Result<BTreeMap<(TaskStatus, &'static str), u64>> {
+            match self {
+                Self::Real(s) => s.count_by_status_and_kind(filter).await,
+                Self::Mock(m) => unsafe { m.get("count_by_status_and_kind").call(filter) },
+            }
+        }
+
+        pub async fn tasks_summary_by_day(&self, since: OffsetDateTime) -> Result<Vec<DaySummary>> {
+            match self {
+                Self::Real(s) => s.tasks_summary_by_day(since).await,
+                Self::Mock(m) => unsafe { m.get("tasks_summary_by_day").call(since) },
+            }
+        }
+
+        pub async fn register_retries(
+            &self,
+            retries: Vec<(TaskId, TaskContent)>,
+        ) -> Result<Vec<Task>> {
+            match self {
+                Self::Real(s) => s.register_retries(retries).await,
+                Self::Mock(m) => unsafe { m.get("register_retries").call(retries) },
+            }
+        }
+
         pub async fn register(&self, content: TaskContent) -> Result<Task> {
             match self {
                 Self::Real(s) => s.register(content).await,
+                Self::Mock(m) => unsafe { m.get("register").call(content) },
+            }
+        }
+
+        pub async fn register_with_priority(
+            &self,
+            content: TaskContent,
+            priority: u8,
+        ) -> Result<Task> {
+            match self {
+                Self::Real(s) => s.register_with_priority(content, priority).await,
+                Self::Mock(m) => unsafe {
+                    m.get("register_with_priority").call((content, priority))
+                },
+            }
+        }
+
+        pub async fn import_tasks(&self, reader: impl Read + Send + 'static) -> Result<usize> {
+            match self {
+                Self::Real(s) => s.import_tasks(reader).await,
                 Self::Mock(_m) => todo!(),
             }
         }
@@ -370,7 +975,14 @@ pub mod test {
             }
         }
 
-        pub fn load_dump(path: impl AsRef<Path>, env: Arc<Env>) -> anyhow::Result<()> {
+        pub fn requeue_interrupted_tasks(&self) -> Result<usize> {
+            match self {
+                Self::Real(s) => s.requeue_interrupted_tasks(),
+                Self::Mock(m) => unsafe { m.get("requeue_interrupted_tasks").call(()) },
+            }
+        }
+
+        pub fn load_dump(path: impl AsRef<Path>, env: Arc<Env>) -> anyhow::Result<LoadedTasks> {
             TaskStore::load_dump(path, env)
         }
     }
@@ -391,6 +1003,8 @@ pub mod test {
                 index_uid: IndexUid::new_unchecked("test"),
             },
             events: Vec::new(),
+            retry_of: None,
+            priority: 0,
         };
 
         let mut runner = TestRunner::new(Config::default());
@@ -417,4 +1031,651 @@ pub mod test {
             })
             .unwrap();
     }
+
+    #[actix_rt::test]
+    async fn register_in_full_task_database() {
+        let tmp = tmp_env_with_size(4096 * 16);
+        let store = TaskStore::new(tmp.env()).unwrap();
+
+        let ids: Vec<String> = (0..100).map(|i| format!("document-{}", i)).collect();
+        let error = loop {
+            let content = TaskContent::DocumentDeletion {
+                index_uid: IndexUid::new_unchecked("test"),
+                deletion: DocumentDeletion::Ids(ids.clone()),
+            };
+            match store.register(content).await {
+                Ok(task) => assert!(task.id < 1000, "the task database never got full"),
+                Err(error) => break error,
+            }
+        };
+
+        assert!(matches!(error, TaskError::TaskDatabaseFull), "{}", error);
+        assert!(matches!(error.error_code(), Code::DatabaseSizeLimitReached));
+    }
+
+    /// Returns the position of the variant of `content` among the variants of `TaskContent`. This
+    /// match is exhaustive on purpose: a new variant must be given a position here, and then a
+    /// sample in `every_task_content` for `dump_and_load_every_task_content` to pass.
+    fn task_content_position(content: &TaskContent) -> usize {
+        match content {
+            TaskContent::DocumentAddition { .. } => 0,
+            TaskContent::DocumentDeletion { .. } => 1,
+            TaskContent::SettingsUpdate { .. } => 2,
+            TaskContent::IndexDeletion { .. } => 3,
+            TaskContent::IndexCreation { .. } => 4,
+            TaskContent::IndexUpdate { .. } => 5,
+            TaskContent::Dump { .. } => 6,
+            TaskContent::DocumentsExport { .. } => 7,
+            TaskContent::TaskCancelation { .. } => 8,
+        }
+    }
+
+    const TASK_CONTENT_VARIANTS: usize = 9;
+
+    fn every_task_content() -> Vec<TaskContent> {
+        let index_uid = IndexUid::new_unchecked("test");
+        vec![
+            TaskContent::DocumentAddition {
+                index_uid: index_uid.clone(),
+                content_uuid: Uuid::new_v4(),
+                merge_strategy: IndexDocumentsMethod::UpdateDocuments,
+                primary_key: Some("id".to_string()),
+                documents_count: 12,
+                allow_index_creation: false,
+            },
+            TaskContent::DocumentDeletion {
+                index_uid: index_uid.clone(),
+                deletion: DocumentDeletion::Ids(vec!["1".to_string(), "2".to_string()]),
+            },
+            TaskContent::SettingsUpdate {
+                index_uid: index_uid.clone(),
+                settings: Settings::default(),
+                is_deletion: true,
+                allow_index_creation: true,
+            },
+            TaskContent::IndexDeletion {
+                index_uid: index_uid.clone(),
+            },
+            TaskContent::IndexCreation {
+                index_uid: index_uid.clone(),
+                primary_key: Some("id".to_string()),
+            },
+            TaskContent::IndexUpdate {
+                index_uid: index_uid.clone(),
+                primary_key: None,
+            },
+            TaskContent::Dump {
+                uid: "20221018-000000000".to_string(),
+                indexes: None,
+            },
+            TaskContent::DocumentsExport {
+                index_uid,
+                path: PathBuf::from("export.ndjson"),
+                filter: Some("id > 1".to_string()),
+            },
+            TaskContent::TaskCancelation {
+                tasks: vec![0, 1],
+                reason: None,
+            },
+        ]
+    }
+
+    #[actix_rt::test]
+    async fn dump_and_load_every_task_content() {
+        let contents = every_task_content();
+        let positions: HashSet<_> = contents.iter().map(task_content_position).collect();
+        assert_eq!(
+            positions,
+            (0..TASK_CONTENT_VARIANTS).collect(),
+            "every variant of `TaskContent` must be covered by `every_task_content`"
+        );
+        assert_eq!(TaskContent::KINDS.len(), TASK_CONTENT_VARIANTS);
+        for content in &contents {
+            let task = serde_json::json!({ "content": content });
+            assert_eq!(unknown_task_kind(&task), None, "{:?}", content);
+            assert!(
+                task["content"].get(content.kind()).is_some(),
+                "{:?}",
+                content
+            );
+        }
+
+        let tmp = tmp_env();
+        let store = TaskStore::new(tmp.env()).unwrap();
+        let now = OffsetDateTime::now_utc();
+        let tasks: Vec<_> = contents
+            .into_iter()
+            .enumerate()
+            .map(|(id, content)| Task {
+                id: id as TaskId,
+                content,
+                events: vec![
+                    TaskEvent::Created(now),
+                    TaskEvent::succeeded(TaskResult::Other),
+                ],
+                retry_of: None,
+                priority: 0,
+            })
+            .collect();
+        store.update_tasks(tasks.clone()).await.unwrap();
+
+        let dump_dir = tempfile::tempdir().unwrap();
+        let update_file_store = UpdateFileStore::new(dump_dir.path()).unwrap();
+        TaskStore::dump(tmp.env(), dump_dir.path(), update_file_store, None)
+            .await
+            .unwrap();
+
+        let dst = tmp_env();
+        assert_eq!(
+            TaskStore::load_dump(dump_dir.path(), dst.env()).unwrap(),
+            LoadedTasks {
+                replayed: TASK_CONTENT_VARIANTS,
+                skipped: 0,
+            }
+        );
+
+        let loaded = TaskStore::new(dst.env()).unwrap();
+        for task in tasks {
+            assert_eq!(loaded.get_task(task.id, None).await.unwrap(), task);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn dump_and_load_canceled_tasks() {
+        let tmp = tmp_env();
+        let store = TaskStore::new(tmp.env()).unwrap();
+        let now = OffsetDateTime::now_utc();
+
+        let canceled = Task {
+            id: 0,
+            content: TaskContent::IndexCreation {
+                primary_key: None,
+                index_uid: IndexUid::new_unchecked("test"),
+            },
+            events: vec![
+                TaskEvent::Created(now),
+                TaskEvent::Canceled {
+                    canceled_by: 1,
+                    reason: Some("wrong index".to_string()),
+                    timestamp: now,
+                },
+            ],
+            retry_of: None,
+            priority: 0,
+        };
+        let cancelation = Task {
+            id: 1,
+            content: TaskContent::TaskCancelation {
+                tasks: vec![0],
+                reason: Some("wrong index".to_string()),
+            },
+            events: vec![
+                TaskEvent::Created(now),
+                TaskEvent::succeeded(TaskResult::TaskCancelation { canceled_tasks: 1 }),
+            ],
+            retry_of: None,
+            priority: 0,
+        };
+        let tasks = vec![canceled, cancelation];
+        store.update_tasks(tasks.clone()).await.unwrap();
+
+        let dump_dir = tempfile::tempdir().unwrap();
+        let update_file_store = UpdateFileStore::new(dump_dir.path()).unwrap();
+        TaskStore::dump(tmp.env(), dump_dir.path(), update_file_store, None)
+            .await
+            .unwrap();
+
+        let dst = tmp_env();
+        assert_eq!(
+            TaskStore::load_dump(dump_dir.path(), dst.env())
+                .unwrap()
+                .replayed,
+            2
+        );
+
+        let loaded = TaskStore::new(dst.env()).unwrap();
+        assert_eq!(loaded.get_task(0, None).await.unwrap(), tasks[0]);
+        assert_eq!(loaded.get_task(1, None).await.unwrap(), tasks[1]);
+        assert!(loaded
+            .fetch_unfinished_tasks(None)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn load_dump_skips_unknown_task_kinds() {
+        let dump_dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dump_dir.path().join("updates")).unwrap();
+        let created = r#""events": [{ "Created": "2022-10-18T00:00:00Z" }]"#;
+        let tasks = [
+            format!(
+                r#"{{ "id": 0, "content": {{ "IndexDeletion": {{ "index_uid": "test" }} }}, {} }}"#,
+                created
+            ),
+            format!(
+                r#"{{ "id": 1, "content": {{ "IndexSwap": {{ "swaps": [] }} }}, {} }}"#,
+                created
+            ),
+            format!(
+                r#"{{ "id": 2, "content": {{ "Dump": {{ "uid": "20221018-000000000" }} }}, {} }}"#,
+                created
+            ),
+        ];
+        std::fs::write(dump_dir.path().join("updates/data.jsonl"), tasks.join("\n")).unwrap();
+
+        let dst = tmp_env();
+        assert_eq!(
+            TaskStore::load_dump(dump_dir.path(), dst.env()).unwrap(),
+            LoadedTasks {
+                replayed: 2,
+                skipped: 1,
+            }
+        );
+
+        let loaded = TaskStore::new(dst.env()).unwrap();
+        let ids: Vec<_> = loaded
+            .list_tasks(None, None, None)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|task| task.id)
+            .collect();
+        assert_eq!(ids, [2, 0]);
+    }
+
+    #[test]
+    fn load_dump_fails_on_malformed_tasks() {
+        let dump_dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dump_dir.path().join("updates")).unwrap();
+        // a known kind with a missing field is not skipped.
+        std::fs::write(
+            dump_dir.path().join("updates/data.jsonl"),
+            r#"{ "id": 0, "content": { "IndexDeletion": {} }, "events": [] }"#,
+        )
+        .unwrap();
+
+        let dst = tmp_env();
+        assert!(TaskStore::load_dump(dump_dir.path(), dst.env()).is_err());
+    }
+
+    #[actix_rt::test]
+    async fn import_tasks_from_json() {
+        let tmp = tmp_env();
+        let store = TaskStore::new(tmp.env()).unwrap();
+        store
+            .register(TaskContent::IndexCreation {
+                primary_key: None,
+                index_uid: IndexUid::new_unchecked("catto"),
+            })
+            .await
+            .unwrap();
+
+        let now = OffsetDateTime::now_utc();
+        let mut finished = Task {
+            id: 1,
+            content: TaskContent::IndexCreation {
+                index_uid: IndexUid::new_unchecked("doggo"),
+                primary_key: Some("id".to_string()),
+            },
+            events: vec![TaskEvent::Created(now)],
+            retry_of: None,
+            priority: 0,
+        };
+        finished
+            .events
+            .push(TaskEvent::succeeded(TaskResult::Other));
+        let enqueued = Task {
+            id: 2,
+            content: TaskContent::IndexDeletion {
+                index_uid: IndexUid::new_unchecked("doggo"),
+            },
+            events: vec![TaskEvent::Created(now)],
+            retry_of: None,
+            priority: 0,
+        };
+        let tasks = serde_json::to_string(&[enqueued, finished]).unwrap();
+        let count = store
+            .import_tasks(std::io::Cursor::new(tasks.clone()))
+            .await
+            .unwrap();
+        assert_eq!(count, 2);
+
+        let mut filter = TaskFilter::default();
+        filter.filter_index("doggo".to_string());
+        let imported = store.list_tasks(None, Some(filter), None).await.unwrap();
+        assert_eq!(imported.iter().map(|t| t.id).collect::<Vec<_>>(), [2, 1]);
+        assert!(imported[1].is_finished());
+        assert!(!imported[0].is_finished());
+        assert_eq!(
+            store.fetch_unfinished_tasks(Some(1)).await.unwrap(),
+            [imported[0].clone()]
+        );
+
+        // The uids of the imported tasks are already used.
+        let error = store
+            .import_tasks(std::io::Cursor::new(tasks))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, TaskError::TaskUidAlreadyUsed(1)));
+        assert!(matches!(error.error_code(), Code::BadRequest));
+
+        let error = store
+            .import_tasks(std::io::Cursor::new("[{ \"id\": 3 }]"))
+            .await
+            .unwrap_err();
+        assert!(matches!(error.error_code(), Code::MalformedPayload));
+
+        assert_eq!(store.list_tasks(None, None, None).await.unwrap().len(), 3);
+    }
+
+    #[actix_rt::test]
+    async fn requeue_interrupted_tasks() {
+        let tmp = tmp_env();
+        let store = TaskStore::new(tmp.env()).unwrap();
+
+        let now = OffsetDateTime::now_utc();
+        let gen_task = |id, events| Task {
+            id,
+            content: TaskContent::IndexDeletion {
+                index_uid: IndexUid::new_unchecked("doggos"),
+            },
+            events,
+            retry_of: None,
+            priority: 0,
+        };
+        let batched = TaskEvent::Batched {
+            timestamp: now,
+            batch_id: 0,
+        };
+        let tasks = vec![
+            gen_task(
+                0,
+                vec![
+                    TaskEvent::Created(now),
+                    batched.clone(),
+                    TaskEvent::Processing(now),
+                    TaskEvent::succeeded(TaskResult::Other),
+                ],
+            ),
+            gen_task(
+                1,
+                vec![
+                    TaskEvent::Created(now),
+                    batched.clone(),
+                    TaskEvent::Processing(now),
+                ],
+            ),
+            gen_task(2, vec![TaskEvent::Created(now), batched]),
+            gen_task(3, vec![TaskEvent::Created(now)]),
+        ];
+        let mut txn = store.store.wtxn().unwrap();
+        for task in &tasks {
+            store.register_raw_update(&mut txn, task).unwrap();
+        }
+        txn.commit().unwrap();
+
+        assert_eq!(store.requeue_interrupted_tasks().unwrap(), 2);
+
+        let enqueued = store.fetch_unfinished_tasks(None).await.unwrap();
+        assert_eq!(enqueued.iter().map(|t| t.id).collect::<Vec<_>>(), [1, 2, 3]);
+        assert!(enqueued
+            .iter()
+            .all(|t| t.events == [TaskEvent::Created(now)]));
+        // the finished tasks are left untouched.
+        assert_eq!(store.get_task(0, None).await.unwrap(), tasks[0]);
+
+        assert_eq!(store.requeue_interrupted_tasks().unwrap(), 0);
+    }
+
+    #[actix_rt::test]
+    async fn delete_only_finished_tasks() {
+        let tmp = tmp_env();
+        let store = TaskStore::new(tmp.env()).unwrap();
+
+        let now = OffsetDateTime::now_utc();
+        let gen_task = |id, events| Task {
+            id,
+            content: TaskContent::IndexDeletion {
+                index_uid: IndexUid::new_unchecked("doggos"),
+            },
+            events,
+            retry_of: None,
+            priority: 0,
+        };
+        let tasks = vec![
+            gen_task(0, vec![TaskEvent::Created(now)]),
+            gen_task(1, vec![TaskEvent::Created(now), TaskEvent::Processing(now)]),
+            gen_task(
+                2,
+                vec![
+                    TaskEvent::Created(now),
+                    TaskEvent::succeeded(TaskResult::Other),
+                ],
+            ),
+            gen_task(
+                3,
+                vec![
+                    TaskEvent::Created(now),
+                    TaskEvent::Canceled {
+                        canceled_by: 4,
+                        reason: None,
+                        timestamp: now,
+                    },
+                ],
+            ),
+        ];
+        let mut txn = store.store.wtxn().unwrap();
+        for task in &tasks {
+            store.register_raw_update(&mut txn, task).unwrap();
+        }
+        txn.commit().unwrap();
+
+        let deleted = store
+            .delete_tasks(RoaringBitmap::from_iter([0, 1, 2, 3, 42]))
+            .await
+            .unwrap();
+        assert_eq!(deleted, tasks[2..]);
+
+        let remaining = store.list_tasks(None, None, None).await.unwrap();
+        assert_eq!(remaining.iter().map(|t| t.id).collect::<Vec<_>>(), [1, 0]);
+
+        let txn = store.store.rtxn().unwrap();
+        assert!(store.store.verify_index_uid_task_ids(&txn).unwrap());
+    }
+
+    #[actix_rt::test]
+    async fn count_unfinished_tasks_before() {
+        let tmp = tmp_env();
+        let store = TaskStore::new(tmp.env()).unwrap();
+
+        let content = || TaskContent::IndexDeletion {
+            index_uid: IndexUid::new_unchecked("doggos"),
+        };
+        let mut first = store.register(content()).await.unwrap();
+        store.register(content()).await.unwrap();
+        let last = store.register(content()).await.unwrap();
+
+        assert_eq!(
+            store.count_unfinished_tasks_before(first.id).await.unwrap(),
+            0
+        );
+        assert_eq!(
+            store.count_unfinished_tasks_before(last.id).await.unwrap(),
+            2
+        );
+
+        // the finished tasks are not in the way anymore.
+        first.push_event(TaskEvent::succeeded(TaskResult::Other));
+        store.update_tasks(vec![first]).await.unwrap();
+        assert_eq!(
+            store.count_unfinished_tasks_before(last.id).await.unwrap(),
+            1
+        );
+    }
+
+    #[actix_rt::test]
+    async fn tasks_summary_by_day() {
+        let tmp = tmp_env();
+        let store = TaskStore::new(tmp.env()).unwrap();
+
+        let today = OffsetDateTime::now_utc();
+        let yesterday = today - time::Duration::days(1);
+        let long_ago = today - time::Duration::days(10);
+        let gen_task = |id: TaskId, events: Vec<TaskEvent>| Task {
+            id,
+            content: TaskContent::IndexDeletion {
+                index_uid: IndexUid::new_unchecked("doggos"),
+            },
+            events,
+            retry_of: None,
+            priority: 0,
+        };
+        let succeeded_at = |timestamp| TaskEvent::Succeeded {
+            result: TaskResult::Other,
+            timestamp,
+        };
+        let tasks = vec![
+            // too old to be counted.
+            gen_task(
+                0,
+                vec![TaskEvent::Created(long_ago), succeeded_at(long_ago)],
+            ),
+            // enqueued long ago but finished yesterday.
+            gen_task(
+                1,
+                vec![TaskEvent::Created(long_ago), succeeded_at(yesterday)],
+            ),
+            gen_task(
+                2,
+                vec![
+                    TaskEvent::Created(yesterday),
+                    TaskEvent::Failed {
+                        error: TaskError::UnexistingTask(2).into(),
+                        timestamp: yesterday,
+                    },
+                ],
+            ),
+            // enqueued yesterday but finished today.
+            gen_task(3, vec![TaskEvent::Created(yesterday), succeeded_at(today)]),
+            gen_task(
+                4,
+                vec![TaskEvent::Created(yesterday), TaskEvent::Processing(today)],
+            ),
+            gen_task(5, vec![TaskEvent::Created(today)]),
+            gen_task(
+                6,
+                vec![
+                    TaskEvent::Created(today),
+                    TaskEvent::Canceled {
+                        canceled_by: 7,
+                        reason: None,
+                        timestamp: today,
+                    },
+                ],
+            ),
+        ];
+        let mut txn = store.store.wtxn().unwrap();
+        for task in &tasks {
+            store.register_raw_update(&mut txn, task).unwrap();
+        }
+        txn.commit().unwrap();
+
+        let summaries = store
+            .tasks_summary_by_day(today - time::Duration::days(2))
+            .await
+            .unwrap();
+        assert_eq!(
+            summaries,
+            vec![
+                DaySummary {
+                    date: yesterday.date(),
+                    enqueued: 0,
+                    processing: 1,
+                    succeeded: 1,
+                    failed: 1,
+                    canceled: 0,
+                },
+                DaySummary {
+                    date: today.date(),
+                    enqueued: 1,
+                    processing: 0,
+                    succeeded: 1,
+                    failed: 0,
+                    canceled: 1,
+                },
+            ]
+        );
+    }
+
+    /// Subscriber recording the name and the fields of every span that is created.
+    #[derive(Default, Clone)]
+    struct SpanRecorder {
+        spans: Arc<Mutex<Vec<(&'static str, BTreeMap<&'static str, String>)>>>,
+    }
+
+    struct FieldVisitor<'a>(&'a mut BTreeMap<&'static str, String>);
+
+    impl tracing::field::Visit for FieldVisitor<'_> {
+        fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+            self.0.insert(field.name(), value.to_string());
+        }
+
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.0.insert(field.name(), format!("{:?}", value));
+        }
+    }
+
+    impl tracing::Subscriber for SpanRecorder {
+        fn enabled(&self, _metadata: &tracing::Metadata) -> bool {
+            true
+        }
+
+        fn new_span(&self, attrs: &tracing::span::Attributes) -> tracing::span::Id {
+            let mut fields = BTreeMap::new();
+            attrs.record(&mut FieldVisitor(&mut fields));
+            let mut spans = self.spans.lock().unwrap();
+            spans.push((attrs.metadata().name(), fields));
+            tracing::span::Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, span: &tracing::span::Id, values: &tracing::span::Record) {
+            let mut spans = self.spans.lock().unwrap();
+            let (_, fields) = &mut spans[span.into_u64() as usize - 1];
+            values.record(&mut FieldVisitor(fields));
+        }
+
+        fn record_follows_from(&self, _span: &tracing::span::Id, _follows: &tracing::span::Id) {}
+
+        fn event(&self, _event: &tracing::Event) {}
+
+        fn enter(&self, _span: &tracing::span::Id) {}
+
+        fn exit(&self, _span: &tracing::span::Id) {}
+    }
+
+    #[actix_rt::test]
+    async fn register_is_instrumented() {
+        let recorder = SpanRecorder::default();
+        let _guard = tracing::subscriber::set_default(recorder.clone());
+        // other tests may have registered the callsites while no subscriber was interested.
+        tracing::callsite::rebuild_interest_cache();
+
+        let tmp = tmp_env();
+        let store = TaskStore::new(tmp.env()).unwrap();
+        let task = store
+            .register(TaskContent::IndexDeletion {
+                index_uid: IndexUid::new_unchecked("doggos"),
+            })
+            .await
+            .unwrap();
+
+        let spans = recorder.spans.lock().unwrap();
+        let (_, fields) = spans
+            .iter()
+            .find(|(name, _)| *name == "register")
+            .expect("no register span");
+        assert_eq!(fields["task_id"], task.id.to_string());
+        assert_eq!(fields["index_uid"], "doggos");
+    }
 }
@@ -3,9 +3,13 @@
 type BEU32 = milli::heed::zerocopy::U32<milli::heed::byteorder::BE>;
 
 const INDEX_UIDS_TASK_IDS: &str = "index-uids-task-ids";
+const STATUS_TASK_IDS: &str = "status-task-ids";
+const KIND_TASK_IDS: &str = "kind-task-ids";
 const TASKS: &str = "tasks";
+const META: &str = "tasks-meta";
+const NEXT_TASK_ID_KEY: &str = "next-task-id";
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap};
 use std::ops::Bound::{Excluded, Unbounded};
 use std::result::Result as StdResult;
 use std::sync::Arc;
@@ -15,7 +19,7 @@ use milli::heed::{Database, Env, RoTxn, RwTxn};
 use milli::heed_codec::RoaringBitmapCodec;
 use roaring::RoaringBitmap;
 
-use crate::tasks::task::{Task, TaskId};
+use crate::tasks::task::{Task, TaskContent, TaskId, TaskStatus};
 
 use super::super::Result;
 use super::TaskFilter;
@@ -24,7 +28,14 @@ pub struct Store {
     env: Arc<Env>,
     /// Maps an index uid to the set of tasks ids associated to it.
     index_uid_task_ids: Database<Str, RoaringBitmapCodec>,
+    /// Maps a task status to the set of ids of the tasks in this status.
+    status_task_ids: Database<Str, RoaringBitmapCodec>,
+    /// Maps a task kind, see `TaskContent::kind`, to the set of ids of the tasks of this kind.
+    kind_task_ids: Database<Str, RoaringBitmapCodec>,
     tasks: Database<OwnedType<BEU32>, SerdeJson<Task>>,
+    /// Holds the id of the next task, which is never given to another task even when the tasks
+    /// with the highest ids are deleted.
+    meta: Database<Str, OwnedType<BEU32>>,
 }
 
 impl Drop for Store {
@@ -43,13 +54,28 @@ impl Store {
     /// queue with the `reset_and_return_unfinished_update` method.
     pub fn new(env: Arc<milli::heed::Env>) -> Result<Self> {
         let index_uid_task_ids = env.create_database(Some(INDEX_UIDS_TASK_IDS))?;
+        let status_task_ids = env.create_database(Some(STATUS_TASK_IDS))?;
+        let kind_task_ids = env.create_database(Some(KIND_TASK_IDS))?;
         let tasks = env.create_database(Some(TASKS))?;
+        let meta = env.create_database(Some(META))?;
 
-        Ok(Self {
+        let store = Self {
             env,
             index_uid_task_ids,
+            status_task_ids,
+            kind_task_ids,
             tasks,
-        })
+            meta,
+        };
+
+        // The tasks stored before the status and kind mappings existed are added to them once.
+        let mut txn = store.wtxn()?;
+        if store.status_task_ids.is_empty(&txn)? && !store.tasks.is_empty(&txn)? {
+            store.rebuild_status_and_kind_task_ids(&mut txn)?;
+        }
+        txn.commit()?;
+
+        Ok(store)
     }
 
     pub fn wtxn(&self) -> Result<RwTxn> {
@@ -64,29 +90,37 @@ impl Store {
     ///
     /// The required `mut txn` acts as a reservation system. It guarantees that as long as you commit
     /// the task to the store in the same transaction, no one else will have this task id.
+    ///
+    /// The ids of deleted tasks are never given again, the stores written before the next id was
+    /// recorded fall back on the id following the last task.
     pub fn next_task_id(&self, txn: &mut RwTxn) -> Result<TaskId> {
-        let id = self
+        let recorded = self.meta.get(txn, NEXT_TASK_ID_KEY)?.map(|id| id.get());
+        let after_last = self
             .tasks
             .lazily_decode_data()
             .last(txn)?
-            .map(|(id, _)| id.get() + 1)
-            .unwrap_or(0);
-        Ok(id)
+            .map(|(id, _)| id.get() + 1);
+        Ok(recorded.max(after_last).unwrap_or(0))
     }
 
     pub fn put(&self, txn: &mut RwTxn, task: &Task) -> Result<()> {
+        if task.id >= self.next_task_id(txn)? {
+            self.meta
+                .put(txn, NEXT_TASK_ID_KEY, &BEU32::new(task.id + 1))?;
+        }
         self.tasks.put(txn, &BEU32::new(task.id), task)?;
         // only add the task to the indexes index if it has an index_uid
         if let Some(index_uid) = task.index_uid() {
-            let mut tasks_set = self
-                .index_uid_task_ids
-                .get(txn, index_uid)?
-                .unwrap_or_default();
-
-            tasks_set.insert(task.id);
+            insert_task_id(txn, self.index_uid_task_ids, index_uid, task.id)?;
+        }
 
-            self.index_uid_task_ids.put(txn, index_uid, &tasks_set)?;
+        // The task may be an update of a task in another status.
+        let status = task.status();
+        for other in TaskStatus::ALL.iter().filter(|other| **other != status) {
+            remove_task_id(txn, self.status_task_ids, other.as_str(), task.id)?;
         }
+        insert_task_id(txn, self.status_task_ids, status.as_str(), task.id)?;
+        insert_task_id(txn, self.kind_task_ids, task.content.kind(), task.id)?;
 
         Ok(())
     }
@@ -96,6 +130,91 @@ impl Store {
         Ok(task)
     }
 
+    /// Removes the task from the store, along with its entries in the index uid, status and kind
+    /// to task ids mappings.
+    pub fn delete(&self, txn: &mut RwTxn, task: &Task) -> Result<()> {
+        self.tasks.delete(txn, &BEU32::new(task.id))?;
+        if let Some(index_uid) = task.index_uid() {
+            remove_task_id(txn, self.index_uid_task_ids, index_uid, task.id)?;
+        }
+        remove_task_id(txn, self.status_task_ids, task.status().as_str(), task.id)?;
+        remove_task_id(txn, self.kind_task_ids, task.content.kind(), task.id)?;
+
+        Ok(())
+    }
+
+    /// Returns whether the index uid to task ids mapping matches the content of the tasks.
+    pub fn verify_index_uid_task_ids(&self, txn: &RoTxn) -> Result<bool> {
+        let expected = self.compute_index_uid_task_ids(txn)?;
+
+        let mut found = HashMap::new();
+        for result in self.index_uid_task_ids.iter(txn)? {
+            let (index_uid, tasks_set) = result?;
+            if !tasks_set.is_empty() {
+                found.insert(index_uid.to_string(), tasks_set);
+            }
+        }
+
+        Ok(expected == found)
+    }
+
+    /// Regenerates the index uid to task ids mapping from scratch, from the content of the tasks.
+    /// This is used to recover from a mapping that drifted from the tasks, after a crash for
+    /// example.
+    pub fn rebuild_index_uid_task_ids(&self, txn: &mut RwTxn) -> Result<()> {
+        let index_uid_task_ids = self.compute_index_uid_task_ids(txn)?;
+
+        self.index_uid_task_ids.clear(txn)?;
+        for (index_uid, tasks_set) in index_uid_task_ids {
+            self.index_uid_task_ids.put(txn, &index_uid, &tasks_set)?;
+        }
+
+        Ok(())
+    }
+
+    /// Regenerates the status and kind to task ids mappings from the content of the tasks.
+    fn rebuild_status_and_kind_task_ids(&self, txn: &mut RwTxn) -> Result<()> {
+        let mut status_task_ids: HashMap<&str, RoaringBitmap> = HashMap::new();
+        let mut kind_task_ids: HashMap<&str, RoaringBitmap> = HashMap::new();
+        for result in self.tasks.iter(txn)? {
+            let (_, task) = result?;
+            status_task_ids
+                .entry(task.status().as_str())
+                .or_default()
+                .insert(task.id);
+            kind_task_ids
+                .entry(task.content.kind())
+                .or_default()
+                .insert(task.id);
+        }
+
+        self.status_task_ids.clear(txn)?;
+        for (status, tasks_set) in status_task_ids {
+            self.status_task_ids.put(txn, status, &tasks_set)?;
+        }
+        self.kind_task_ids.clear(txn)?;
+        for (kind, tasks_set) in kind_task_ids {
+            self.kind_task_ids.put(txn, kind, &tasks_set)?;
+        }
+
+        Ok(())
+    }
+
+    fn compute_index_uid_task_ids(&self, txn: &RoTxn) -> Result<HashMap<String, RoaringBitmap>> {
+        let mut index_uid_task_ids: HashMap<String, RoaringBitmap> = HashMap::new();
+        for result in self.tasks.iter(txn)? {
+            let (_, task) = result?;
+            if let Some(index_uid) = task.index_uid() {
+                index_uid_task_ids
+                    .entry(index_uid.to_string())
+                    .or_default()
+                    .insert(task.id);
+            }
+        }
+
+        Ok(index_uid_task_ids)
+    }
+
     /// Returns the unfinished tasks starting from the given taskId in ascending order.
     pub fn fetch_unfinished_tasks(&self, txn: &RoTxn, from: Option<TaskId>) -> Result<Vec<Task>> {
         // We must NEVER re-enqueue an already processed task! It's content uuid would point to an unexisting file.
@@ -126,16 +245,18 @@ impl Store {
             None => self.tasks.last(txn)?.map_or(0, |(id, _)| id.get()),
         };
 
-        let filter_fn = |task: &Task| {
-            filter
-                .as_ref()
-                .and_then(|f| f.filter_fn.as_ref())
-                .map_or(true, |f| f(task))
+        let filter_fn = |task: &Task| filter.as_ref().map_or(true, |f| f.post_filter(task));
+
+        let candidates = match filter.as_ref() {
+            Some(filter) => self.compute_candidates(txn, filter)?,
+            None => None,
         };
 
-        let result: Result<Vec<_>> = match filter.as_ref().and_then(|f| f.filtered_indexes()) {
-            Some(indexes) => self
-                .compute_candidates(txn, indexes, from)?
+        let result: Result<Vec<_>> = match candidates {
+            // No task can match the filter, there is no need to load any of them.
+            Some(candidates) if candidates.is_empty() => Ok(Vec::new()),
+            Some(candidates) => self
+                .iter_candidates(txn, candidates, Some(from))
                 .filter(|result| result.as_ref().map_or(true, filter_fn))
                 .take(limit.unwrap_or(usize::MAX))
                 .collect(),
@@ -151,29 +272,259 @@ impl Store {
         result.map_err(Into::into)
     }
 
-    fn compute_candidates<'a>(
+    /// Returns the number of tasks matching the filter, along with the tasks starting from the
+    /// given taskId and going in descending order.
+    ///
+    /// Both are computed from the snapshot of the store seen by `txn`, so the total is always
+    /// consistent with the returned tasks, even if new tasks are registered in the meantime.
+    pub fn list_tasks_with_total(
+        &self,
+        txn: &RoTxn,
+        from: Option<TaskId>,
+        filter: Option<TaskFilter>,
+        limit: Option<usize>,
+    ) -> Result<(u64, Vec<Task>)> {
+        let ids = self.task_ids(txn, filter.as_ref())?;
+        let total = ids.len();
+        let tasks = self
+            .iter_candidates(txn, ids, from)
+            .take(limit.unwrap_or(usize::MAX))
+            .collect::<Result<_>>()?;
+
+        Ok((total, tasks))
+    }
+
+    /// Returns the ids of the tasks matching the filter, from the snapshot of the store seen by
+    /// `txn`. The tasks are only loaded when the filter has constraints on their content.
+    pub fn task_ids(&self, txn: &RoTxn, filter: Option<&TaskFilter>) -> Result<RoaringBitmap> {
+        let filter = match filter {
+            Some(filter) if filter.has_post_filter() => {
+                let mut ids = RoaringBitmap::new();
+                for task in self.filtered_tasks(txn, Some(filter))? {
+                    ids.insert(task?.id);
+                }
+                return Ok(ids);
+            }
+            Some(filter) => filter,
+            None => return self.all_task_ids(txn),
+        };
+
+        match self.compute_candidates(txn, filter)? {
+            // The tasks of the indexes, statuses and kinds all exist.
+            Some(candidates) if filter.restricts_by_mapping() => Ok(candidates),
+            // The filtered uids may not exist.
+            Some(candidates) => {
+                let tasks = self.tasks.lazily_decode_data();
+                let mut ids = RoaringBitmap::new();
+                for id in candidates {
+                    if tasks.get(txn, &BEU32::new(id))?.is_some() {
+                        ids.insert(id);
+                    }
+                }
+                Ok(ids)
+            }
+            None => self.all_task_ids(txn),
+        }
+    }
+
+    /// Returns the ids of all the tasks, without loading them.
+    fn all_task_ids(&self, txn: &RoTxn) -> Result<RoaringBitmap> {
+        let mut ids = RoaringBitmap::new();
+        for result in self.tasks.lazily_decode_data().iter(txn)? {
+            let (id, _) = result?;
+            ids.insert(id.get());
+        }
+
+        Ok(ids)
+    }
+
+    /// Counts the tasks matching the filter by the key `key` returns for each of them. The tasks
+    /// are read one at a time, from the snapshot of the store seen by `txn`.
+    pub fn count_tasks<K: Ord>(
+        &self,
+        txn: &RoTxn,
+        filter: Option<TaskFilter>,
+        key: impl Fn(&Task) -> K,
+    ) -> Result<BTreeMap<K, u64>> {
+        let mut counts = BTreeMap::new();
+        for task in self.filtered_tasks(txn, filter.as_ref())? {
+            *counts.entry(key(&task?)).or_default() += 1;
+        }
+
+        Ok(counts)
+    }
+
+    /// Counts the tasks matching the filter by status and kind, see `TaskContent::kind`, from the
+    /// status and kind to task ids mappings. The tasks are only loaded when the filter has
+    /// constraints on their content.
+    pub fn count_by_status_and_kind(
+        &self,
+        txn: &RoTxn,
+        filter: Option<&TaskFilter>,
+    ) -> Result<BTreeMap<(TaskStatus, &'static str), u64>> {
+        let candidates = self.task_ids(txn, filter)?;
+
+        let mut kind_task_ids = Vec::new();
+        for &kind in TaskContent::KINDS {
+            if let Some(tasks_set) = self.kind_task_ids.get(txn, kind)? {
+                kind_task_ids.push((kind, tasks_set & &candidates));
+            }
+        }
+
+        let mut counts = BTreeMap::new();
+        for status in TaskStatus::ALL {
+            let tasks_set = match self.status_task_ids.get(txn, status.as_str())? {
+                Some(tasks_set) => tasks_set & &candidates,
+                None => continue,
+            };
+            for (kind, tasks_of_kind) in &kind_task_ids {
+                let count = (&tasks_set & tasks_of_kind).len();
+                if count != 0 {
+                    counts.insert((status, *kind), count);
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// Returns the tasks matching the filter, in descending order.
+    fn filtered_tasks<'a>(
         &'a self,
         txn: &'a RoTxn,
-        indexes: &HashSet<String>,
-        from: TaskId,
-    ) -> Result<impl Iterator<Item = Result<Task>> + 'a> {
-        let mut candidates = RoaringBitmap::new();
-
-        for index_uid in indexes {
-            if let Some(tasks_set) = self.index_uid_task_ids.get(txn, index_uid)? {
-                candidates |= tasks_set;
+        filter: Option<&'a TaskFilter>,
+    ) -> Result<Box<dyn Iterator<Item = Result<Task>> + 'a>> {
+        let candidates = match filter {
+            Some(filter) => self.compute_candidates(txn, filter)?,
+            None => None,
+        };
+
+        let candidates: Box<dyn Iterator<Item = Result<Task>> + 'a> = match candidates {
+            // No task can match the filter, there is no need to load any of them.
+            Some(candidates) if candidates.is_empty() => return Ok(Box::new(std::iter::empty())),
+            Some(candidates) => Box::new(self.iter_candidates(txn, candidates, None)),
+            None => Box::new(
+                self.tasks
+                    .rev_iter(txn)?
+                    .map(|r| r.map(|(_, t)| t).map_err(Into::into)),
+            ),
+        };
+
+        Ok(Box::new(candidates.filter(move |result| {
+            result
+                .as_ref()
+                .map_or(true, |task| filter.map_or(true, |f| f.post_filter(task)))
+        })))
+    }
+
+    /// Returns the ids of the only tasks that can match the filter, according to its index,
+    /// status, kind and uid restrictions, or `None` if the filter doesn't restrict them and any
+    /// task can match.
+    fn compute_candidates(
+        &self,
+        txn: &RoTxn,
+        filter: &TaskFilter,
+    ) -> Result<Option<RoaringBitmap>> {
+        let mut candidates = match filter.filtered_indexes() {
+            Some(indexes) => {
+                let mut candidates = RoaringBitmap::new();
+                for index_uid in indexes {
+                    if let Some(tasks_set) = self.index_uid_task_ids.get(txn, index_uid)? {
+                        candidates |= tasks_set;
+                    }
+                }
+                Some(candidates)
+            }
+            None => None,
+        };
+
+        if let Some(statuses) = filter.filtered_statuses() {
+            let mut tasks_with_status = RoaringBitmap::new();
+            for status in statuses {
+                if let Some(tasks_set) = self.status_task_ids.get(txn, status.as_str())? {
+                    tasks_with_status |= tasks_set;
+                }
             }
+            candidates = match candidates {
+                Some(candidates) => Some(candidates & tasks_with_status),
+                None => Some(tasks_with_status),
+            };
         }
 
-        candidates.remove_range((Excluded(from), Unbounded));
+        if let Some(kinds) = filter.filtered_kinds() {
+            let mut tasks_of_kind = RoaringBitmap::new();
+            for kind in kinds {
+                if let Some(tasks_set) = self.kind_task_ids.get(txn, kind)? {
+                    tasks_of_kind |= tasks_set;
+                }
+            }
+            candidates = match candidates {
+                Some(candidates) => Some(candidates & tasks_of_kind),
+                None => Some(tasks_of_kind),
+            };
+        }
+
+        if let Some(uids) = filter.filtered_uids() {
+            candidates = match candidates {
+                Some(candidates) => Some(candidates & uids),
+                None => Some(uids.clone()),
+            };
+        }
+
+        Ok(candidates)
+    }
+
+    fn iter_candidates<'a>(
+        &'a self,
+        txn: &'a RoTxn,
+        mut candidates: RoaringBitmap,
+        from: Option<TaskId>,
+    ) -> impl Iterator<Item = Result<Task>> + 'a {
+        if let Some(from) = from {
+            candidates.remove_range((Excluded(from), Unbounded));
+        }
 
-        let iter = candidates
+        candidates
             .into_iter()
             .rev()
-            .filter_map(|id| self.get(txn, id).transpose());
+            .filter_map(|id| self.get(txn, id).transpose())
+    }
+}
+
+/// Adds `id` to the set of task ids of `key` in the mapping `db`.
+fn insert_task_id(
+    txn: &mut RwTxn,
+    db: Database<Str, RoaringBitmapCodec>,
+    key: &str,
+    id: TaskId,
+) -> Result<()> {
+    let mut tasks_set = db.get(txn, key)?.unwrap_or_default();
+    if tasks_set.insert(id) {
+        db.put(txn, key, &tasks_set)?;
+    }
 
-        Ok(iter)
+    Ok(())
+}
+
+/// Removes `id` from the set of task ids of `key` in the mapping `db`, along with the set if it
+/// becomes empty.
+fn remove_task_id(
+    txn: &mut RwTxn,
+    db: Database<Str, RoaringBitmapCodec>,
+    key: &str,
+    id: TaskId,
+) -> Result<()> {
+    if let Some(mut tasks_set) = db.get(txn, key)? {
+        if tasks_set.remove(id) {
+            if tasks_set.is_empty() {
+                db.delete(txn, key)?;
+            } else {
+                db.put(txn, key, &tasks_set)?;
+            }
+        }
     }
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -183,8 +534,9 @@ pub mod test {
     use milli::heed::EnvOpenOptions;
     use nelson::Mocker;
     use tempfile::TempDir;
+    use time::OffsetDateTime;
 
-    use crate::tasks::task::TaskContent;
+    use crate::tasks::task::{TaskEvent, TaskResult};
 
     use super::*;
 
@@ -204,10 +556,14 @@ pub mod test {
     }
 
     pub fn tmp_env() -> TmpEnv {
+        tmp_env_with_size(4096 * 100000)
+    }
+
+    pub fn tmp_env_with_size(map_size: usize) -> TmpEnv {
         let tmp = tempfile::tempdir().unwrap();
 
         let mut options = EnvOpenOptions::new();
-        options.map_size(4096 * 100000);
+        options.map_size(map_size);
         options.max_dbs(1000);
         let env = Arc::new(options.open(tmp.path()).unwrap());
 
@@ -254,6 +610,27 @@ pub mod test {
             }
         }
 
+        pub fn delete(&self, txn: &mut RwTxn, task: &Task) -> Result<()> {
+            match self {
+                MockStore::Real(index) => index.delete(txn, task),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
+
+        pub fn verify_index_uid_task_ids(&self, txn: &RoTxn) -> Result<bool> {
+            match self {
+                MockStore::Real(index) => index.verify_index_uid_task_ids(txn),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
+
+        pub fn rebuild_index_uid_task_ids(&self, txn: &mut RwTxn) -> Result<()> {
+            match self {
+                MockStore::Real(index) => index.rebuild_index_uid_task_ids(txn),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
+
         pub fn fetch_unfinished_tasks(
             &self,
             txn: &RoTxn,
@@ -277,6 +654,49 @@ pub mod test {
                 MockStore::Fake(_) => todo!(),
             }
         }
+
+        pub fn list_tasks_with_total(
+            &self,
+            txn: &RoTxn,
+            from: Option<TaskId>,
+            filter: Option<TaskFilter>,
+            limit: Option<usize>,
+        ) -> Result<(u64, Vec<Task>)> {
+            match self {
+                MockStore::Real(index) => index.list_tasks_with_total(txn, from, filter, limit),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
+
+        pub fn task_ids(&self, txn: &RoTxn, filter: Option<&TaskFilter>) -> Result<RoaringBitmap> {
+            match self {
+                MockStore::Real(index) => index.task_ids(txn, filter),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
+
+        pub fn count_by_status_and_kind(
+            &self,
+            txn: &RoTxn,
+            filter: Option<&TaskFilter>,
+        ) -> Result<BTreeMap<(TaskStatus, &'static str), u64>> {
+            match self {
+                MockStore::Real(index) => index.count_by_status_and_kind(txn, filter),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
+
+        pub fn count_tasks<K: Ord>(
+            &self,
+            txn: &RoTxn,
+            filter: Option<TaskFilter>,
+            key: impl Fn(&Task) -> K,
+        ) -> Result<BTreeMap<K, u64>> {
+            match self {
+                MockStore::Real(index) => index.count_tasks(txn, filter, key),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
     }
 
     #[test]
@@ -291,6 +711,8 @@ pub mod test {
                     index_uid: IndexUid::new_unchecked("test"),
                 },
                 events: vec![],
+                retry_of: None,
+                priority: 0,
             })
             .collect::<Vec<_>>();
 
@@ -323,6 +745,8 @@ pub mod test {
                 index_uid: IndexUid::new_unchecked("test"),
             },
             events: vec![],
+            retry_of: None,
+            priority: 0,
         };
 
         let task_2 = Task {
@@ -331,6 +755,8 @@ pub mod test {
                 index_uid: IndexUid::new_unchecked("test1"),
             },
             events: vec![],
+            retry_of: None,
+            priority: 0,
         };
 
         let mut txn = store.wtxn().unwrap();
@@ -353,6 +779,8 @@ pub mod test {
                 index_uid: IndexUid::new_unchecked("test"),
             },
             events: vec![],
+            retry_of: None,
+            priority: 0,
         };
         let task_2 = Task {
             id: 1,
@@ -360,6 +788,8 @@ pub mod test {
                 index_uid: IndexUid::new_unchecked("test1"),
             },
             events: vec![],
+            retry_of: None,
+            priority: 0,
         };
 
         let mut txn = store.wtxn().unwrap();
@@ -374,4 +804,409 @@ pub mod test {
         assert_eq!(tasks.len(), 1);
         assert_eq!(tasks.first().as_ref().unwrap().index_uid().unwrap(), "test");
     }
+
+    #[test]
+    fn impossible_filter_loads_no_task() {
+        let tmp = tmp_env();
+        let store = Store::new(tmp.env()).unwrap();
+
+        let gen_task = |id, index_uid| Task {
+            id,
+            content: TaskContent::IndexDeletion {
+                index_uid: IndexUid::new_unchecked(index_uid),
+            },
+            events: vec![],
+            retry_of: None,
+            priority: 0,
+        };
+
+        let mut txn = store.wtxn().unwrap();
+        store.put(&mut txn, &gen_task(0, "test")).unwrap();
+        store.put(&mut txn, &gen_task(1, "test")).unwrap();
+        store.put(&mut txn, &gen_task(2, "other")).unwrap();
+        txn.commit().unwrap();
+
+        let impossible_filter = || {
+            let mut filter = TaskFilter::default();
+            filter.filter_index("other".into());
+            filter.filter_uids([0, 1]);
+            filter.filter_fn(Box::new(|_| panic!("no task must be loaded")));
+            filter
+        };
+
+        let txn = store.rtxn().unwrap();
+        let tasks = store
+            .list_tasks(&txn, None, Some(impossible_filter()), None)
+            .unwrap();
+        assert!(tasks.is_empty());
+        let (total, tasks) = store
+            .list_tasks_with_total(&txn, None, Some(impossible_filter()), None)
+            .unwrap();
+        assert_eq!(total, 0);
+        assert!(tasks.is_empty());
+
+        let mut filter = TaskFilter::default();
+        filter.filter_index("test".into());
+        filter.filter_uids([1, 2]);
+        let tasks = store.list_tasks(&txn, None, Some(filter), None).unwrap();
+        assert_eq!(tasks, [gen_task(1, "test")]);
+    }
+
+    #[test]
+    fn list_tasks_with_total_uses_one_snapshot() {
+        let tmp = tmp_env();
+        let store = Arc::new(Store::new(tmp.env()).unwrap());
+
+        let gen_task = |id| Task {
+            id,
+            content: TaskContent::IndexDeletion {
+                index_uid: IndexUid::new_unchecked("test"),
+            },
+            events: vec![],
+            retry_of: None,
+            priority: 0,
+        };
+
+        let mut txn = store.wtxn().unwrap();
+        (0..10)
+            .try_for_each(|id| store.put(&mut txn, &gen_task(id)))
+            .unwrap();
+        txn.commit().unwrap();
+
+        let rtxn = store.rtxn().unwrap();
+
+        // Register new tasks concurrently, while the snapshot is still alive.
+        let writer = {
+            let store = store.clone();
+            std::thread::spawn(move || {
+                let mut txn = store.wtxn().unwrap();
+                (10..20)
+                    .try_for_each(|id| store.put(&mut txn, &gen_task(id)))
+                    .unwrap();
+                txn.commit().unwrap();
+            })
+        };
+        writer.join().unwrap();
+
+        let (total, tasks) = store
+            .list_tasks_with_total(&rtxn, None, None, Some(3))
+            .unwrap();
+        assert_eq!(total, 10);
+        assert_eq!(tasks.iter().map(|t| t.id).collect::<Vec<_>>(), &[9, 8, 7]);
+
+        let mut filter = TaskFilter::default();
+        filter.filter_index("test".into());
+        let (total, tasks) = store
+            .list_tasks_with_total(&rtxn, Some(4), Some(filter), Some(10))
+            .unwrap();
+        assert_eq!(total, 10);
+        assert_eq!(tasks.len(), 5);
+        drop(rtxn);
+
+        let rtxn = store.rtxn().unwrap();
+        let (total, tasks) = store
+            .list_tasks_with_total(&rtxn, None, None, Some(3))
+            .unwrap();
+        assert_eq!(total, 20);
+        assert_eq!(tasks.first().unwrap().id, 19);
+    }
+
+    #[test]
+    fn task_ids_match_the_filter() {
+        let tmp = tmp_env();
+        let store = Store::new(tmp.env()).unwrap();
+
+        let gen_task = |id, index_uid: &str| Task {
+            id,
+            content: TaskContent::IndexDeletion {
+                index_uid: IndexUid::new_unchecked(index_uid),
+            },
+            events: vec![],
+            retry_of: None,
+            priority: 0,
+        };
+
+        let mut txn = store.wtxn().unwrap();
+        for id in 0..6 {
+            let index_uid = if id % 2 == 0 { "even" } else { "odd" };
+            store.put(&mut txn, &gen_task(id, index_uid)).unwrap();
+        }
+        txn.commit().unwrap();
+
+        let txn = store.rtxn().unwrap();
+        let ids = store.task_ids(&txn, None).unwrap();
+        assert_eq!(ids.into_iter().collect::<Vec<_>>(), [0, 1, 2, 3, 4, 5]);
+
+        let mut filter = TaskFilter::default();
+        filter.filter_index("even".into());
+        filter.filter_uids([0, 2, 3, 42]);
+        filter.filter_fn(Box::new(|task| task.id != 0));
+        let ids = store.task_ids(&txn, Some(&filter)).unwrap();
+        assert_eq!(ids.into_iter().collect::<Vec<_>>(), [2]);
+    }
+
+    #[test]
+    fn task_ids_without_loading_the_tasks() {
+        let tmp = tmp_env();
+        let store = Store::new(tmp.env()).unwrap();
+
+        let gen_task = |id| Task {
+            id,
+            content: TaskContent::IndexDeletion {
+                index_uid: IndexUid::new_unchecked("test"),
+            },
+            events: vec![],
+            retry_of: None,
+            priority: 0,
+        };
+
+        let mut txn = store.wtxn().unwrap();
+        for id in 0..4 {
+            store.put(&mut txn, &gen_task(id)).unwrap();
+        }
+        // the tasks can't be deserialized anymore, so only the filters that don't need their
+        // content work.
+        for id in 0..4 {
+            store
+                .tasks
+                .remap_data_type::<Str>()
+                .put(&mut txn, &BEU32::new(id), "not a task")
+                .unwrap();
+        }
+        txn.commit().unwrap();
+
+        let txn = store.rtxn().unwrap();
+        let ids = store.task_ids(&txn, None).unwrap();
+        assert_eq!(ids.into_iter().collect::<Vec<_>>(), [0, 1, 2, 3]);
+
+        let mut filter = TaskFilter::default();
+        filter.filter_uids([1, 3, 42]);
+        let ids = store.task_ids(&txn, Some(&filter)).unwrap();
+        assert_eq!(ids.into_iter().collect::<Vec<_>>(), [1, 3]);
+
+        let mut filter = TaskFilter::default();
+        filter.filter_index("test".into());
+        filter.filter_uids([0, 2]);
+        let ids = store.task_ids(&txn, Some(&filter)).unwrap();
+        assert_eq!(ids.into_iter().collect::<Vec<_>>(), [0, 2]);
+
+        let mut filter = TaskFilter::default();
+        filter.filter_fn(Box::new(|_| true));
+        assert!(store.task_ids(&txn, Some(&filter)).is_err());
+    }
+
+    #[test]
+    fn filter_status_and_kind() {
+        let tmp = tmp_env();
+        let store = Store::new(tmp.env()).unwrap();
+
+        let index_uid = IndexUid::new_unchecked("test");
+        let deletion = |id, events| Task {
+            id,
+            content: TaskContent::IndexDeletion {
+                index_uid: index_uid.clone(),
+            },
+            events,
+            retry_of: None,
+            priority: 0,
+        };
+        let creation = Task {
+            id: 2,
+            content: TaskContent::IndexCreation {
+                index_uid: index_uid.clone(),
+                primary_key: None,
+            },
+            events: vec![TaskEvent::Created(OffsetDateTime::now_utc())],
+            retry_of: None,
+            priority: 0,
+        };
+        let enqueued = vec![TaskEvent::Created(OffsetDateTime::now_utc())];
+        let mut succeeded = enqueued.clone();
+        succeeded.push(TaskEvent::succeeded(TaskResult::Other));
+
+        let mut txn = store.wtxn().unwrap();
+        store.put(&mut txn, &deletion(0, enqueued.clone())).unwrap();
+        store.put(&mut txn, &deletion(1, enqueued)).unwrap();
+        store.put(&mut txn, &creation).unwrap();
+        // the task leaves the enqueued tasks once it is updated.
+        store.put(&mut txn, &deletion(0, succeeded)).unwrap();
+        txn.commit().unwrap();
+
+        let ids = |filter: &TaskFilter| {
+            let txn = store.rtxn().unwrap();
+            let ids = store.task_ids(&txn, Some(filter)).unwrap();
+            ids.into_iter().collect::<Vec<_>>()
+        };
+
+        let mut enqueued_filter = TaskFilter::default();
+        enqueued_filter.filter_status(TaskStatus::Enqueued);
+        assert_eq!(ids(&enqueued_filter), [1, 2]);
+
+        let mut filter = TaskFilter::default();
+        filter.filter_status(TaskStatus::Succeeded);
+        filter.filter_status(TaskStatus::Enqueued);
+        filter.filter_kind("IndexDeletion");
+        assert_eq!(ids(&filter), [0, 1]);
+
+        let mut filter = TaskFilter::default();
+        filter.filter_kind("IndexCreation");
+        assert_eq!(ids(&filter), [2]);
+
+        let txn = store.rtxn().unwrap();
+        let counts = store.count_by_status_and_kind(&txn, None).unwrap();
+        let expected = [
+            ((TaskStatus::Enqueued, "IndexDeletion"), 1),
+            ((TaskStatus::Enqueued, "IndexCreation"), 1),
+            ((TaskStatus::Succeeded, "IndexDeletion"), 1),
+        ];
+        assert_eq!(counts, BTreeMap::from(expected));
+        let counts = store
+            .count_by_status_and_kind(&txn, Some(&enqueued_filter))
+            .unwrap();
+        assert_eq!(counts.values().sum::<u64>(), 2);
+        drop(txn);
+
+        let mut txn = store.wtxn().unwrap();
+        store.delete(&mut txn, &creation).unwrap();
+        txn.commit().unwrap();
+        assert_eq!(ids(&enqueued_filter), [1]);
+        assert!(ids(&filter).is_empty());
+
+        // the mappings are computed again for the stores created before they existed.
+        let mut txn = store.wtxn().unwrap();
+        store.status_task_ids.clear(&mut txn).unwrap();
+        store.kind_task_ids.clear(&mut txn).unwrap();
+        txn.commit().unwrap();
+        let store = Store::new(tmp.env()).unwrap();
+        let txn = store.rtxn().unwrap();
+        let mut filter = TaskFilter::default();
+        filter.filter_status(TaskStatus::Succeeded);
+        filter.filter_kind("IndexDeletion");
+        let ids = store.task_ids(&txn, Some(&filter)).unwrap();
+        assert_eq!(ids.into_iter().collect::<Vec<_>>(), [0]);
+    }
+
+    #[test]
+    fn filter_index_lifecycle() {
+        let tmp = tmp_env();
+        let store = Store::new(tmp.env()).unwrap();
+
+        let index_uid = IndexUid::new_unchecked("test");
+        let gen_task = |id, content, succeeded| Task {
+            id,
+            content,
+            events: if succeeded {
+                vec![TaskEvent::succeeded(TaskResult::Other)]
+            } else {
+                vec![TaskEvent::Created(OffsetDateTime::now_utc())]
+            },
+            retry_of: None,
+            priority: 0,
+        };
+        let creation = TaskContent::IndexCreation {
+            index_uid: index_uid.clone(),
+            primary_key: None,
+        };
+        let deletion = TaskContent::IndexDeletion {
+            index_uid: index_uid.clone(),
+        };
+        let update = TaskContent::IndexUpdate {
+            index_uid,
+            primary_key: None,
+        };
+
+        let mut txn = store.wtxn().unwrap();
+        store
+            .put(&mut txn, &gen_task(0, creation.clone(), true))
+            .unwrap();
+        store.put(&mut txn, &gen_task(1, creation, false)).unwrap();
+        store.put(&mut txn, &gen_task(2, update, true)).unwrap();
+        store
+            .put(&mut txn, &gen_task(3, deletion.clone(), false))
+            .unwrap();
+        store.put(&mut txn, &gen_task(4, deletion, true)).unwrap();
+        txn.commit().unwrap();
+
+        let txn = store.rtxn().unwrap();
+        let mut filter = TaskFilter::default();
+        filter.only_index_created();
+        let ids = store.task_ids(&txn, Some(&filter)).unwrap();
+        assert_eq!(ids.into_iter().collect::<Vec<_>>(), [0]);
+
+        let mut filter = TaskFilter::default();
+        filter.only_index_deleted();
+        let tasks = store.list_tasks(&txn, None, Some(filter), None).unwrap();
+        assert_eq!(tasks.iter().map(|t| t.id).collect::<Vec<_>>(), [4]);
+
+        // the lifecycle is combined with the other constraints of the filter.
+        let mut filter = TaskFilter::default();
+        filter.only_index_created();
+        filter.filter_uids([1, 2, 3, 4]);
+        let ids = store.task_ids(&txn, Some(&filter)).unwrap();
+        assert!(ids.is_empty());
+    }
+
+    #[test]
+    fn rebuild_corrupted_index_uid_task_ids() {
+        let tmp = tmp_env();
+        let store = Store::new(tmp.env()).unwrap();
+
+        let gen_task = |id, index_uid| Task {
+            id,
+            content: TaskContent::IndexDeletion {
+                index_uid: IndexUid::new_unchecked(index_uid),
+            },
+            events: vec![],
+            retry_of: None,
+            priority: 0,
+        };
+
+        let mut txn = store.wtxn().unwrap();
+        store.put(&mut txn, &gen_task(0, "test")).unwrap();
+        store.put(&mut txn, &gen_task(1, "test")).unwrap();
+        store.put(&mut txn, &gen_task(2, "other")).unwrap();
+        let dump = Task {
+            id: 3,
+            content: TaskContent::Dump {
+                uid: "dump".to_string(),
+                indexes: None,
+            },
+            events: vec![],
+            retry_of: None,
+            priority: 0,
+        };
+        store.put(&mut txn, &dump).unwrap();
+        assert!(store.verify_index_uid_task_ids(&txn).unwrap());
+
+        // task 1 goes missing from its index, and task 0 is attributed to the wrong index.
+        let tasks_set: RoaringBitmap = [0].into_iter().collect();
+        store
+            .index_uid_task_ids
+            .put(&mut txn, "test", &tasks_set)
+            .unwrap();
+        let tasks_set: RoaringBitmap = [0, 2].into_iter().collect();
+        store
+            .index_uid_task_ids
+            .put(&mut txn, "other", &tasks_set)
+            .unwrap();
+        assert!(!store.verify_index_uid_task_ids(&txn).unwrap());
+
+        let mut filter = TaskFilter::default();
+        filter.filter_index("test".into());
+        let tasks = store.list_tasks(&txn, None, Some(filter), None).unwrap();
+        assert_eq!(tasks.iter().map(|t| t.id).collect::<Vec<_>>(), &[0]);
+
+        store.rebuild_index_uid_task_ids(&mut txn).unwrap();
+        assert!(store.verify_index_uid_task_ids(&txn).unwrap());
+
+        let mut filter = TaskFilter::default();
+        filter.filter_index("test".into());
+        let tasks = store.list_tasks(&txn, None, Some(filter), None).unwrap();
+        assert_eq!(tasks.iter().map(|t| t.id).collect::<Vec<_>>(), &[1, 0]);
+
+        let mut filter = TaskFilter::default();
+        filter.filter_index("other".into());
+        let tasks = store.list_tasks(&txn, None, Some(filter), None).unwrap();
+        assert_eq!(tasks.iter().map(|t| t.id).collect::<Vec<_>>(), &[2]);
+    }
 }
@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use time::OffsetDateTime;
 use tokio::sync::{watch, RwLock};
+use tracing::Instrument;
 
 use super::batch::Batch;
 use super::error::Result;
@@ -38,7 +39,8 @@ impl UpdateLoop {
                 break;
             }
 
-            if let Err(e) = self.process_next_batch().await {
+            let tick = tracing::debug_span!("tick");
+            if let Err(e) = self.process_next_batch().instrument(tick).await {
                 log::error!("an error occurred while processing an update batch: {}", e);
             }
         }
@@ -65,7 +67,15 @@ impl UpdateLoop {
                 .await?
         };
 
-        let batch = performer.process_batch(batch).await;
+        let span = tracing::info_span!(
+            "process_batch",
+            batch_id = tracing::field::Empty,
+            task_ids = ?batch.content.task_ids(),
+        );
+        if let Some(id) = batch.id {
+            span.record("batch_id", &id);
+        }
+        let batch = performer.process_batch(batch).instrument(span).await;
 
         self.handle_batch_result(batch, performer).await?;
 
@@ -84,7 +94,7 @@ impl UpdateLoop {
     ) -> Result<()> {
         let mut scheduler = self.scheduler.write().await;
         let content = scheduler.update_tasks(batch.content).await?;
-        scheduler.finish();
+        scheduler.finish(&content);
         drop(scheduler);
         batch.content = content;
         performer.finish(&batch).await;
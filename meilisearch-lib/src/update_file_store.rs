@@ -1,10 +1,12 @@
 use std::fs::{create_dir_all, File};
-use std::io::{self, BufReader, BufWriter, Write};
+use std::hash::Hasher;
+use std::io::{self, BufReader, BufWriter, Read, Write};
 use std::ops::{Deref, DerefMut};
 use std::path::{Path, PathBuf};
 
 use milli::documents::DocumentsBatchReader;
 use serde_json::Map;
+use siphasher::sip128::{Hasher128, SipHasher};
 use tempfile::{NamedTempFile, PersistError};
 use uuid::Uuid;
 
@@ -100,7 +102,7 @@ mod store {
                     .ok_or_else(|| anyhow::anyhow!("invalid update file name"))?;
                 let dst_path = dst_update_files_path.join(file_uuid);
                 let dst_file = BufWriter::new(File::create(dst_path)?);
-                read_ndjson(update_file, dst_file)?;
+                read_ndjson(update_file, dst_file, None)?;
             }
 
             Ok(())
@@ -123,6 +125,12 @@ mod store {
             Ok((uuid, update_file))
         }
 
+        /// Creates an anonymous file in the update files directory, to write a payload in while
+        /// it is received. The file is removed as soon as it is dropped.
+        pub fn new_payload_file(&self) -> Result<File> {
+            Ok(tempfile::tempfile_in(&self.path)?)
+        }
+
         /// Returns the file corresponding to the requested uuid.
         pub fn get_update(&self, uuid: Uuid) -> Result<File> {
             let path = self.path.join(uuid.to_string());
@@ -130,6 +138,15 @@ mod store {
             Ok(file)
         }
 
+        /// Copies the update file pointed to by `uuid` to a new persisted update file, and returns
+        /// the uuid of the copy.
+        pub fn duplicate(&self, uuid: Uuid) -> Result<Uuid> {
+            let (copy_uuid, mut copy) = self.new_update()?;
+            io::copy(&mut self.get_update(uuid)?, &mut *copy)?;
+            copy.persist()?;
+            Ok(copy_uuid)
+        }
+
         /// Copies the content of the update file pointed to by `uuid` to the `dst` directory.
         pub fn snapshot(&self, uuid: Uuid, dst: impl AsRef<Path>) -> Result<()> {
             let src = self.path.join(uuid.to_string());
@@ -178,6 +195,37 @@ mod store {
             Ok(self.get_update(uuid)?.metadata()?.len())
         }
 
+        /// Returns a hash of the content of the update file pointed to by `uuid`.
+        pub fn hash(&self, uuid: Uuid) -> Result<u128> {
+            let mut file = BufReader::new(self.get_update(uuid)?);
+            let mut hasher = SipHasher::new();
+            let mut buffer = [0; 8192];
+            loop {
+                match file.read(&mut buffer)? {
+                    0 => break,
+                    n => hasher.write(&buffer[..n]),
+                }
+            }
+
+            Ok(hasher.finish128().as_u128())
+        }
+
+        /// Returns the uuid and the size of every persisted update file.
+        pub fn list(&self) -> Result<Vec<(Uuid, u64)>> {
+            let mut files = Vec::new();
+            for entry in std::fs::read_dir(&self.path)? {
+                let entry = entry?;
+                // Update files that are not persisted yet are not named after their uuid.
+                let uuid = match entry.file_name().to_str().map(Uuid::parse_str) {
+                    Some(Ok(uuid)) => uuid,
+                    _ => continue,
+                };
+                files.push((uuid, entry.metadata()?.len()));
+            }
+
+            Ok(files)
+        }
+
         pub async fn delete(&self, uuid: Uuid) -> Result<()> {
             let path = self.path.join(uuid.to_string());
             tokio::fs::remove_file(path).await?;
@@ -220,6 +268,13 @@ mod test {
             }
         }
 
+        pub fn new_payload_file(&self) -> Result<File> {
+            match self {
+                MockUpdateFileStore::Real(s) => s.new_payload_file(),
+                MockUpdateFileStore::Mock(_) => todo!(),
+            }
+        }
+
         pub fn get_update(&self, uuid: Uuid) -> Result<File> {
             match self {
                 MockUpdateFileStore::Real(s) => s.get_update(uuid),
@@ -241,6 +296,13 @@ mod test {
             }
         }
 
+        pub fn duplicate(&self, uuid: Uuid) -> Result<Uuid> {
+            match self {
+                MockUpdateFileStore::Real(s) => s.duplicate(uuid),
+                MockUpdateFileStore::Mock(m) => unsafe { m.get("duplicate").call(uuid) },
+            }
+        }
+
         pub fn get_size(&self, uuid: Uuid) -> Result<u64> {
             match self {
                 MockUpdateFileStore::Real(s) => s.get_size(uuid),
@@ -248,6 +310,20 @@ mod test {
             }
         }
 
+        pub fn hash(&self, uuid: Uuid) -> Result<u128> {
+            match self {
+                MockUpdateFileStore::Real(s) => s.hash(uuid),
+                MockUpdateFileStore::Mock(mocker) => unsafe { mocker.get("hash").call(uuid) },
+            }
+        }
+
+        pub fn list(&self) -> Result<Vec<(Uuid, u64)>> {
+            match self {
+                MockUpdateFileStore::Real(s) => s.list(),
+                MockUpdateFileStore::Mock(mocker) => unsafe { mocker.get("list").call(()) },
+            }
+        }
+
         pub async fn delete(&self, uuid: Uuid) -> Result<()> {
             match self {
                 MockUpdateFileStore::Real(s) => s.delete(uuid).await,
@@ -0,0 +1,117 @@
+use serde_json::{Map, Value};
+
+pub type Document = Map<String, Value>;
+
+/// Flattens the nested objects and arrays of `doc`, so that every value of the returned document
+/// is a leaf of the original one. The flattening rules are:
+///
+/// - a field of a nested object is keyed by the key of the object and its own key, joined by a
+///   dot: `{ "a": { "b": 1 } }` gives `{ "a.b": 1 }`;
+/// - an element of an array is keyed by the key of the array and its index, joined by a dot:
+///   `{ "a": [1, 2] }` gives `{ "a.0": 1, "a.1": 2 }`;
+/// - these rules apply recursively, so objects in arrays and arrays in objects are flattened as
+///   well: `{ "a": [{ "b": 1 }] }` gives `{ "a.0.b": 1 }`;
+/// - empty objects and empty arrays are kept as is, so that no field is lost:
+///   `{ "a": {}, "b": [] }` is left unchanged;
+/// - the other values, including `null`, are kept as is.
+///
+/// The keys are never escaped, so a key that already contains a dot may collide with a flattened
+/// key, e.g. `{ "a.b": 1, "a": { "b": 2 } }`, in which case the value that comes last in the
+/// iteration order of `doc` is kept.
+pub fn flatten_document(doc: &Document) -> Document {
+    let mut flattened = Document::new();
+    for (key, value) in doc {
+        flatten_value(key.clone(), value, &mut flattened);
+    }
+    flattened
+}
+
+fn flatten_value(key: String, value: &Value, flattened: &mut Document) {
+    match value {
+        Value::Object(object) if !object.is_empty() => {
+            for (field, value) in object {
+                flatten_value(format!("{}.{}", key, field), value, flattened);
+            }
+        }
+        Value::Array(array) if !array.is_empty() => {
+            for (index, value) in array.iter().enumerate() {
+                flatten_value(format!("{}.{}", key, index), value, flattened);
+            }
+        }
+        _ => {
+            flattened.insert(key, value.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn flatten(doc: Value) -> Value {
+        match doc {
+            Value::Object(doc) => Value::Object(flatten_document(&doc)),
+            _ => panic!("a document must be an object"),
+        }
+    }
+
+    #[test]
+    fn flatten_nested_objects() {
+        assert_eq!(
+            flatten(json!({
+                "id": 1,
+                "author": { "name": "kefir", "address": { "city": "Paris", "zip": null } },
+            })),
+            json!({
+                "id": 1,
+                "author.name": "kefir",
+                "author.address.city": "Paris",
+                "author.address.zip": null,
+            })
+        );
+    }
+
+    #[test]
+    fn flatten_arrays() {
+        assert_eq!(
+            flatten(json!({
+                "tags": ["good", "boy"],
+                "matrix": [[1, 2], [3]],
+            })),
+            json!({
+                "tags.0": "good",
+                "tags.1": "boy",
+                "matrix.0.0": 1,
+                "matrix.0.1": 2,
+                "matrix.1.0": 3,
+            })
+        );
+    }
+
+    #[test]
+    fn flatten_mixed_structures() {
+        assert_eq!(
+            flatten(json!({
+                "id": "doggo",
+                "owners": [{ "name": "tamo", "pets": ["kefir"] }, { "name": "marin", "pets": [] }],
+                "meta": { "labels": [{ "color": "red" }], "extra": {} },
+            })),
+            json!({
+                "id": "doggo",
+                "owners.0.name": "tamo",
+                "owners.0.pets.0": "kefir",
+                "owners.1.name": "marin",
+                "owners.1.pets": [],
+                "meta.labels.0.color": "red",
+                "meta.extra": {},
+            })
+        );
+    }
+
+    #[test]
+    fn flatten_flat_document() {
+        let doc = json!({ "id": 1, "name": "kefir", "good": true, "nothing": null });
+        assert_eq!(flatten(doc.clone()), doc);
+    }
+}
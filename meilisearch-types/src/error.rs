@@ -120,6 +120,8 @@ pub enum Code {
     IndexAlreadyExists,
     IndexNotFound,
     InvalidIndexUid,
+    IndexBusy,
+    IndexationAborted,
     InvalidMinWordLengthForTypo,
 
     // invalid state error
@@ -138,6 +140,8 @@ pub enum Code {
     BadRequest,
     DatabaseSizeLimitReached,
     DocumentNotFound,
+    TotalDocumentsQuotaExceeded,
+    TooManyDocumentFields,
     Internal,
     InvalidGeoField,
     InvalidRankingRule,
@@ -147,6 +151,7 @@ pub enum Code {
     NoSpaceLeftOnDevice,
     DumpNotFound,
     TaskNotFound,
+    WriteNotApplied,
     PayloadTooLarge,
     RetrieveDocument,
     SearchDocuments,
@@ -157,6 +162,8 @@ pub enum Code {
 
     InvalidContentType,
     MissingContentType,
+    InvalidCsvDelimiter,
+    InvalidCsvQuote,
     MalformedPayload,
     MissingPayload,
 
@@ -187,6 +194,10 @@ impl Code {
             // thrown when requesting an unexisting index
             IndexNotFound => ErrCode::invalid("index_not_found", StatusCode::NOT_FOUND),
             InvalidIndexUid => ErrCode::invalid("invalid_index_uid", StatusCode::BAD_REQUEST),
+            // thrown when an immediate operation targets an index with an in-flight batch
+            IndexBusy => ErrCode::invalid("index_busy", StatusCode::CONFLICT),
+            // thrown when the indexation of a batch is interrupted by the cancelation of its tasks
+            IndexationAborted => ErrCode::invalid("indexation_aborted", StatusCode::CONFLICT),
 
             // invalid state error
             InvalidState => ErrCode::internal("invalid_state", StatusCode::INTERNAL_SERVER_ERROR),
@@ -225,6 +236,12 @@ impl Code {
                 StatusCode::INTERNAL_SERVER_ERROR,
             ),
             DocumentNotFound => ErrCode::invalid("document_not_found", StatusCode::NOT_FOUND),
+            TotalDocumentsQuotaExceeded => {
+                ErrCode::invalid("total_documents_quota_exceeded", StatusCode::BAD_REQUEST)
+            }
+            TooManyDocumentFields => {
+                ErrCode::invalid("too_many_document_fields", StatusCode::BAD_REQUEST)
+            }
             Internal => ErrCode::internal("internal", StatusCode::INTERNAL_SERVER_ERROR),
             InvalidGeoField => ErrCode::invalid("invalid_geo_field", StatusCode::BAD_REQUEST),
             InvalidToken => ErrCode::authentication("invalid_api_key", StatusCode::FORBIDDEN),
@@ -232,6 +249,8 @@ impl Code {
                 ErrCode::authentication("missing_authorization_header", StatusCode::UNAUTHORIZED)
             }
             TaskNotFound => ErrCode::invalid("task_not_found", StatusCode::NOT_FOUND),
+            // thrown when waiting for the write of a task that failed or was canceled
+            WriteNotApplied => ErrCode::invalid("write_not_applied", StatusCode::CONFLICT),
             DumpNotFound => ErrCode::invalid("dump_not_found", StatusCode::NOT_FOUND),
             NoSpaceLeftOnDevice => {
                 ErrCode::internal("no_space_left_on_device", StatusCode::INTERNAL_SERVER_ERROR)
@@ -255,6 +274,10 @@ impl Code {
             MissingContentType => {
                 ErrCode::invalid("missing_content_type", StatusCode::UNSUPPORTED_MEDIA_TYPE)
             }
+            InvalidCsvDelimiter => {
+                ErrCode::invalid("invalid_csv_delimiter", StatusCode::BAD_REQUEST)
+            }
+            InvalidCsvQuote => ErrCode::invalid("invalid_csv_quote", StatusCode::BAD_REQUEST),
             MalformedPayload => ErrCode::invalid("malformed_payload", StatusCode::BAD_REQUEST),
             InvalidContentType => {
                 ErrCode::invalid("invalid_content_type", StatusCode::UNSUPPORTED_MEDIA_TYPE)
@@ -1,3 +1,6 @@
+pub mod document;
 pub mod error;
 pub mod index_uid;
 pub mod star_or;
+
+pub use document::{flatten_document, Document};
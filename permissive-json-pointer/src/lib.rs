@@ -167,12 +167,14 @@ fn create_value(value: &Document, mut selectors: HashSet<&str>) -> Document {
         // first we insert all the key at the root level
         if selectors.contains(key as &str) {
             new_value.insert(key.to_string(), value.clone());
-            // if the key was simple we can delete it and move to
-            // the next key
+            // if the key was simple we can delete it, a dotted key must
+            // stay to select the nested fields of the next keys
             if is_simple(key) {
                 selectors.remove(key as &str);
-                continue;
             }
+            // the whole value is selected, the sub selectors can't add
+            // anything to it
+            continue;
         }
 
         // we extract all the sub selectors matching the current field
@@ -644,6 +646,125 @@ mod tests {
         );
     }
 
+    #[test]
+    fn deep_nested_in_arrays_of_objects() {
+        let value: Value = json!({
+            "author": {
+                "name": "kefir",
+                "books": [
+                    {
+                        "title": "the guide",
+                        "editions": [
+                            { "year": 2020, "publisher": { "name": "dog press", "city": "paris" } },
+                            { "year": 2022, "publisher": { "name": "cat press", "city": "lyon" } },
+                        ]
+                    },
+                    { "title": "the sequel", "editions": [[{ "year": 2023 }]] },
+                    "not an object",
+                ]
+            }
+        });
+        let value: &Document = value.as_object().unwrap();
+
+        let res: Value = select_values(value, vec!["author.books.title"]).into();
+        assert_eq!(
+            res,
+            json!({
+                "author": {
+                    "books": [
+                        { "title": "the guide" },
+                        { "title": "the sequel" },
+                    ]
+                }
+            })
+        );
+
+        let res: Value = select_values(
+            value,
+            vec!["author.name", "author.books.editions.publisher.name"],
+        )
+        .into();
+        assert_eq!(
+            res,
+            json!({
+                "author": {
+                    "name": "kefir",
+                    "books": [
+                        {
+                            "editions": [
+                                { "publisher": { "name": "dog press" } },
+                                { "publisher": { "name": "cat press" } },
+                            ]
+                        },
+                    ]
+                }
+            })
+        );
+
+        let res: Value = select_values(value, vec!["author.books.editions.year"]).into();
+        assert_eq!(
+            res,
+            json!({
+                "author": {
+                    "books": [
+                        { "editions": [{ "year": 2020 }, { "year": 2022 }] },
+                        { "editions": [[{ "year": 2023 }]] },
+                    ]
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn dotted_key_with_sub_selectors() {
+        let value: Value = json!({
+            "a.b": {
+                "c": 1,
+                "d": 2,
+            },
+            "a": {
+                "b": {
+                    "c": 3,
+                    "d": 4,
+                }
+            }
+        });
+        let value: &Document = value.as_object().unwrap();
+
+        // selecting the whole dotted key must not be narrowed by its sub selectors.
+        let res: Value = select_values(value, vec!["a.b", "a.b.c"]).into();
+        assert_eq!(
+            res,
+            json!({
+                "a.b": {
+                    "c": 1,
+                    "d": 2,
+                },
+                "a": {
+                    "b": {
+                        "c": 3,
+                        "d": 4,
+                    }
+                }
+            })
+        );
+
+        let res: Value = select_values(value, vec!["a.b.c"]).into();
+        assert_eq!(
+            res,
+            json!({
+                "a.b": {
+                    "c": 1,
+                },
+                "a": {
+                    "b": {
+                        "c": 3,
+                    }
+                }
+            })
+        );
+    }
+
     #[test]
     fn all_conflict_variation() {
         let value: Value = json!({